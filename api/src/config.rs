@@ -0,0 +1,701 @@
+use super::routes::utils::{ComputePool, DEFAULT_QUEUE_TIMEOUT};
+use clap::{Parser, ValueEnum};
+use log::LevelFilter;
+use serde::Deserialize;
+use std::{
+    error::Error,
+    fmt::{self, Display, Formatter},
+    fs,
+    net::{IpAddr, Ipv4Addr},
+    path::{Path, PathBuf},
+    str::FromStr,
+    time::Duration,
+};
+use vault_graph::{DataSource, GraphConfig, DEFAULT_DUMP_URL};
+use warp::{cors::Builder, http::uri::Authority};
+
+/// The port listened on when none is configured.
+pub const DEFAULT_PORT: u16 = 8080;
+
+/// The origin allowed to make cross-origin requests when none are configured.
+pub const DEFAULT_CORS_ORIGIN: &str = "https://vault.arpanlaha.com";
+
+/// The number of most downloaded crates whose default dependency graphs are cached at startup by default.
+pub const DEFAULT_WARM_CACHE_CRATES: usize = 100;
+
+/// The environment variable naming the configuration file, if `--config` is not passed.
+pub const CONFIG_PATH_VAR: &str = "VAULT_CONFIG";
+
+/// The kind of location to load the crates.io database dump from.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum DataSourceKind {
+    /// A tarballed database dump downloaded from `data.url`.
+    Remote,
+
+    /// A tarballed database dump on the local filesystem at `data.path`.
+    Dump,
+
+    /// An unpacked database dump `data` directory on the local filesystem at `data.path`.
+    Snapshot,
+}
+
+impl FromStr for DataSourceKind {
+    type Err = String;
+
+    fn from_str(kind: &str) -> Result<Self, Self::Err> {
+        <Self as ValueEnum>::from_str(kind, true)
+    }
+}
+
+/// The command-line arguments of the Vault API.
+///
+/// Each flag overrides the corresponding environment variable and configuration file setting.
+#[derive(Debug, Default, Parser)]
+#[command(version, about, long_about = None)]
+pub struct Args {
+    /// The port to listen on, kept for compatibility with earlier versions (prefer `--port`).
+    #[arg(value_name = "PORT", conflicts_with = "port")]
+    pub legacy_port: Option<u16>,
+
+    /// The TOML configuration file to read [env: `VAULT_CONFIG`].
+    #[arg(short, long, value_name = "FILE")]
+    pub config: Option<PathBuf>,
+
+    /// The address to listen on [env: `VAULT_ADDRESS`] [default: 0.0.0.0].
+    #[arg(long, value_name = "IP")]
+    pub address: Option<IpAddr>,
+
+    /// Listen on 127.0.0.1 only, shorthand for `--address 127.0.0.1`.
+    #[arg(short, long, conflicts_with = "address")]
+    pub local: bool,
+
+    /// The port to listen on [env: `VAULT_PORT`] [default: 8080].
+    #[arg(short, long)]
+    pub port: Option<u16>,
+
+    /// An origin allowed to make cross-origin requests, or `*` for any; may be repeated [env: `VAULT_CORS_ORIGINS`].
+    #[arg(long = "cors-origin", value_name = "ORIGIN")]
+    pub cors_origins: Vec<String>,
+
+    /// The log level, one of `off`, `error`, `warn`, `info`, `debug` or `trace` [env: `VAULT_LOG_LEVEL`].
+    #[arg(long, value_name = "LEVEL")]
+    pub log_level: Option<String>,
+
+    /// The kind of location to load the database dump from [env: `VAULT_DATA_SOURCE`] [default: remote].
+    #[arg(long, value_enum, value_name = "KIND")]
+    pub data_source: Option<DataSourceKind>,
+
+    /// The URL of the database dump, for the `remote` data source [env: `VAULT_DATA_URL`].
+    #[arg(long, value_name = "URL")]
+    pub data_url: Option<String>,
+
+    /// The path of the database dump, for the `dump` and `snapshot` data sources [env: `VAULT_DATA_PATH`].
+    #[arg(long, value_name = "PATH")]
+    pub data_path: Option<PathBuf>,
+
+    /// The time between reloads of the database dump, which is never reloaded if unset; each reload holds a second
+    /// graph in memory until it replaces the served one [env: `VAULT_REFRESH_INTERVAL_SECS`].
+    #[arg(long, value_name = "SECONDS")]
+    pub refresh_interval_secs: Option<u64>,
+
+    /// The target triple used when a query does not specify one [env: `VAULT_DEFAULT_TARGET`].
+    #[arg(long, value_name = "TRIPLE")]
+    pub default_target: Option<String>,
+
+    /// The additional cfg name enabled when a query does not specify one [env: `VAULT_DEFAULT_CFG_NAME`].
+    #[arg(long, value_name = "NAME")]
+    pub default_cfg_name: Option<String>,
+
+    /// The max number of dependency graphs to cache, where `0` disables caching [env: `VAULT_CACHE_CAPACITY`].
+    #[arg(long, value_name = "COUNT")]
+    pub cache_capacity: Option<usize>,
+
+    /// The number of most downloaded crates to cache dependency graphs of at startup
+    /// [env: `VAULT_WARM_CACHE_CRATES`].
+    #[arg(long, value_name = "COUNT")]
+    pub warm_cache_crates: Option<usize>,
+
+    /// The max number of graph computations to run at once [env: `VAULT_COMPUTE_CONCURRENCY`].
+    #[arg(long, value_name = "COUNT")]
+    pub compute_concurrency: Option<usize>,
+
+    /// The time a graph computation may wait for a free slot [env: `VAULT_QUEUE_TIMEOUT_SECS`].
+    #[arg(long, value_name = "SECONDS")]
+    pub queue_timeout_secs: Option<u64>,
+}
+
+/// The validated configuration of the Vault API.
+#[derive(Clone, Debug)]
+pub struct Config {
+    /// The address to listen on.
+    pub address: IpAddr,
+
+    /// The port to listen on.
+    pub port: u16,
+
+    /// The origins allowed to make cross-origin requests, where `*` allows any origin.
+    pub cors_origins: Vec<String>,
+
+    /// The max level of log messages to emit.
+    pub log_level: LevelFilter,
+
+    /// The `GraphConfig` specifying the database dump to load and the resolution defaults.
+    pub graph: GraphConfig,
+
+    /// The time between reloads of the database dump, if it should be reloaded.
+    ///
+    /// The previous graph is kept in memory and served until a reload finishes, and kept if it fails.
+    pub refresh_interval: Option<Duration>,
+
+    /// The number of most downloaded crates to cache dependency graphs of at startup.
+    pub warm_cache_crates: usize,
+
+    /// The max number of graph computations to run at once.
+    pub compute_concurrency: usize,
+
+    /// The time a graph computation may wait for a free slot before being rejected.
+    pub queue_timeout: Duration,
+}
+
+impl Config {
+    /// Loads the configuration from defaults, a configuration file, environment variables and command-line
+    /// arguments, in increasing order of precedence.
+    ///
+    /// The configuration file is read from `--config`, or else the `VAULT_CONFIG` environment variable, if either
+    /// is set.
+    ///
+    /// # Arguments
+    /// * `args` - the parsed command-line arguments.
+    /// * `env` - a function returning the value of an environment variable, if set.
+    ///
+    /// # Errors
+    /// * Returns a `ConfigError` describing every problem found if the configuration cannot be read or is invalid.
+    pub fn load(args: Args, env: impl Fn(&str) -> Option<String>) -> Result<Self, ConfigError> {
+        let mut problems = vec![];
+
+        let file_settings = args
+            .config
+            .clone()
+            .or_else(|| env(CONFIG_PATH_VAR).map(PathBuf::from))
+            .map_or_else(Settings::default, |config_path| {
+                Settings::from_file(&config_path, &mut problems)
+            });
+
+        let settings = file_settings
+            .merge(Settings::from_env(&env, &mut problems))
+            .merge(Settings::from(args));
+
+        let config = Self::resolve(settings, &mut problems);
+
+        if problems.is_empty() {
+            Ok(config)
+        } else {
+            Err(ConfigError { problems })
+        }
+    }
+
+    /// Returns a `ComputePool` with the configured concurrency and queue timeout.
+    #[must_use]
+    pub fn compute_pool(&self) -> ComputePool {
+        ComputePool::new(self.compute_concurrency, self.queue_timeout)
+    }
+
+    /// Returns a CORS filter builder allowing the configured origins.
+    #[must_use]
+    pub fn cors(&self) -> Builder {
+        if self.cors_origins.iter().any(|origin| origin == "*") {
+            warp::cors().allow_any_origin()
+        } else {
+            warp::cors().allow_origins(self.cors_origins.iter().map(String::as_str))
+        }
+    }
+
+    /// Fills in defaults for unset settings and checks the result, recording any problems found.
+    ///
+    /// # Arguments
+    /// * `settings` - the merged `Settings`.
+    /// * `problems` - the list of problems to append to.
+    fn resolve(settings: Settings, problems: &mut Vec<String>) -> Self {
+        let cors_origins = settings
+            .cors_origins
+            .unwrap_or_else(|| vec![String::from(DEFAULT_CORS_ORIGIN)]);
+
+        for origin in &cors_origins {
+            if !is_valid_origin(origin) {
+                problems.push(format!(
+                    "`server.cors_origins`: `{origin}` is not `*` or an origin such as `https://example.com`"
+                ));
+            }
+        }
+
+        let log_level = settings.log_level.map_or(LevelFilter::Info, |log_level| {
+            LevelFilter::from_str(&log_level).unwrap_or_else(|_| {
+                problems.push(format!(
+                    "`server.log_level`: `{log_level}` is not one of `off`, `error`, `warn`, `info`, `debug` or `trace`"
+                ));
+                LevelFilter::Info
+            })
+        });
+
+        let source = get_data_source(
+            settings.data_source,
+            settings.data_url,
+            settings.data_path,
+            problems,
+        );
+
+        let refresh_interval = settings.refresh_interval_secs.map(|seconds| {
+            if seconds == 0 {
+                problems.push(String::from(
+                    "`data.refresh_interval_secs` must be positive; leave it unset to never refresh",
+                ));
+            }
+
+            Duration::from_secs(seconds)
+        });
+
+        let default_pool = ComputePool::default();
+
+        let compute_concurrency = settings
+            .compute_concurrency
+            .unwrap_or_else(|| default_pool.concurrency());
+
+        if compute_concurrency == 0 {
+            problems.push(String::from("`compute.concurrency` must be at least 1"));
+        }
+
+        let queue_timeout = settings
+            .queue_timeout_secs
+            .map_or(DEFAULT_QUEUE_TIMEOUT, Duration::from_secs);
+
+        if queue_timeout.is_zero() {
+            problems.push(String::from(
+                "`compute.queue_timeout_secs` must be positive",
+            ));
+        }
+
+        let graph_defaults = GraphConfig::default();
+
+        let graph = GraphConfig {
+            source,
+            default_target: settings
+                .default_target
+                .unwrap_or(graph_defaults.default_target),
+            default_cfg_name: settings
+                .default_cfg_name
+                .unwrap_or(graph_defaults.default_cfg_name),
+            cache_capacity: settings
+                .cache_capacity
+                .unwrap_or(graph_defaults.cache_capacity),
+        };
+
+        if let Err(graph_problems) = graph.validate() {
+            problems.extend(graph_problems);
+        }
+
+        Self {
+            address: settings
+                .address
+                .unwrap_or(IpAddr::V4(Ipv4Addr::UNSPECIFIED)),
+            port: settings.port.unwrap_or(DEFAULT_PORT),
+            cors_origins,
+            log_level,
+            graph,
+            refresh_interval,
+            warm_cache_crates: settings
+                .warm_cache_crates
+                .unwrap_or(DEFAULT_WARM_CACHE_CRATES),
+            compute_concurrency,
+            queue_timeout,
+        }
+    }
+}
+
+/// An error describing every problem found in the configuration.
+#[derive(Debug)]
+pub struct ConfigError {
+    /// A description of each problem found.
+    problems: Vec<String>,
+}
+
+impl ConfigError {
+    /// Returns a description of each problem found.
+    #[must_use]
+    pub fn problems(&self) -> &[String] {
+        &self.problems
+    }
+}
+
+impl Display for ConfigError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "Invalid configuration:")?;
+
+        for problem in &self.problems {
+            write!(f, "\n  - {problem}")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Error for ConfigError {}
+
+/// Configuration settings from a single source, where unset settings fall back to lower-precedence sources.
+#[derive(Default)]
+struct Settings {
+    /// The address to listen on.
+    address: Option<IpAddr>,
+
+    /// The port to listen on.
+    port: Option<u16>,
+
+    /// The origins allowed to make cross-origin requests.
+    cors_origins: Option<Vec<String>>,
+
+    /// The max level of log messages to emit.
+    log_level: Option<String>,
+
+    /// The kind of location to load the database dump from.
+    data_source: Option<DataSourceKind>,
+
+    /// The URL of the database dump.
+    data_url: Option<String>,
+
+    /// The path of the database dump.
+    data_path: Option<PathBuf>,
+
+    /// The time (in seconds) between reloads of the database dump.
+    refresh_interval_secs: Option<u64>,
+
+    /// The target triple used when a query does not specify one.
+    default_target: Option<String>,
+
+    /// The additional cfg name enabled when a query does not specify one.
+    default_cfg_name: Option<String>,
+
+    /// The max number of dependency graphs to cache.
+    cache_capacity: Option<usize>,
+
+    /// The number of most downloaded crates to cache dependency graphs of at startup.
+    warm_cache_crates: Option<usize>,
+
+    /// The max number of graph computations to run at once.
+    compute_concurrency: Option<usize>,
+
+    /// The time (in seconds) a graph computation may wait for a free slot.
+    queue_timeout_secs: Option<u64>,
+}
+
+impl Settings {
+    /// Reads `Settings` from a TOML configuration file, recording any problems found.
+    ///
+    /// # Arguments
+    /// * `config_path` - the path of the configuration file.
+    /// * `problems` - the list of problems to append to.
+    fn from_file(config_path: &Path, problems: &mut Vec<String>) -> Self {
+        let contents = match fs::read_to_string(config_path) {
+            Ok(contents) => contents,
+            Err(err) => {
+                problems.push(format!(
+                    "unable to read configuration file `{}`: {err}",
+                    config_path.display()
+                ));
+                return Self::default();
+            }
+        };
+
+        match toml::from_str::<ConfigFile>(&contents) {
+            Ok(config_file) => Self::from(config_file),
+            Err(err) => {
+                problems.push(format!(
+                    "unable to parse configuration file `{}`: {}",
+                    config_path.display(),
+                    err.message()
+                ));
+                Self::default()
+            }
+        }
+    }
+
+    /// Reads `Settings` from `VAULT_*` environment variables, recording any problems found.
+    ///
+    /// # Arguments
+    /// * `env` - a function returning the value of an environment variable, if set.
+    /// * `problems` - the list of problems to append to.
+    fn from_env(env: &impl Fn(&str) -> Option<String>, problems: &mut Vec<String>) -> Self {
+        Self {
+            address: parse_env_var(env, "VAULT_ADDRESS", problems),
+            port: parse_env_var(env, "VAULT_PORT", problems),
+            cors_origins: env("VAULT_CORS_ORIGINS").map(|origins| {
+                origins
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|origin| !origin.is_empty())
+                    .map(String::from)
+                    .collect()
+            }),
+            log_level: env("VAULT_LOG_LEVEL"),
+            data_source: parse_env_var(env, "VAULT_DATA_SOURCE", problems),
+            data_url: env("VAULT_DATA_URL"),
+            data_path: env("VAULT_DATA_PATH").map(PathBuf::from),
+            refresh_interval_secs: parse_env_var(env, "VAULT_REFRESH_INTERVAL_SECS", problems),
+            default_target: env("VAULT_DEFAULT_TARGET"),
+            default_cfg_name: env("VAULT_DEFAULT_CFG_NAME"),
+            cache_capacity: parse_env_var(env, "VAULT_CACHE_CAPACITY", problems),
+            warm_cache_crates: parse_env_var(env, "VAULT_WARM_CACHE_CRATES", problems),
+            compute_concurrency: parse_env_var(env, "VAULT_COMPUTE_CONCURRENCY", problems),
+            queue_timeout_secs: parse_env_var(env, "VAULT_QUEUE_TIMEOUT_SECS", problems),
+        }
+    }
+
+    /// Combines two `Settings`, preferring the settings of the higher-precedence source where both are set.
+    ///
+    /// # Arguments
+    /// * `higher` - the `Settings` of the higher-precedence source.
+    fn merge(self, higher: Self) -> Self {
+        Self {
+            address: higher.address.or(self.address),
+            port: higher.port.or(self.port),
+            cors_origins: higher.cors_origins.or(self.cors_origins),
+            log_level: higher.log_level.or(self.log_level),
+            data_source: higher.data_source.or(self.data_source),
+            data_url: higher.data_url.or(self.data_url),
+            data_path: higher.data_path.or(self.data_path),
+            refresh_interval_secs: higher.refresh_interval_secs.or(self.refresh_interval_secs),
+            default_target: higher.default_target.or(self.default_target),
+            default_cfg_name: higher.default_cfg_name.or(self.default_cfg_name),
+            cache_capacity: higher.cache_capacity.or(self.cache_capacity),
+            warm_cache_crates: higher.warm_cache_crates.or(self.warm_cache_crates),
+            compute_concurrency: higher.compute_concurrency.or(self.compute_concurrency),
+            queue_timeout_secs: higher.queue_timeout_secs.or(self.queue_timeout_secs),
+        }
+    }
+}
+
+impl From<Args> for Settings {
+    fn from(args: Args) -> Self {
+        Self {
+            address: if args.local {
+                Some(IpAddr::V4(Ipv4Addr::LOCALHOST))
+            } else {
+                args.address
+            },
+            port: args.port.or(args.legacy_port),
+            cors_origins: if args.cors_origins.is_empty() {
+                None
+            } else {
+                Some(args.cors_origins)
+            },
+            log_level: args.log_level,
+            data_source: args.data_source,
+            data_url: args.data_url,
+            data_path: args.data_path,
+            refresh_interval_secs: args.refresh_interval_secs,
+            default_target: args.default_target,
+            default_cfg_name: args.default_cfg_name,
+            cache_capacity: args.cache_capacity,
+            warm_cache_crates: args.warm_cache_crates,
+            compute_concurrency: args.compute_concurrency,
+            queue_timeout_secs: args.queue_timeout_secs,
+        }
+    }
+}
+
+impl From<ConfigFile> for Settings {
+    fn from(config_file: ConfigFile) -> Self {
+        let ConfigFile {
+            server,
+            data,
+            graph,
+            cache,
+            compute,
+        } = config_file;
+
+        Self {
+            address: server.address,
+            port: server.port,
+            cors_origins: server.cors_origins,
+            log_level: server.log_level,
+            data_source: data.source,
+            data_url: data.url,
+            data_path: data.path,
+            refresh_interval_secs: data.refresh_interval_secs,
+            default_target: graph.default_target,
+            default_cfg_name: graph.default_cfg_name,
+            cache_capacity: cache.capacity,
+            warm_cache_crates: cache.warm_crates,
+            compute_concurrency: compute.concurrency,
+            queue_timeout_secs: compute.queue_timeout_secs,
+        }
+    }
+}
+
+/// The structure of a TOML configuration file.
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct ConfigFile {
+    /// The `[server]` table.
+    server: ServerTable,
+
+    /// The `[data]` table.
+    data: DataTable,
+
+    /// The `[graph]` table.
+    graph: GraphTable,
+
+    /// The `[cache]` table.
+    cache: CacheTable,
+
+    /// The `[compute]` table.
+    compute: ComputeTable,
+}
+
+/// The `[server]` table of a configuration file.
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct ServerTable {
+    /// The address to listen on.
+    address: Option<IpAddr>,
+
+    /// The port to listen on.
+    port: Option<u16>,
+
+    /// The origins allowed to make cross-origin requests.
+    cors_origins: Option<Vec<String>>,
+
+    /// The max level of log messages to emit.
+    log_level: Option<String>,
+}
+
+/// The `[data]` table of a configuration file.
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct DataTable {
+    /// The kind of location to load the database dump from.
+    source: Option<DataSourceKind>,
+
+    /// The URL of the database dump.
+    url: Option<String>,
+
+    /// The path of the database dump.
+    path: Option<PathBuf>,
+
+    /// The time (in seconds) between reloads of the database dump.
+    refresh_interval_secs: Option<u64>,
+}
+
+/// The `[graph]` table of a configuration file.
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct GraphTable {
+    /// The target triple used when a query does not specify one.
+    default_target: Option<String>,
+
+    /// The additional cfg name enabled when a query does not specify one.
+    default_cfg_name: Option<String>,
+}
+
+/// The `[cache]` table of a configuration file.
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct CacheTable {
+    /// The max number of dependency graphs to cache.
+    capacity: Option<usize>,
+
+    /// The number of most downloaded crates to cache dependency graphs of at startup.
+    warm_crates: Option<usize>,
+}
+
+/// The `[compute]` table of a configuration file.
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct ComputeTable {
+    /// The max number of graph computations to run at once.
+    concurrency: Option<usize>,
+
+    /// The time (in seconds) a graph computation may wait for a free slot.
+    queue_timeout_secs: Option<u64>,
+}
+
+/// Parses an environment variable, if set, recording a problem if its value is invalid.
+///
+/// # Arguments
+/// * `env` - a function returning the value of an environment variable, if set.
+/// * `name` - the name of the environment variable.
+/// * `problems` - the list of problems to append to.
+fn parse_env_var<T: FromStr>(
+    env: &impl Fn(&str) -> Option<String>,
+    name: &str,
+    problems: &mut Vec<String>,
+) -> Option<T> {
+    env(name).and_then(|value| {
+        value.trim().parse().map_or_else(
+            |_| {
+                problems.push(format!("`{name}`: `{value}` is not a valid value"));
+                None
+            },
+            Some,
+        )
+    })
+}
+
+/// Returns the `DataSource` described by the data settings, recording any problems found.
+///
+/// # Arguments
+/// * `kind` - the kind of location to load the database dump from, defaulting to `remote`.
+/// * `url` - the URL of the database dump, only allowed for the `remote` data source.
+/// * `path` - the path of the database dump, required for the `dump` and `snapshot` data sources.
+/// * `problems` - the list of problems to append to.
+fn get_data_source(
+    kind: Option<DataSourceKind>,
+    url: Option<String>,
+    path: Option<PathBuf>,
+    problems: &mut Vec<String>,
+) -> DataSource {
+    match kind.unwrap_or(DataSourceKind::Remote) {
+        DataSourceKind::Remote => {
+            if path.is_some() {
+                problems.push(String::from(
+                    "`data.path` is only used by the `dump` and `snapshot` data sources; set `data.source` accordingly",
+                ));
+            }
+
+            DataSource::Remote(url.unwrap_or_else(|| String::from(DEFAULT_DUMP_URL)))
+        }
+
+        kind => {
+            if url.is_some() {
+                problems.push(String::from(
+                    "`data.url` is only used by the `remote` data source",
+                ));
+            }
+
+            let Some(path) = path else {
+                problems.push(String::from(
+                    "`data.path` is required by the `dump` and `snapshot` data sources",
+                ));
+                return DataSource::default();
+            };
+
+            if kind == DataSourceKind::Dump {
+                DataSource::Dump(path)
+            } else {
+                DataSource::Snapshot(path)
+            }
+        }
+    }
+}
+
+/// Returns if a string is an origin (e.g. `https://example.com:8080`) or `*`.
+///
+/// # Arguments
+/// * `origin` - the string to check.
+fn is_valid_origin(origin: &str) -> bool {
+    origin == "*"
+        || origin.split_once("://").is_some_and(|(scheme, authority)| {
+            (scheme == "http" || scheme == "https")
+                && !authority.contains('@')
+                && Authority::from_str(authority).is_ok()
+        })
+}
@@ -1,4 +1,5 @@
 #![warn(clippy::all, clippy::pedantic, clippy::nursery)]
 #![allow(clippy::unused_async)]
 
+pub mod config;
 pub mod routes;
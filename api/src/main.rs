@@ -1,45 +1,150 @@
 #![warn(clippy::all, clippy::pedantic, clippy::nursery)]
 #![allow(clippy::unused_async)]
 
+use clap::Parser;
 use env_logger::{Builder, Env};
-use std::{env, sync::Arc};
-use vault_api::routes::{self, utils};
-use vault_graph::Graph;
+use log::{error, info};
+use std::{
+    env, process,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tokio::time;
+use vault_api::{
+    config::{Args, Config},
+    routes::{
+        self, metrics,
+        utils::{self, SharedState},
+    },
+};
+use vault_graph::{Graph, LoadError, LoadProgress, LoadReport};
 use warp::Filter;
 
 #[tokio::main]
 async fn main() {
-    let mut args = env::args();
-
-    // port defaults to 8080 if not provided
-    let port = {
-        let port_string = args.nth(1).unwrap_or_else(|| String::from("8080"));
-
-        port_string
-            .parse::<u16>()
-            .unwrap_or_else(|_| panic!("{port_string} is not a valid port number"))
-    };
-
-    // address defaults to `0.0.0.0`, unless the `-l` or `--local` argument is passed, in which case the address is `127.0.0.1`
-    let address = args.next().map_or([0; 4], |arg| {
-        if arg == "--local" || arg == "-l" {
-            [127, 0, 0, 1]
-        } else {
-            [0; 4]
-        }
+    let config = Config::load(Args::parse(), |name| env::var(name).ok()).unwrap_or_else(|err| {
+        eprintln!("{err}");
+        process::exit(2);
     });
 
-    let app_state = Arc::new(Graph::new());
+    // initialize logger at the configured level, unless overridden by `RUST_LOG`
+    Builder::from_env(Env::default().default_filter_or(config.log_level.to_string())).init();
 
-    // initialize logger at `info` level
-    Builder::from_env(Env::default().default_filter_or("info")).init();
+    // the server starts right away, serving health checks while the first graph loads
+    let progress = LoadProgress::default();
+    let state = SharedState::loading(progress.clone());
+    tokio::spawn(serve_graph(state.clone(), config.clone(), progress));
 
     warp::serve(
-        routes::get(app_state.clone())
+        routes::with_compute_pool(state, config.compute_pool())
             .recover(utils::handle_rejection)
-            .with(warp::cors().allow_origin("https://vault.arpanlaha.com"))
+            .with(warp::log::custom(|info| metrics::observe_request(&info)))
+            .with(config.cors())
             .with(warp::log("info")),
     )
-    .run((address, port))
+    .run((config.address, config.port))
     .await;
 }
+
+/// Loads the first registry graph in the background, then keeps it refreshed if configured.
+///
+/// The process exits if the first graph cannot be loaded.
+///
+/// # Arguments
+/// * `state` - the `SharedState` holding the served graph.
+/// * `config` - the `Config` of the server.
+/// * `progress` - the `LoadProgress` to report the first load to.
+async fn serve_graph(state: SharedState, config: Config, progress: LoadProgress) {
+    let refresh_interval = config.refresh_interval;
+    let config = Arc::new(config);
+
+    let load_config = config.clone();
+    match state
+        .reload(move || load_graph(&load_config, &progress))
+        .await
+    {
+        Ok(()) => info!("Serving registry graph."),
+        Err(err) => {
+            error!("Unable to load registry graph: {err}");
+            process::exit(1);
+        }
+    }
+
+    if let Some(refresh_interval) = refresh_interval {
+        refresh_graph(state, config, refresh_interval).await;
+    }
+}
+
+/// Loads the registry graph as configured and warms its dependency graph cache.
+///
+/// # Arguments
+/// * `config` - the `Config` of the server.
+/// * `progress` - the `LoadProgress` to report to.
+///
+/// # Errors
+/// * Returns a `LoadError` if the registry graph cannot be loaded.
+fn load_graph(config: &Config, progress: &LoadProgress) -> Result<Graph, LoadError> {
+    let mut graph = Graph::from_config_with_progress(&config.graph, progress)?;
+    graph.set_dependency_graph_observer(metrics::observe_dependency_graph);
+    graph.warm_cache(config.warm_cache_crates);
+    log_load_report(graph.load_report());
+
+    Ok(graph)
+}
+
+/// Reloads the registry graph periodically, replacing the served graph once each reload finishes.
+///
+/// Requests are served by the previous graph while a reload is in progress, and it is kept if the reload fails. Both
+/// graphs are held in memory until the reload finishes, so peak memory use is about twice that of a single graph.
+///
+/// # Arguments
+/// * `state` - the `SharedState` holding the served graph.
+/// * `config` - the `Config` of the server.
+/// * `refresh_interval` - the time between reloads.
+async fn refresh_graph(state: SharedState, config: Arc<Config>, refresh_interval: Duration) {
+    loop {
+        time::sleep(refresh_interval).await;
+        info!("Refreshing registry graph...");
+
+        let config = config.clone();
+        let refresh_start = Instant::now();
+        match state
+            .reload(move || load_graph(&config, &LoadProgress::default()))
+            .await
+        {
+            Ok(()) => {
+                metrics::observe_refresh(refresh_start.elapsed(), true);
+                info!("Refreshed registry graph.");
+            }
+            Err(err) => {
+                metrics::observe_refresh(refresh_start.elapsed(), false);
+                error!("Unable to refresh registry graph: {err}");
+            }
+        }
+    }
+}
+
+/// Logs the time taken by each phase of loading the registry graph.
+///
+/// # Arguments
+/// * `load_report` - the `LoadReport` of the registry graph.
+fn log_load_report(load_report: &LoadReport) {
+    for phase in &load_report.phases {
+        if let Some(items) = phase.items {
+            info!(
+                "Finished {} phase ({} items) in {} seconds, starting at {} seconds.",
+                phase.name, items, phase.seconds, phase.started_at
+            );
+        } else {
+            info!(
+                "Finished {} phase in {} seconds, starting at {} seconds.",
+                phase.name, phase.seconds, phase.started_at
+            );
+        }
+    }
+
+    info!(
+        "Finished loading registry graph in {} seconds.",
+        load_report.seconds
+    );
+}
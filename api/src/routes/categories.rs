@@ -1,47 +1,72 @@
-use super::utils::{State, VaultError};
+use super::utils::{ready, SharedState, State, VaultError};
 use warp::{Filter, Rejection, Reply};
 
 pub use handlers::CategoryResponse;
 
 /// Wraps all `Category` routes.
 #[must_use]
-pub fn routes(state: State) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+pub fn routes(
+    state: SharedState,
+) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
     get_categories(state.clone())
         .or(get_category(state.clone()))
         .or(random(state.clone()))
-        .or(search(state))
+        .or(search(state.clone()))
+        .or(get_footprint_leaderboard(state))
 }
 
 /// Returns a list of all categories.
-fn get_categories(state: State) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+fn get_categories(
+    state: SharedState,
+) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
     warp::path("categories")
         .and(warp::path::end())
         .and(warp::get())
-        .and_then(move || handlers::get_categories(state.clone()))
+        .and(ready(state.clone()))
+        .and_then(move || handlers::get_categories(state.load()))
 }
 
 /// Returns the `Category` with the given id, if found.
 ///
 /// # Errors
 /// * Returns a `404` error if no `Category` with the given id is found.
-fn get_category(state: State) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+fn get_category(
+    state: SharedState,
+) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
     warp::path!("categories" / String)
         .and(warp::get())
-        .and_then(move |category_id| handlers::get_category(category_id, state.clone()))
+        .and(ready(state.clone()))
+        .and_then(move |category_id| handlers::get_category(category_id, state.load()))
 }
 
 /// Returns a random `Category`.
-fn random(state: State) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+fn random(state: SharedState) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
     warp::path!("random" / "categories")
         .and(warp::get())
-        .and_then(move || handlers::random(state.clone()))
+        .and(ready(state.clone()))
+        .and_then(move || handlers::random(state.load()))
 }
 
 /// Searches for categorys matching the given search term.
-fn search(state: State) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+fn search(state: SharedState) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
     warp::path!("search" / "categories" / String)
         .and(warp::get())
-        .and_then(move |search_term| handlers::search(search_term, state.clone()))
+        .and(ready(state.clone()))
+        .and_then(move |search_term| handlers::search(search_term, state.load()))
+}
+
+/// Returns the crates of the `Category` with the given id with the heaviest and lightest transitive footprints, if
+/// found.
+///
+/// # Errors
+/// * Returns a `404` error if no `Category` with the given id is found.
+fn get_footprint_leaderboard(
+    state: SharedState,
+) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+    warp::path!("categories" / String / "footprints")
+        .and(warp::get())
+        .and(ready(state.clone()))
+        .and_then(move |category_id| handlers::get_footprint_leaderboard(category_id, state.load()))
 }
 
 mod handlers {
@@ -86,6 +111,21 @@ mod handlers {
         ))
     }
 
+    /// Returns the crates of the `Category` with the given id with the heaviest and lightest transitive footprints,
+    /// if found.
+    ///
+    /// # Errors
+    /// * Returns a `404` error if no `Category` with the given id is found.
+    pub async fn get_footprint_leaderboard(
+        category_id: String,
+        state: State,
+    ) -> Result<impl Reply, Rejection> {
+        state.get_footprint_leaderboard(&category_id).map_or_else(
+            || Err(reject::custom(VaultError::CategoryNotFound(category_id))),
+            |footprint_leaderboard| Ok(reply::json(&footprint_leaderboard)),
+        )
+    }
+
     #[derive(Serialize)]
     /// A struct containing a `Category` as well as any subcategories.
     pub struct CategoryResponse<'a> {
@@ -103,7 +143,7 @@ mod handlers {
         /// * `category` - the given `Category`.
         /// * `graph` - the `Graph` containing the crates.io data.
         #[must_use]
-        pub fn new(category: &'a Category, graph: &'a Graph) -> CategoryResponse<'a> {
+        pub fn new(category: &'a Category, graph: &'a Graph) -> Self {
             CategoryResponse {
                 category,
                 children: graph
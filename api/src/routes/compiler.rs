@@ -1,26 +1,34 @@
-use super::utils::State;
+use super::utils::{ready, SharedState, State};
 use warp::{Filter, Rejection, Reply};
 
 pub use handlers::{CfgNameList, TargetList};
 
 /// Wraps all compiler-related routes.
 #[must_use]
-pub fn routes(state: State) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+pub fn routes(
+    state: SharedState,
+) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
     get_targets(state.clone()).or(get_cfg_names(state))
 }
 
 /// Returns a list of targets.
-fn get_targets(state: State) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+fn get_targets(
+    state: SharedState,
+) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
     warp::path!("compiler" / "targets")
         .and(warp::get())
-        .and_then(move || handlers::get_targets(state.clone()))
+        .and(ready(state.clone()))
+        .and_then(move || handlers::get_targets(state.load()))
 }
 
 /// Returns a list of cfg names.
-fn get_cfg_names(state: State) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+fn get_cfg_names(
+    state: SharedState,
+) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
     warp::path!("compiler" / "cfg-names")
         .and(warp::get())
-        .and_then(move || handlers::get_cfg_names(state.clone()))
+        .and(ready(state.clone()))
+        .and_then(move || handlers::get_cfg_names(state.load()))
 }
 
 mod handlers {
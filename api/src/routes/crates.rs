@@ -1,75 +1,341 @@
-use super::utils::{State, VaultError};
-use std::{borrow::ToOwned, collections::HashMap};
+use super::utils::{ready, ComputePool, SharedState, State, VaultError};
+use std::collections::HashMap;
 use warp::{Filter, Rejection, Reply};
 
+/// The max size of an uploaded manifest in bytes.
+const MAX_MANIFEST_BYTES: u64 = 256 * 1024;
+
+/// The max size of an uploaded lockfile in bytes.
+const MAX_LOCKFILE_BYTES: u64 = 4 * 1024 * 1024;
+
+/// The max size of uploaded `cargo metadata` output in bytes.
+const MAX_METADATA_BYTES: u64 = 16 * 1024 * 1024;
+
+/// The max size of an uploaded graph query in bytes.
+const MAX_GRAPH_QUERY_BYTES: u64 = 64 * 1024;
+
+/// The max number of features whose cost is computed in a single request.
+const MAX_FEATURE_COSTS: usize = 64;
+
 /// Wraps all `Crate` routes.
 #[must_use]
-pub fn routes(state: State) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+pub fn routes(
+    state: SharedState,
+    pool: ComputePool,
+) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
     get_crate(state.clone())
         .or(random(state.clone()))
         .or(search(state.clone()))
-        .or(get_dependency_graph(state.clone()))
-        .or(get_random_dependency_graph(state))
+        .or(get_dependency_graph(state.clone(), pool.clone()))
+        .or(query_dependency_graph(state.clone(), pool.clone()))
+        .or(get_manifest_dependency_graph(state.clone(), pool.clone()))
+        .or(get_sbom(state.clone(), pool.clone()))
+        .or(get_manifest_sbom(state.clone(), pool.clone()))
+        .or(analyze_lockfile(state.clone(), pool.clone()))
+        .or(compare_metadata(state.clone(), pool.clone()))
+        .or(get_dominator_tree(state.clone(), pool.clone()))
+        .or(get_random_dependency_graph(state.clone(), pool.clone()))
+        .or(get_feature_costs(state.clone(), pool.clone()))
+        .or(get_feature_suggestion(state.clone(), pool.clone()))
+        .or(get_cycles(state, pool))
 }
 
 /// Returns the `Crate` with the given id, if found.
 ///
 /// # Errors
 /// * Returns a `404` error if no `Crate` with the given id is found.
-fn get_crate(state: State) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+fn get_crate(
+    state: SharedState,
+) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
     warp::path!("crates" / String)
         .and(warp::get())
-        .and_then(move |crate_id| handlers::get_crate(crate_id, state.clone()))
+        .and(ready(state.clone()))
+        .and_then(move |crate_id| handlers::get_crate(crate_id, state.load()))
 }
 
 /// Returns a random `Crate`.
-fn random(state: State) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+fn random(state: SharedState) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
     warp::path!("random" / "crates")
         .and(warp::get())
-        .and_then(move || handlers::random(state.clone()))
+        .and(ready(state.clone()))
+        .and_then(move || handlers::random(state.load()))
 }
 
 /// Searches for crates matching the given search term.
-fn search(state: State) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+///
+/// # Errors
+/// * Returns a `400` error if the `rank` query option is not `downloads` or `centrality`.
+fn search(state: SharedState) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
     warp::path!("search" / "crates" / String)
         .and(warp::get())
-        .and_then(move |search_term| handlers::search(search_term, state.clone()))
+        .and(ready(state.clone()))
+        .and(warp::query::<HashMap<String, String>>())
+        .and_then(
+            move |search_term, query_param_map: HashMap<String, String>| {
+                handlers::search(search_term, query_param_map, state.load())
+            },
+        )
 }
 
 /// Returns the `DependencyGraph` of the `Crate` ith the given id, if found.
 ///
+/// The graph is exported in the format named by the `format` query option, or else the one preferred by the `Accept`
+/// header, defaulting to JSON.
+///
 /// # Errors
 /// * Returns a `404` error if no `Crate` with the given id is found.
+/// * Returns a `400` error if any query options have improper values or any requested features do not exist.
+/// * Returns a `503` error if the `ComputePool` is saturated.
 fn get_dependency_graph(
-    state: State,
+    state: SharedState,
+    pool: ComputePool,
 ) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
     warp::path!("graph" / String)
         .and(warp::get())
+        .and(ready(state.clone()))
         .and(warp::query::<HashMap<String, String>>())
-        .and_then(move |crate_id, query_param_map: HashMap<String, String>| {
-            handlers::get_dependency_graph(
-                crate_id,
-                query_param_map.get("features").map(ToOwned::to_owned),
-                query_param_map.get("target").map(ToOwned::to_owned),
-                query_param_map.get("cfg_name").map(ToOwned::to_owned),
-                state.clone(),
+        .and(warp::header::optional::<String>("accept"))
+        .and_then(
+            move |crate_id, query_param_map: HashMap<String, String>, accept| {
+                handlers::get_dependency_graph(
+                    crate_id,
+                    query_param_map,
+                    accept,
+                    state.load(),
+                    pool.clone(),
+                )
+            },
+        )
+}
+
+/// Returns the `DependencyGraph` described by the JSON `DependencyGraphQuery` in the request body.
+///
+/// The graph is exported in the query's `format`, defaulting to JSON.
+///
+/// # Errors
+/// * Returns a `400` error if the query cannot be parsed, naming the field with an improper value.
+/// * Returns a `404` error if any root crate is not found.
+/// * Returns a `400` error if the query references nonexistent values or any requested features do not exist.
+/// * Returns a `503` error if the `ComputePool` is saturated.
+fn query_dependency_graph(
+    state: SharedState,
+    pool: ComputePool,
+) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+    warp::path!("graph")
+        .and(warp::post())
+        .and(ready(state.clone()))
+        .and(warp::body::content_length_limit(MAX_GRAPH_QUERY_BYTES))
+        .and(warp::body::bytes())
+        .and_then(move |query| handlers::query_dependency_graph(query, state.load(), pool.clone()))
+}
+
+/// Returns the `DependencyGraph` of a crate built from the `Cargo.toml` manifest in the request body.
+///
+/// # Errors
+/// * Returns a `400` error if the manifest cannot be parsed or depends on crates not in the registry.
+/// * Returns a `400` error if any query options have improper values or any requested features do not exist.
+/// * Returns a `503` error if the `ComputePool` is saturated.
+fn get_manifest_dependency_graph(
+    state: SharedState,
+    pool: ComputePool,
+) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+    warp::path!("manifest" / "graph")
+        .and(warp::post())
+        .and(ready(state.clone()))
+        .and(warp::query::<HashMap<String, String>>())
+        .and(warp::body::content_length_limit(MAX_MANIFEST_BYTES))
+        .and(warp::body::bytes())
+        .and_then(move |query_param_map: HashMap<String, String>, manifest| {
+            handlers::get_manifest_dependency_graph(
+                query_param_map,
+                manifest,
+                state.load(),
+                pool.clone(),
             )
         })
 }
 
+/// Returns a Software Bill of Materials for the `DependencyGraph` of the `Crate` with the given id, if found.
+///
+/// The document is a `CycloneDX` JSON document unless the `format` query option is `spdx`.
+///
+/// # Errors
+/// * Returns a `404` error if no `Crate` with the given id is found.
+/// * Returns a `400` error if any query options have improper values or any requested features do not exist.
+/// * Returns a `503` error if the `ComputePool` is saturated.
+fn get_sbom(
+    state: SharedState,
+    pool: ComputePool,
+) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+    warp::path!("sbom" / String)
+        .and(warp::get())
+        .and(ready(state.clone()))
+        .and(warp::query::<HashMap<String, String>>())
+        .and_then(move |crate_id, query_param_map: HashMap<String, String>| {
+            handlers::get_sbom(crate_id, query_param_map, state.load(), pool.clone())
+        })
+}
+
+/// Returns a Software Bill of Materials for the `DependencyGraph` of a crate built from the `Cargo.toml` manifest in
+/// the request body.
+///
+/// # Errors
+/// * Returns a `400` error if the manifest cannot be parsed or depends on crates not in the registry.
+/// * Returns a `400` error if any query options have improper values or any requested features do not exist.
+/// * Returns a `503` error if the `ComputePool` is saturated.
+fn get_manifest_sbom(
+    state: SharedState,
+    pool: ComputePool,
+) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+    warp::path!("manifest" / "sbom")
+        .and(warp::post())
+        .and(ready(state.clone()))
+        .and(warp::query::<HashMap<String, String>>())
+        .and(warp::body::content_length_limit(MAX_MANIFEST_BYTES))
+        .and(warp::body::bytes())
+        .and_then(move |query_param_map: HashMap<String, String>, manifest| {
+            handlers::get_manifest_sbom(query_param_map, manifest, state.load(), pool.clone())
+        })
+}
+
+/// Matches the packages of the `Cargo.lock` lockfile in the request body against the registry.
+///
+/// # Errors
+/// * Returns a `400` error if the lockfile cannot be parsed.
+/// * Returns a `503` error if the `ComputePool` is saturated.
+fn analyze_lockfile(
+    state: SharedState,
+    pool: ComputePool,
+) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+    warp::path!("lockfile" / "analysis")
+        .and(warp::post())
+        .and(ready(state.clone()))
+        .and(warp::body::content_length_limit(MAX_LOCKFILE_BYTES))
+        .and(warp::body::bytes())
+        .and_then(move |lockfile| handlers::analyze_lockfile(lockfile, state.load(), pool.clone()))
+}
+
+/// Compares the `cargo metadata` output in the request body with the `DependencyGraph` of its root package.
+///
+/// # Errors
+/// * Returns a `400` error if the metadata cannot be parsed or its root package depends on crates not in the registry.
+/// * Returns a `400` error if any query options have improper values.
+/// * Returns a `503` error if the `ComputePool` is saturated.
+fn compare_metadata(
+    state: SharedState,
+    pool: ComputePool,
+) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+    warp::path!("metadata" / "comparison")
+        .and(warp::post())
+        .and(ready(state.clone()))
+        .and(warp::query::<HashMap<String, String>>())
+        .and(warp::body::content_length_limit(MAX_METADATA_BYTES))
+        .and(warp::body::bytes())
+        .and_then(move |query_param_map: HashMap<String, String>, metadata| {
+            handlers::compare_metadata(query_param_map, metadata, state.load(), pool.clone())
+        })
+}
+
+/// Returns the dominator tree of the `DependencyGraph` of the `Crate` with the given id, if found.
+///
+/// # Errors
+/// * Returns a `404` error if no `Crate` with the given id is found.
+/// * Returns a `400` error if any query options have improper values or any requested features do not exist.
+/// * Returns a `503` error if the `ComputePool` is saturated.
+fn get_dominator_tree(
+    state: SharedState,
+    pool: ComputePool,
+) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+    warp::path!("graph" / String / "dominators")
+        .and(warp::get())
+        .and(ready(state.clone()))
+        .and(warp::query::<HashMap<String, String>>())
+        .and_then(move |crate_id, query_param_map: HashMap<String, String>| {
+            handlers::get_dominator_tree(crate_id, query_param_map, state.load(), pool.clone())
+        })
+}
+
+/// Returns the cost of enabling each feature of the `Crate` with the given id, if found.
+///
+/// # Errors
+/// * Returns a `404` error if no `Crate` with the given id is found.
+/// * Returns a `400` error if any query options have improper values or any requested features do not exist.
+/// * Returns a `400` error if more features would be analyzed than allowed in a single request.
+/// * Returns a `503` error if the `ComputePool` is saturated.
+fn get_feature_costs(
+    state: SharedState,
+    pool: ComputePool,
+) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+    warp::path!("crates" / String / "feature-costs")
+        .and(warp::get())
+        .and(ready(state.clone()))
+        .and(warp::query::<HashMap<String, String>>())
+        .and_then(move |crate_id, query_param_map: HashMap<String, String>| {
+            handlers::get_feature_costs(crate_id, query_param_map, state.load(), pool.clone())
+        })
+}
+
+/// Returns the smallest change to the features of the `Crate` with the given id which removes an unwanted crate from
+/// its `DependencyGraph`, if found.
+///
+/// # Errors
+/// * Returns a `404` error if no `Crate` with the given id is found.
+/// * Returns a `400` error if any query options have improper values or any requested features do not exist.
+/// * Returns a `503` error if the `ComputePool` is saturated.
+fn get_feature_suggestion(
+    state: SharedState,
+    pool: ComputePool,
+) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+    warp::path!("crates" / String / "feature-suggestion")
+        .and(warp::get())
+        .and(ready(state.clone()))
+        .and(warp::query::<HashMap<String, String>>())
+        .and_then(move |crate_id, query_param_map: HashMap<String, String>| {
+            handlers::get_feature_suggestion(crate_id, query_param_map, state.load(), pool.clone())
+        })
+}
+
+/// Returns the groups of crates which depend on each other in a cycle, ranked by size.
+///
+/// # Errors
+/// * Returns a `400` error if the `kinds` query option references nonexistent dependency kinds.
+/// * Returns a `503` error if the `ComputePool` is saturated.
+fn get_cycles(
+    state: SharedState,
+    pool: ComputePool,
+) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+    warp::path!("cycles")
+        .and(warp::get())
+        .and(ready(state.clone()))
+        .and(warp::query::<HashMap<String, String>>())
+        .and_then(move |query_param_map: HashMap<String, String>| {
+            handlers::get_cycles(query_param_map, state.load(), pool.clone())
+        })
+}
+
 /// Returns the `DependencyGraph` of a random `Crate`.
+///
+/// # Errors
+/// * Returns a `503` error if the `ComputePool` is saturated.
 fn get_random_dependency_graph(
-    state: State,
+    state: SharedState,
+    pool: ComputePool,
 ) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
     warp::path!("random" / "graph")
         .and(warp::get())
-        .and_then(move || handlers::get_random_dependency_graph(state.clone()))
+        .and(ready(state.clone()))
+        .and_then(move || handlers::get_random_dependency_graph(state.load(), pool.clone()))
 }
 
 mod handlers {
-    use super::{State, VaultError};
-    use vault_graph::{Random, Search};
-    use warp::{reject, reply, Rejection, Reply};
+    use super::{ComputePool, State, VaultError, MAX_FEATURE_COSTS};
+    use std::{collections::HashMap, str};
+    use vault_graph::{
+        Crate, DependencyGraphOptions, DependencyGraphQuery, DependencyGraphQueryError,
+        DependencyKind, GraphFormat, Lockfile, Metadata, Random, Resolver, SbomFormat, SbomInfo,
+        Search, SearchRanking,
+    };
+    use warp::{hyper::body::Bytes, reject, reply, Rejection, Reply};
 
     /// Returns the `Crate` with the given id, if found.
     ///
@@ -77,7 +343,11 @@ mod handlers {
     /// * Returns a `404` error if no `Crate` with the given id is found.
     pub async fn get_crate(crate_id: String, state: State) -> Result<impl Reply, Rejection> {
         state.crates().get(&crate_id).map_or_else(
-            || Err(reject::custom(VaultError::CrateNotFound(crate_id))),
+            || {
+                Err(reject::custom(VaultError::crate_not_found(
+                    crate_id, &state,
+                )))
+            },
             |crate_val| Ok(reply::json(crate_val)),
         )
     }
@@ -88,74 +358,864 @@ mod handlers {
     }
 
     /// Searches for crates matching the given search term.
-    pub async fn search(search_term: String, state: State) -> Result<impl Reply, Rejection> {
-        Ok(reply::json(
-            &state.crate_names().search(&search_term, state.crates()),
-        ))
+    ///
+    /// # Errors
+    /// * Returns a `400` error if the `rank` query option is not `downloads` or `centrality`.
+    pub async fn search(
+        search_term: String,
+        mut query_param_map: HashMap<String, String>,
+        state: State,
+    ) -> Result<impl Reply, Rejection> {
+        let ranking = match query_param_map.remove("rank").as_deref() {
+            None | Some("downloads") => SearchRanking::Popularity,
+            Some("centrality") => SearchRanking::Centrality,
+            Some(_) => {
+                return Err(reject::custom(VaultError::NonexistentOptions(vec![
+                    String::from("rank"),
+                ])))
+            }
+        };
+
+        Ok(reply::json(&state.crate_names().search_ranked(
+            &search_term,
+            state.crates(),
+            ranking,
+        )))
     }
 
     /// Returns the `DependencyGraph` of the `Crate` ith the given id, if found.
     ///
+    /// The query options are converted to a `DependencyGraphQuery` and answered as with `query_dependency_graph`.
+    ///
     /// # Errors
     /// * Returns a `404` error if no `Crate` with the given id is found.
-    /// * Returns a `400` error if the `platform` or `cfg_name` query options reference nonexistent values.
+    /// * Returns a `400` error if the `format` query option is not a supported `GraphFormat`.
+    /// * Returns a `400` error if the `target` or `cfg_name` query options reference nonexistent values.
+    /// * Returns a `400` error if the `default_features`, `all_features` or `infer_proc_macros` query options are not
+    ///   booleans.
+    /// * Returns a `400` error if the `resolver` query option is not `1` or `2`.
+    /// * Returns a `400` error if the `potential_dependencies` query option is not a non-negative integer.
+    /// * Returns a `400` error if any requested features are not exposed by the `Crate`.
+    /// * Returns a `503` error if the `ComputePool` is saturated.
     pub async fn get_dependency_graph(
         crate_id: String,
-        features_option: Option<String>,
-        target_option: Option<String>,
-        cfg_name_option: Option<String>,
+        mut query_param_map: HashMap<String, String>,
+        accept: Option<String>,
+        state: State,
+        pool: ComputePool,
+    ) -> Result<impl Reply, Rejection> {
+        let format = get_graph_format(&mut query_param_map, accept.as_deref())?;
+        let mut query = parse_graph_query(&crate_id, query_param_map, &state)?;
+        query.format = format;
+
+        check_graph_query_features(&query, &state)?;
+        run_dependency_graph_query(query, state, pool).await
+    }
+
+    /// Returns the `DependencyGraph` described by a JSON `DependencyGraphQuery`.
+    ///
+    /// # Errors
+    /// * Returns a `400` error if the query is not valid UTF-8 or cannot be parsed.
+    /// * Returns a `400` error if the `target`, `host`, `cfgs` or `exclude` fields reference nonexistent values.
+    /// * Returns a `404` error if any root crate is not found.
+    /// * Returns a `400` error if any requested features are not exposed by their root crate.
+    /// * Returns a `503` error if the `ComputePool` is saturated.
+    pub async fn query_dependency_graph(
+        query: Bytes,
+        state: State,
+        pool: ComputePool,
+    ) -> Result<impl Reply, Rejection> {
+        let query = str::from_utf8(&query)
+            .map_err(|_| {
+                VaultError::InvalidGraphQuery(
+                    String::from("Graph query is not valid UTF-8."),
+                    vec![],
+                )
+            })
+            .and_then(|query| {
+                DependencyGraphQuery::parse(query).map_err(|err| {
+                    let fields = match &err {
+                        DependencyGraphQueryError::Invalid(_) => vec![],
+                        DependencyGraphQueryError::Fields(field_errors) => field_errors
+                            .iter()
+                            .map(|field_error| field_error.field.clone())
+                            .collect(),
+                    };
+
+                    VaultError::InvalidGraphQuery(err.to_string(), fields)
+                })
+            })
+            .map_err(reject::custom)?;
+
+        validate_dependency_graph_query(&query, &state)?;
+        run_dependency_graph_query(query, state, pool).await
+    }
+
+    /// Returns the `DependencyGraph` of a crate built from a `Cargo.toml` manifest.
+    ///
+    /// The crate is only visible to this request, so it may share its name with a crate in the registry.
+    ///
+    /// # Errors
+    /// * Returns a `400` error if the manifest is not valid UTF-8 or cannot be parsed.
+    /// * Returns a `400` error if the manifest depends on crates not in the registry.
+    /// * Returns a `400` error if any query options have improper values or any requested features do not exist.
+    /// * Returns a `503` error if the `ComputePool` is saturated.
+    pub async fn get_manifest_dependency_graph(
+        query_param_map: HashMap<String, String>,
+        manifest: Bytes,
+        state: State,
+        pool: ComputePool,
+    ) -> Result<impl Reply, Rejection> {
+        let (manifest, query) = get_manifest_query(&manifest, query_param_map, &state)?;
+
+        pool.run(move || {
+            reply::json(
+                &state
+                    .get_manifest_dependency_graph(
+                        &manifest,
+                        query.roots[0].features.clone(),
+                        &query.target,
+                        &query.cfg_name(),
+                        &query.options(),
+                    )
+                    .unwrap(),
+            )
+        })
+        .await
+    }
+
+    /// Returns a Software Bill of Materials for the `DependencyGraph` of the `Crate` with the given id, if found.
+    ///
+    /// # Errors
+    /// * Returns a `404` error if no `Crate` with the given id is found.
+    /// * Returns a `400` error if the `format` query option is not a supported `SbomFormat`.
+    /// * Returns a `400` error if any other query options have improper values.
+    /// * Returns a `400` error if any requested features are not exposed by the `Crate`.
+    /// * Returns a `503` error if the `ComputePool` is saturated.
+    pub async fn get_sbom(
+        crate_id: String,
+        mut query_param_map: HashMap<String, String>,
+        state: State,
+        pool: ComputePool,
+    ) -> Result<impl Reply, Rejection> {
+        let format = get_sbom_format(&mut query_param_map)?;
+        let query = parse_graph_query(&crate_id, query_param_map, &state)?;
+        check_graph_query_features(&query, &state)?;
+
+        pool.run(move || {
+            state.query_dependency_graph(&query).map_or_else(
+                || {
+                    Err(reject::custom(VaultError::crate_not_found(
+                        crate_id, &state,
+                    )))
+                },
+                |dependency_graph| {
+                    Ok(reply::with_header(
+                        state.get_sbom(&dependency_graph, None, format, &SbomInfo::new()),
+                        "content-type",
+                        format.content_type(),
+                    ))
+                },
+            )
+        })
+        .await?
+    }
+
+    /// Returns a Software Bill of Materials for the `DependencyGraph` of a crate built from a `Cargo.toml` manifest.
+    ///
+    /// # Errors
+    /// * Returns a `400` error if the manifest is not valid UTF-8 or cannot be parsed.
+    /// * Returns a `400` error if the manifest depends on crates not in the registry.
+    /// * Returns a `400` error if the `format` query option is not a supported `SbomFormat`.
+    /// * Returns a `400` error if any other query options have improper values or any requested features do not exist.
+    /// * Returns a `503` error if the `ComputePool` is saturated.
+    pub async fn get_manifest_sbom(
+        mut query_param_map: HashMap<String, String>,
+        manifest: Bytes,
+        state: State,
+        pool: ComputePool,
+    ) -> Result<impl Reply, Rejection> {
+        let format = get_sbom_format(&mut query_param_map)?;
+        let (manifest, query) = get_manifest_query(&manifest, query_param_map, &state)?;
+
+        pool.run(move || {
+            let dependency_graph = state
+                .get_manifest_dependency_graph(
+                    &manifest,
+                    query.roots[0].features.clone(),
+                    &query.target,
+                    &query.cfg_name(),
+                    &query.options(),
+                )
+                .unwrap();
+
+            reply::with_header(
+                state.get_sbom(&dependency_graph, Some(&manifest), format, &SbomInfo::new()),
+                "content-type",
+                format.content_type(),
+            )
+        })
+        .await
+    }
+
+    /// Matches the packages of a `Cargo.lock` lockfile against the registry.
+    ///
+    /// # Errors
+    /// * Returns a `400` error if the lockfile is not valid UTF-8 or cannot be parsed.
+    /// * Returns a `503` error if the `ComputePool` is saturated.
+    pub async fn analyze_lockfile(
+        lockfile: Bytes,
+        state: State,
+        pool: ComputePool,
+    ) -> Result<impl Reply, Rejection> {
+        let lockfile = str::from_utf8(&lockfile)
+            .map_err(|_| String::from("Lockfile is not valid UTF-8."))
+            .and_then(|lockfile| Lockfile::parse(lockfile).map_err(|err| err.to_string()))
+            .map_err(|message| reject::custom(VaultError::InvalidLockfile(message)))?;
+
+        pool.run(move || reply::json(&state.analyze_lockfile(&lockfile)))
+            .await
+    }
+
+    /// Compares the output of `cargo metadata` with the `DependencyGraph` of its root package.
+    ///
+    /// The features of the root package are taken from the metadata, so only the `target`, `cfg_name`, `host`,
+    /// `infer_proc_macros` and `resolver` query options apply.
+    ///
+    /// # Errors
+    /// * Returns a `400` error if the metadata is not valid UTF-8 or cannot be parsed.
+    /// * Returns a `400` error if the root package depends on crates not in the registry.
+    /// * Returns a `400` error if any query options have improper values.
+    /// * Returns a `503` error if the `ComputePool` is saturated.
+    pub async fn compare_metadata(
+        query_param_map: HashMap<String, String>,
+        metadata: Bytes,
+        state: State,
+        pool: ComputePool,
+    ) -> Result<impl Reply, Rejection> {
+        let metadata = str::from_utf8(&metadata)
+            .map_err(|_| String::from("Metadata is not valid UTF-8."))
+            .and_then(|metadata| Metadata::parse(metadata).map_err(|err| err.to_string()))
+            .map_err(|message| reject::custom(VaultError::InvalidMetadata(message)))?;
+
+        let query = parse_graph_query(&metadata.root.name, query_param_map, &state)?;
+
+        // check if all dependencies exist
+        let unknown_dependencies = state.get_unknown_dependencies(&metadata.root);
+        if !unknown_dependencies.is_empty() {
+            return Err(reject::custom(VaultError::UnknownDependencies(
+                unknown_dependencies,
+            )));
+        }
+
+        pool.run(move || {
+            reply::json(
+                &state
+                    .compare_metadata(
+                        &metadata,
+                        &query.target,
+                        &query.cfg_name(),
+                        &query.options(),
+                    )
+                    .unwrap(),
+            )
+        })
+        .await
+    }
+
+    /// Returns the dominator tree of the `DependencyGraph` of the `Crate` with the given id, if found.
+    ///
+    /// # Errors
+    /// * Returns a `404` error if no `Crate` with the given id is found.
+    /// * Returns a `400` error if any query options have improper values or any requested features do not exist.
+    /// * Returns a `503` error if the `ComputePool` is saturated.
+    pub async fn get_dominator_tree(
+        crate_id: String,
+        query_param_map: HashMap<String, String>,
         state: State,
+        pool: ComputePool,
     ) -> Result<impl Reply, Rejection> {
+        let query = parse_graph_query(&crate_id, query_param_map, &state)?;
+        check_graph_query_features(&query, &state)?;
+
+        pool.run(move || {
+            state.query_dependency_graph(&query).map_or_else(
+                || {
+                    Err(reject::custom(VaultError::crate_not_found(
+                        crate_id, &state,
+                    )))
+                },
+                |dependency_graph| Ok(reply::json(&dependency_graph.dominator_tree())),
+            )
+        })
+        .await?
+    }
+
+    /// Checks that the root crate and the features requested by a `DependencyGraphQuery` parsed from query options
+    /// exist.
+    ///
+    /// Unknown features are reported by the `features` query option, rather than by their field in the query.
+    ///
+    /// # Errors
+    /// * Returns a `404` error if no `Crate` with the given id is found.
+    /// * Returns a `400` error if any requested features are not exposed by the `Crate`.
+    fn check_graph_query_features(
+        query: &DependencyGraphQuery,
+        state: &State,
+    ) -> Result<(), Rejection> {
+        let root = &query.roots[0];
+
+        match state.get_unknown_features(&root.name, &root.features) {
+            None => Err(reject::custom(VaultError::crate_not_found(
+                root.name.clone(),
+                state,
+            ))),
+            Some(unknown_features) if !unknown_features.is_empty() => Err(reject::custom(
+                VaultError::UnknownFeatures(vec![String::from("features")], unknown_features),
+            )),
+            Some(_) => Ok(()),
+        }
+    }
+
+    /// Checks that the crates, features, targets and cfg names referenced by a `DependencyGraphQuery` exist.
+    ///
+    /// # Errors
+    /// * Returns a `400` error if the `target`, `host`, `cfgs` or `exclude` fields reference nonexistent values.
+    /// * Returns a `404` error if any root crate is not found.
+    /// * Returns a `400` error if any requested features are not exposed by their root crate.
+    fn validate_dependency_graph_query(
+        query: &DependencyGraphQuery,
+        state: &State,
+    ) -> Result<(), Rejection> {
         let mut nonexistent_options: Vec<String> = vec![];
 
-        // check if target was provided and exists
-        if let Some(target) = target_option.clone() {
-            if !state.targets().contains_key(&target) {
-                nonexistent_options.push(String::from("target"));
+        for (field, triple) in [("target", &query.target), ("host", &query.host)] {
+            if triple
+                .as_ref()
+                .is_some_and(|triple| !state.targets().contains_key(triple))
+            {
+                nonexistent_options.push(String::from(field));
             }
         }
 
-        // check if cfg name was provided and exists
-        if let Some(cfg_name) = cfg_name_option.clone() {
-            if !state.cfg_names().contains(&cfg_name) {
-                nonexistent_options.push(String::from("cfg_name"));
+        for (index, cfg_name) in query.cfgs.iter().enumerate() {
+            if !state.cfg_names().contains(cfg_name) {
+                nonexistent_options.push(format!("cfgs[{index}]"));
+            }
+        }
+
+        for (index, crate_id) in query.exclude.iter().enumerate() {
+            if !state.crates().contains_key(crate_id) {
+                nonexistent_options.push(format!("exclude[{index}]"));
             }
         }
 
-        if nonexistent_options.is_empty() {
+        if !nonexistent_options.is_empty() {
+            return Err(reject::custom(VaultError::NonexistentOptions(
+                nonexistent_options,
+            )));
+        }
+
+        // check if all root crates and requested features exist
+        let mut unknown_feature_fields: Vec<String> = vec![];
+        let mut unknown_features: Vec<String> = vec![];
+        for (root_index, root) in query.roots.iter().enumerate() {
+            let Some(root_unknown_features) =
+                state.get_unknown_features(&root.name, &root.features)
+            else {
+                return Err(reject::custom(VaultError::crate_not_found(
+                    root.name.clone(),
+                    state,
+                )));
+            };
+
+            for (feature_index, feature_name) in root.features.iter().enumerate() {
+                if root_unknown_features.contains(feature_name) {
+                    unknown_feature_fields
+                        .push(format!("roots[{root_index}].features[{feature_index}]"));
+                    unknown_features.push(feature_name.clone());
+                }
+            }
+        }
+
+        if !unknown_features.is_empty() {
+            return Err(reject::custom(VaultError::UnknownFeatures(
+                unknown_feature_fields,
+                unknown_features,
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Computes the `DependencyGraph` described by a validated `DependencyGraphQuery`, exported in its format.
+    ///
+    /// Only JSON responses are cached, as other formats are rarely requested.
+    ///
+    /// # Errors
+    /// * Returns a `404` error if any root crate is not found.
+    /// * Returns a `503` error if the `ComputePool` is saturated.
+    async fn run_dependency_graph_query(
+        query: DependencyGraphQuery,
+        state: State,
+        pool: ComputePool,
+    ) -> Result<impl Reply, Rejection> {
+        pool.run(move || {
+            let format = query.format;
+            let dependency_graph = if format == GraphFormat::Json {
+                state
+                    .query_cached_dependency_graph(&query)
+                    .map(|dependency_graph| dependency_graph.to_string())
+            } else {
+                state
+                    .query_dependency_graph(&query)
+                    .map(|dependency_graph| dependency_graph.export(format))
+            };
+
+            dependency_graph.map_or_else(
+                || {
+                    Err(reject::custom(VaultError::crate_not_found(
+                        query.roots[0].name.clone(),
+                        &state,
+                    )))
+                },
+                |dependency_graph| {
+                    Ok(reply::with_header(
+                        dependency_graph,
+                        "content-type",
+                        format.content_type(),
+                    ))
+                },
+            )
+        })
+        .await?
+    }
+
+    /// Returns the `GraphFormat` requested by the `format` query option, or else by the `Accept` header.
+    ///
+    /// Defaults to `GraphFormat::Json` if neither names a supported format.
+    ///
+    /// # Errors
+    /// * Returns a `400` error if the `format` query option is not a supported format.
+    fn get_graph_format(
+        query_param_map: &mut HashMap<String, String>,
+        accept: Option<&str>,
+    ) -> Result<GraphFormat, Rejection> {
+        query_param_map.remove("format").map_or_else(
+            || {
+                Ok(accept
+                    .and_then(GraphFormat::from_accept_header)
+                    .unwrap_or(GraphFormat::Json))
+            },
+            |format| {
+                format.parse::<GraphFormat>().map_err(|_| {
+                    reject::custom(VaultError::NonexistentOptions(vec![String::from("format")]))
+                })
+            },
+        )
+    }
+
+    /// Parses a `Cargo.toml` manifest and the validated query options of a `DependencyGraph` request for it.
+    ///
+    /// # Errors
+    /// * Returns a `400` error if the manifest is not valid UTF-8 or cannot be parsed.
+    /// * Returns a `400` error if the manifest depends on crates not in the registry.
+    /// * Returns a `400` error if any query options have improper values.
+    /// * Returns a `400` error if any requested features are not exposed by the manifest.
+    fn get_manifest_query(
+        manifest: &Bytes,
+        query_param_map: HashMap<String, String>,
+        state: &State,
+    ) -> Result<(Crate, DependencyGraphQuery), Rejection> {
+        let manifest = str::from_utf8(manifest)
+            .map_err(|_| String::from("Manifest is not valid UTF-8."))
+            .and_then(|manifest| Crate::from_manifest(manifest).map_err(|err| err.to_string()))
+            .map_err(|message| reject::custom(VaultError::InvalidManifest(message)))?;
+
+        let query = parse_graph_query(&manifest.name, query_param_map, state)?;
+
+        // check if all dependencies exist
+        let unknown_dependencies = state.get_unknown_dependencies(&manifest);
+        if !unknown_dependencies.is_empty() {
+            return Err(reject::custom(VaultError::UnknownDependencies(
+                unknown_dependencies,
+            )));
+        }
+
+        // check if all requested features exist
+        let unknown_features: Vec<String> = query.roots[0]
+            .features
+            .iter()
+            .filter(|feature_name| !manifest.features.contains_key(*feature_name))
+            .cloned()
+            .collect();
+        if !unknown_features.is_empty() {
+            return Err(reject::custom(VaultError::UnknownFeatures(
+                vec![String::from("features")],
+                unknown_features,
+            )));
+        }
+
+        Ok((manifest, query))
+    }
+
+    /// Returns the `SbomFormat` requested by the `format` query option, defaulting to `SbomFormat::CycloneDx`.
+    ///
+    /// # Errors
+    /// * Returns a `400` error if the `format` query option is not a supported format.
+    fn get_sbom_format(
+        query_param_map: &mut HashMap<String, String>,
+    ) -> Result<SbomFormat, Rejection> {
+        query_param_map
+            .remove("format")
+            .map_or(Ok(SbomFormat::CycloneDx), |format| {
+                format.parse::<SbomFormat>().map_err(|_| {
+                    reject::custom(VaultError::NonexistentOptions(vec![String::from("format")]))
+                })
+            })
+    }
+
+    /// Parses the query options of a `DependencyGraph` request into a `DependencyGraphQuery` for a single root crate,
+    /// without checking the root crate or its features.
+    ///
+    /// # Errors
+    /// * Returns a `400` error if the `target` or `cfg_name` query options reference nonexistent values.
+    /// * Returns a `400` error if the `default_features`, `all_features` or `infer_proc_macros` query options are not
+    ///   booleans.
+    /// * Returns a `400` error if the `resolver` query option is not `1` or `2`.
+    /// * Returns a `400` error if the `potential_dependencies` query option is not a non-negative integer.
+    fn parse_graph_query(
+        crate_id: &str,
+        mut query_param_map: HashMap<String, String>,
+        state: &State,
+    ) -> Result<DependencyGraphQuery, Rejection> {
+        let mut nonexistent_options: Vec<String> = vec![];
+        let mut options = DependencyGraphOptions::default();
+
+        let features_option = query_param_map.remove("features");
+        let (target_option, cfg_name_option) =
+            get_compiler_options(&mut query_param_map, state, &mut nonexistent_options);
+
+        // check if host was provided and exists
+        if let Some(host) = query_param_map.remove("host") {
+            if state.targets().contains_key(&host) {
+                options.host = Some(host);
+            } else {
+                nonexistent_options.push(String::from("host"));
+            }
+        }
+
+        // check if resolver was provided and is supported
+        if let Some(resolver) = query_param_map.remove("resolver") {
+            match resolver.as_str() {
+                "1" => options.resolver = Resolver::Unified,
+                "2" => options.resolver = Resolver::V2,
+                _ => nonexistent_options.push(String::from("resolver")),
+            }
+        }
+
+        // check if default features toggle was provided and is a boolean
+        if let Some(default_features) = query_param_map.remove("default_features") {
+            match default_features.parse::<bool>() {
+                Ok(default_features) => options.default_features = default_features,
+                Err(_) => nonexistent_options.push(String::from("default_features")),
+            }
+        }
+
+        // check if all features toggle was provided and is a boolean
+        if let Some(all_features) = query_param_map.remove("all_features") {
+            match all_features.parse::<bool>() {
+                Ok(all_features) => options.all_features = all_features,
+                Err(_) => nonexistent_options.push(String::from("all_features")),
+            }
+        }
+
+        // check if proc macro inference toggle was provided and is a boolean
+        if let Some(infer_proc_macros) = query_param_map.remove("infer_proc_macros") {
+            match infer_proc_macros.parse::<bool>() {
+                Ok(infer_proc_macros) => options.infer_proc_macros = infer_proc_macros,
+                Err(_) => nonexistent_options.push(String::from("infer_proc_macros")),
+            }
+        }
+
+        // check if potential dependency limit was provided and is a number
+        if let Some(potential_dependencies) = query_param_map.remove("potential_dependencies") {
+            match potential_dependencies.parse::<usize>() {
+                Ok(limit) => options.potential_dependencies = Some(limit),
+                Err(_) => nonexistent_options.push(String::from("potential_dependencies")),
+            }
+        }
+
+        if !nonexistent_options.is_empty() {
+            return Err(reject::custom(VaultError::NonexistentOptions(
+                nonexistent_options,
+            )));
+        }
+
+        Ok(DependencyGraphQuery::new(
+            crate_id,
+            parse_features(features_option),
+            &target_option,
+            &cfg_name_option,
+            &options,
+        ))
+    }
+
+    /// Returns the cost of enabling each feature of the `Crate` with the given id, if found.
+    ///
+    /// The `features` query option selects the features to analyze, defaulting to every feature. The
+    /// `default_features` and `all_features` query options are ignored, as costs are computed both over the default
+    /// features and over no default features.
+    ///
+    /// # Errors
+    /// * Returns a `404` error if no `Crate` with the given id is found.
+    /// * Returns a `400` error if the `target`, `host` or `cfg_name` query options reference nonexistent values.
+    /// * Returns a `400` error if the `default_features`, `all_features` or `infer_proc_macros` query options are not
+    ///   booleans.
+    /// * Returns a `400` error if the `resolver` query option is not `1` or `2`.
+    /// * Returns a `400` error if any requested features are not exposed by the `Crate`.
+    /// * Returns a `400` error if more than `MAX_FEATURE_COSTS` features would be analyzed.
+    /// * Returns a `503` error if the `ComputePool` is saturated.
+    pub async fn get_feature_costs(
+        crate_id: String,
+        query_param_map: HashMap<String, String>,
+        state: State,
+        pool: ComputePool,
+    ) -> Result<impl Reply, Rejection> {
+        let query = parse_graph_query(&crate_id, query_param_map, &state)?;
+        let features = &query.roots[0].features;
+
+        // check if the features to analyze are within the limit, before checking that each exists
+        let feature_count = if features.is_empty() {
+            state.crates().get(&crate_id).map_or(0, |crate_val| {
+                crate_val
+                    .features
+                    .keys()
+                    .filter(|feature_name| *feature_name != "default")
+                    .count()
+            })
+        } else {
+            features.len()
+        };
+        if feature_count > MAX_FEATURE_COSTS {
+            return Err(reject::custom(VaultError::TooManyFeatures(
+                MAX_FEATURE_COSTS,
+            )));
+        }
+
+        check_graph_query_features(&query, &state)?;
+
+        pool.run(move || {
             state
-                .get_dependency_graph(
+                .get_feature_costs(
                     &crate_id,
-                    features_option.map_or_else(Vec::new, |features| {
-                        if features.contains(',') {
-                            features
-                                .split(',')
-                                .map(String::from)
-                                .collect::<Vec<String>>()
-                        } else {
-                            vec![features]
-                        }
-                    }),
-                    &target_option,
-                    &cfg_name_option,
+                    &query.roots[0].features,
+                    &query.target,
+                    &query.cfg_name(),
+                    &query.options(),
                 )
                 .map_or_else(
-                    || Err(reject::custom(VaultError::CrateNotFound(crate_id))),
-                    |dependency_graph| Ok(reply::json(&dependency_graph)),
+                    || {
+                        Err(reject::custom(VaultError::crate_not_found(
+                            crate_id, &state,
+                        )))
+                    },
+                    |feature_costs| Ok(reply::json(&feature_costs)),
                 )
-        } else {
-            Err(reject::custom(VaultError::NonexistentOptions(
-                nonexistent_options,
-            )))
+        })
+        .await?
+    }
+
+    /// Returns the smallest change to the features of the `Crate` with the given id which removes an unwanted crate
+    /// from its `DependencyGraph`, if found.
+    ///
+    /// # Errors
+    /// * Returns a `404` error if no `Crate` with the given id is found.
+    /// * Returns a `400` error if the `remove` query option is missing or references a nonexistent crate.
+    /// * Returns a `400` error if the `target`, `host` or `cfg_name` query options reference nonexistent values.
+    /// * Returns a `400` error if the `default_features`, `all_features` or `infer_proc_macros` query options are not
+    ///   booleans.
+    /// * Returns a `400` error if the `resolver` query option is not `1` or `2`.
+    /// * Returns a `400` error if any current or required features are not exposed by the `Crate`.
+    /// * Returns a `503` error if the `ComputePool` is saturated.
+    pub async fn get_feature_suggestion(
+        crate_id: String,
+        mut query_param_map: HashMap<String, String>,
+        state: State,
+        pool: ComputePool,
+    ) -> Result<impl Reply, Rejection> {
+        let required_features = parse_features(query_param_map.remove("required"));
+
+        // check if the unwanted crate was provided and exists
+        let unwanted_crate_id = query_param_map
+            .remove("remove")
+            .filter(|unwanted_crate_id| state.crates().contains_key(unwanted_crate_id));
+
+        let query = parse_graph_query(&crate_id, query_param_map, &state)?;
+        let features = &query.roots[0].features;
+
+        let Some(unwanted_crate_id) = unwanted_crate_id else {
+            return Err(reject::custom(VaultError::NonexistentOptions(vec![
+                String::from("remove"),
+            ])));
+        };
+
+        let mut all_requested_features = features.clone();
+        all_requested_features.extend(required_features.iter().cloned());
+
+        // check if all current and required features exist
+        match state.get_unknown_features(&crate_id, &all_requested_features) {
+            None => {
+                return Err(reject::custom(VaultError::crate_not_found(
+                    crate_id, &state,
+                )))
+            }
+            Some(unknown_features) if !unknown_features.is_empty() => {
+                // report whichever of the current and required features requested the unknown ones
+                let parameters = [("features", features), ("required", &required_features)]
+                    .into_iter()
+                    .filter(|(_, requested_features)| {
+                        requested_features
+                            .iter()
+                            .any(|feature_name| unknown_features.contains(feature_name))
+                    })
+                    .map(|(parameter, _)| String::from(parameter))
+                    .collect();
+
+                return Err(reject::custom(VaultError::UnknownFeatures(
+                    parameters,
+                    unknown_features,
+                )));
+            }
+            Some(_) => (),
+        }
+
+        pool.run(move || {
+            state
+                .get_feature_suggestion(
+                    &crate_id,
+                    &query.roots[0].features,
+                    &required_features,
+                    &unwanted_crate_id,
+                    &query.target,
+                    &query.cfg_name(),
+                    &query.options(),
+                )
+                .map_or_else(
+                    || {
+                        Err(reject::custom(VaultError::crate_not_found(
+                            crate_id, &state,
+                        )))
+                    },
+                    |feature_suggestion| Ok(reply::json(&feature_suggestion)),
+                )
+        })
+        .await?
+    }
+
+    /// Returns the groups of crates which depend on each other in a cycle, ranked by size.
+    ///
+    /// # Errors
+    /// * Returns a `400` error if the `kinds` query option references nonexistent dependency kinds.
+    /// * Returns a `503` error if the `ComputePool` is saturated.
+    pub async fn get_cycles(
+        mut query_param_map: HashMap<String, String>,
+        state: State,
+        pool: ComputePool,
+    ) -> Result<impl Reply, Rejection> {
+        let kinds = match query_param_map.remove("kinds") {
+            // follow every kind of dependency by default
+            None => vec![
+                DependencyKind::Normal,
+                DependencyKind::Build,
+                DependencyKind::Dev,
+            ],
+            Some(kinds) => kinds
+                .split(',')
+                .filter(|kind| !kind.is_empty())
+                .map(|kind| match kind {
+                    "normal" => Ok(DependencyKind::Normal),
+                    "build" => Ok(DependencyKind::Build),
+                    "dev" => Ok(DependencyKind::Dev),
+                    _ => Err(reject::custom(VaultError::NonexistentOptions(vec![
+                        String::from("kinds"),
+                    ]))),
+                })
+                .collect::<Result<Vec<DependencyKind>, Rejection>>()?,
+        };
+
+        pool.run(move || reply::json(&state.get_cycles(&kinds)))
+            .await
+    }
+
+    /// Returns the comma-separated features of a query option, if provided.
+    ///
+    /// # Arguments
+    /// * `features_option` - the value of the query option.
+    fn parse_features(features_option: Option<String>) -> Vec<String> {
+        features_option.map_or_else(Vec::new, |features| {
+            features
+                .split(',')
+                .filter(|feature| !feature.is_empty())
+                .map(String::from)
+                .collect::<Vec<String>>()
+        })
+    }
+
+    /// Returns the `target` and `cfg_name` query options, if provided.
+    ///
+    /// Options referencing nonexistent values are added to `nonexistent_options`.
+    ///
+    /// # Arguments
+    /// * `query_param_map` - the query parameters of the request.
+    /// * `state` - the `Graph` containing the crates.io data.
+    /// * `nonexistent_options` - the list of options provided with improper values.
+    fn get_compiler_options(
+        query_param_map: &mut HashMap<String, String>,
+        state: &State,
+        nonexistent_options: &mut Vec<String>,
+    ) -> (Option<String>, Option<String>) {
+        let target_option = query_param_map.remove("target");
+        let cfg_name_option = query_param_map.remove("cfg_name");
+
+        // check if target was provided and exists
+        if let Some(target) = &target_option {
+            if !state.targets().contains_key(target) {
+                nonexistent_options.push(String::from("target"));
+            }
         }
+
+        // check if cfg name was provided and exists
+        if let Some(cfg_name) = &cfg_name_option {
+            if !state.cfg_names().contains(cfg_name) {
+                nonexistent_options.push(String::from("cfg_name"));
+            }
+        }
+
+        (target_option, cfg_name_option)
     }
 
     /// Returns the `DependencyGraph` of a random `Crate`.
-    pub async fn get_random_dependency_graph(state: State) -> Result<impl Reply, Rejection> {
-        Ok(reply::json(
-            &state
-                .get_dependency_graph(&state.crates().random().name, vec![], &None, &None)
-                .unwrap(),
-        ))
+    ///
+    /// # Errors
+    /// * Returns a `503` error if the `ComputePool` is saturated.
+    pub async fn get_random_dependency_graph(
+        state: State,
+        pool: ComputePool,
+    ) -> Result<impl Reply, Rejection> {
+        pool.run(move || {
+            reply::json(
+                &state
+                    .get_dependency_graph(
+                        &state.crates().random().name,
+                        vec![],
+                        &None,
+                        &None,
+                        &DependencyGraphOptions::default(),
+                    )
+                    .unwrap(),
+            )
+        })
+        .await
     }
 }
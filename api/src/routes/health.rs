@@ -0,0 +1,71 @@
+use super::utils::SharedState;
+use warp::{Filter, Rejection, Reply};
+
+pub use handlers::Readiness;
+
+/// Wraps all health check routes, which are served while the `Graph` is loading.
+#[must_use]
+pub fn routes(
+    state: SharedState,
+) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+    live().or(ready(state))
+}
+
+/// Reports that the server is running.
+fn live() -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+    warp::path!("health" / "live")
+        .and(warp::get())
+        .and_then(handlers::live)
+}
+
+/// Reports whether the `Graph` has loaded, along with the progress of loading it.
+fn ready(state: SharedState) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+    warp::path!("health" / "ready")
+        .and(warp::get())
+        .and_then(move || handlers::ready(state.clone()))
+}
+
+mod handlers {
+    use super::SharedState;
+    use serde::Serialize;
+    use vault_graph::LoadStatus;
+    use warp::{http::StatusCode, reply, Rejection, Reply};
+
+    /// Reports that the server is running.
+    pub async fn live() -> Result<impl Reply, Rejection> {
+        Ok(reply::json(&Liveness { live: true }))
+    }
+
+    /// Reports whether the `Graph` has loaded, with a `503` status until it has.
+    pub async fn ready(state: SharedState) -> Result<impl Reply, Rejection> {
+        let readiness = Readiness {
+            ready: state.is_ready(),
+            load: state.load_status(),
+        };
+
+        let status = if readiness.ready {
+            StatusCode::OK
+        } else {
+            StatusCode::SERVICE_UNAVAILABLE
+        };
+
+        Ok(reply::with_status(reply::json(&readiness), status))
+    }
+
+    /// A struct reporting that the server is running.
+    #[derive(Serialize)]
+    struct Liveness {
+        /// Whether the server is running, which is always the case when it responds.
+        live: bool,
+    }
+
+    /// A struct reporting whether the `Graph` has loaded.
+    #[derive(Serialize)]
+    pub struct Readiness {
+        /// Whether the `Graph` has loaded and data routes are being served.
+        pub ready: bool,
+
+        /// The progress of loading the first `Graph`.
+        pub load: LoadStatus,
+    }
+}
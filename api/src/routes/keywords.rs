@@ -1,9 +1,11 @@
-use super::utils::{State, VaultError};
+use super::utils::{ready, SharedState, State, VaultError};
 use warp::{Filter, Rejection, Reply};
 
 /// Wraps all `Keyword` routes.
 #[must_use]
-pub fn routes(state: State) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+pub fn routes(
+    state: SharedState,
+) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
     get_keyword(state.clone())
         .or(random(state.clone()))
         .or(search(state))
@@ -13,24 +15,29 @@ pub fn routes(state: State) -> impl Filter<Extract = (impl Reply,), Error = Reje
 ///
 /// # Errors
 /// * Returns a `404` error if no `Keyword` with the given id is found.
-fn get_keyword(state: State) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+fn get_keyword(
+    state: SharedState,
+) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
     warp::path!("keywords" / String)
         .and(warp::get())
-        .and_then(move |keyword_id| handlers::get_keyword(keyword_id, state.clone()))
+        .and(ready(state.clone()))
+        .and_then(move |keyword_id| handlers::get_keyword(keyword_id, state.load()))
 }
 
 /// Returns a random `Keyword`.
-fn random(state: State) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+fn random(state: SharedState) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
     warp::path!("random" / "keywords")
         .and(warp::get())
-        .and_then(move || handlers::random(state.clone()))
+        .and(ready(state.clone()))
+        .and_then(move || handlers::random(state.load()))
 }
 
 /// Searches for keywords matching the given search term.
-fn search(state: State) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+fn search(state: SharedState) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
     warp::path!("search" / "keywords" / String)
         .and(warp::get())
-        .and_then(move |search_term| handlers::search(search_term, state.clone()))
+        .and(ready(state.clone()))
+        .and_then(move |search_term| handlers::search(search_term, state.load()))
 }
 
 mod handlers {
@@ -0,0 +1,361 @@
+#![allow(clippy::cast_precision_loss)]
+
+use super::utils::SharedState;
+use prometheus::{
+    exponential_buckets, Histogram, HistogramOpts, HistogramVec, IntCounterVec, Opts, Registry,
+};
+use std::{sync::LazyLock, time::Duration};
+use vault_graph::DependencyGraph;
+use warp::{log::Info, Filter, Rejection, Reply};
+
+/// The routes requests are counted by, with parameters in braces.
+///
+/// Requests for any other path are counted under `unmatched`, keeping the number of series bounded.
+const ROUTES: [&str; 30] = [
+    "/categories",
+    "/categories/{category}",
+    "/categories/{category}/footprints",
+    "/compiler/cfg-names",
+    "/compiler/targets",
+    "/crates/{crate}",
+    "/crates/{crate}/feature-costs",
+    "/crates/{crate}/feature-suggestion",
+    "/cycles",
+    "/graph",
+    "/graph/{crate}",
+    "/graph/{crate}/dominators",
+    "/health/live",
+    "/health/ready",
+    "/keywords/{keyword}",
+    "/lockfile/analysis",
+    "/manifest/graph",
+    "/manifest/sbom",
+    "/metadata/comparison",
+    "/metrics",
+    "/random/categories",
+    "/random/crates",
+    "/random/graph",
+    "/random/keywords",
+    "/sbom/{crate}",
+    "/search/categories/{term}",
+    "/search/crates/{term}",
+    "/search/keywords/{term}",
+    "/state/cache",
+    "/state/last-updated",
+];
+
+/// The metrics recorded as the server runs.
+static METRICS: LazyLock<Metrics> = LazyLock::new(Metrics::new);
+
+/// The metrics recorded as the server runs, as opposed to those read from the current `Graph` when scraped.
+struct Metrics {
+    /// The registry of every recorded metric.
+    registry: Registry,
+
+    /// The number of requests served, by route, method and status.
+    requests: IntCounterVec,
+
+    /// The time taken to serve requests, by route and method.
+    request_duration: HistogramVec,
+
+    /// The number of crates in computed dependency graphs.
+    dependency_graph_crates: Histogram,
+
+    /// The number of dependencies in computed dependency graphs.
+    dependency_graph_edges: Histogram,
+
+    /// The time taken to compute dependency graphs.
+    dependency_graph_duration: Histogram,
+
+    /// The number of registry refreshes, by outcome.
+    refreshes: IntCounterVec,
+
+    /// The time taken by registry refreshes.
+    refresh_duration: Histogram,
+}
+
+impl Metrics {
+    /// Creates and registers every recorded metric.
+    fn new() -> Self {
+        let registry = Registry::new();
+
+        let metrics = Self {
+            requests: IntCounterVec::new(
+                Opts::new("vault_http_requests_total", "Requests served."),
+                &["route", "method", "status"],
+            )
+            .unwrap(),
+            request_duration: HistogramVec::new(
+                HistogramOpts::new(
+                    "vault_http_request_duration_seconds",
+                    "Time taken to serve requests.",
+                ),
+                &["route", "method"],
+            )
+            .unwrap(),
+            dependency_graph_crates: Histogram::with_opts(
+                HistogramOpts::new(
+                    "vault_dependency_graph_crates",
+                    "Crates in computed dependency graphs.",
+                )
+                .buckets(exponential_buckets(1.0, 4.0, 8).unwrap()),
+            )
+            .unwrap(),
+            dependency_graph_edges: Histogram::with_opts(
+                HistogramOpts::new(
+                    "vault_dependency_graph_edges",
+                    "Dependencies in computed dependency graphs.",
+                )
+                .buckets(exponential_buckets(1.0, 4.0, 9).unwrap()),
+            )
+            .unwrap(),
+            dependency_graph_duration: Histogram::with_opts(
+                HistogramOpts::new(
+                    "vault_dependency_graph_duration_seconds",
+                    "Time taken to compute dependency graphs.",
+                )
+                .buckets(exponential_buckets(0.0005, 4.0, 9).unwrap()),
+            )
+            .unwrap(),
+            refreshes: IntCounterVec::new(
+                Opts::new("vault_graph_refreshes_total", "Registry refreshes."),
+                &["outcome"],
+            )
+            .unwrap(),
+            refresh_duration: Histogram::with_opts(
+                HistogramOpts::new(
+                    "vault_graph_refresh_duration_seconds",
+                    "Time taken by registry refreshes.",
+                )
+                .buckets(exponential_buckets(1.0, 2.0, 12).unwrap()),
+            )
+            .unwrap(),
+            registry,
+        };
+
+        let registry = &metrics.registry;
+        registry
+            .register(Box::new(metrics.requests.clone()))
+            .unwrap();
+        registry
+            .register(Box::new(metrics.request_duration.clone()))
+            .unwrap();
+        registry
+            .register(Box::new(metrics.dependency_graph_crates.clone()))
+            .unwrap();
+        registry
+            .register(Box::new(metrics.dependency_graph_edges.clone()))
+            .unwrap();
+        registry
+            .register(Box::new(metrics.dependency_graph_duration.clone()))
+            .unwrap();
+        registry
+            .register(Box::new(metrics.refreshes.clone()))
+            .unwrap();
+        registry
+            .register(Box::new(metrics.refresh_duration.clone()))
+            .unwrap();
+
+        metrics
+    }
+}
+
+/// Records a served request, intended for use with `warp::log::custom`.
+///
+/// # Arguments
+/// * `info` - the `Info` of the request.
+pub fn observe_request(info: &Info<'_>) {
+    let route = get_route(info.path());
+    let method = info.method().as_str();
+
+    METRICS
+        .requests
+        .with_label_values(&[route, method, info.status().as_str()])
+        .inc();
+    METRICS
+        .request_duration
+        .with_label_values(&[route, method])
+        .observe(info.elapsed().as_secs_f64());
+}
+
+/// Records a computed dependency graph, intended for use with `Graph::set_dependency_graph_observer`.
+///
+/// # Arguments
+/// * `dependency_graph` - the computed `DependencyGraph`.
+/// * `duration` - the time taken to compute the dependency graph.
+pub fn observe_dependency_graph(dependency_graph: &DependencyGraph, duration: Duration) {
+    METRICS
+        .dependency_graph_crates
+        .observe(dependency_graph.crates.len() as f64);
+    METRICS
+        .dependency_graph_edges
+        .observe(dependency_graph.dependencies.len() as f64);
+    METRICS
+        .dependency_graph_duration
+        .observe(duration.as_secs_f64());
+}
+
+/// Records a registry refresh.
+///
+/// # Arguments
+/// * `duration` - the time taken by the refresh.
+/// * `succeeded` - whether the refreshed graph replaced the served one.
+pub fn observe_refresh(duration: Duration, succeeded: bool) {
+    METRICS
+        .refreshes
+        .with_label_values(&[if succeeded { "success" } else { "failure" }])
+        .inc();
+    METRICS.refresh_duration.observe(duration.as_secs_f64());
+}
+
+/// Returns the route a path is counted under.
+///
+/// # Arguments
+/// * `path` - the path of the request.
+fn get_route(path: &str) -> &'static str {
+    let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+
+    ROUTES
+        .into_iter()
+        .find(|route| {
+            let route_segments: Vec<&str> = route.trim_matches('/').split('/').collect();
+
+            route_segments.len() == segments.len()
+                && route_segments
+                    .iter()
+                    .zip(&segments)
+                    .all(|(route_segment, segment)| {
+                        route_segment.starts_with('{') || route_segment == segment
+                    })
+        })
+        .unwrap_or("unmatched")
+}
+
+/// Wraps all metrics routes, which are served while the `Graph` is loading.
+#[must_use]
+pub fn routes(
+    state: SharedState,
+) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+    warp::path!("metrics")
+        .and(warp::get())
+        .and_then(move || handlers::get_metrics(state.clone()))
+}
+
+mod handlers {
+    use super::{SharedState, METRICS};
+    use prometheus::{
+        core::Collector, Encoder, Gauge, GaugeVec, IntCounter, IntGauge, Opts, TextEncoder,
+    };
+    use warp::{reply, Rejection, Reply};
+
+    /// Returns every metric in the Prometheus text format.
+    pub async fn get_metrics(state: SharedState) -> Result<impl Reply, Rejection> {
+        let mut metric_families = METRICS.registry.gather();
+        metric_families.extend(
+            get_graph_metrics(&state)
+                .into_iter()
+                .flat_map(|metric| metric.collect()),
+        );
+        metric_families.sort_by(|first, second| first.name().cmp(second.name()));
+
+        let encoder = TextEncoder::new();
+        let mut buffer = vec![];
+        encoder.encode(&metric_families, &mut buffer).unwrap();
+
+        Ok(reply::with_header(
+            buffer,
+            "content-type",
+            encoder.format_type(),
+        ))
+    }
+
+    /// Returns the metrics read from the current `Graph`, which change whenever the registry is refreshed.
+    ///
+    /// # Arguments
+    /// * `state` - the `SharedState` holding the served graph.
+    fn get_graph_metrics(state: &SharedState) -> Vec<Box<dyn Collector>> {
+        let ready = IntGauge::new("vault_ready", "Whether the registry graph has loaded.").unwrap();
+
+        if !state.is_ready() {
+            return vec![Box::new(ready)];
+        }
+
+        ready.set(1);
+        let state = state.load();
+
+        let dataset_age = IntGauge::new(
+            "vault_dataset_age_seconds",
+            "Time since the registry graph was last updated.",
+        )
+        .unwrap();
+        dataset_age.set(i64::try_from(state.time_since_last_update()).unwrap_or(i64::MAX));
+
+        let cache_stats = state.dependency_graph_cache().stats();
+        let cache_hits = IntCounter::new(
+            "vault_dependency_graph_cache_hits_total",
+            "Dependency graph lookups served from the cache.",
+        )
+        .unwrap();
+        cache_hits.inc_by(cache_stats.hits);
+        let cache_misses = IntCounter::new(
+            "vault_dependency_graph_cache_misses_total",
+            "Dependency graph lookups which required computing a dependency graph.",
+        )
+        .unwrap();
+        cache_misses.inc_by(cache_stats.misses);
+        let cache_hit_ratio = Gauge::new(
+            "vault_dependency_graph_cache_hit_ratio",
+            "Fraction of dependency graph lookups served from the cache since the last refresh.",
+        )
+        .unwrap();
+        if cache_stats.hits + cache_stats.misses > 0 {
+            cache_hit_ratio
+                .set(cache_stats.hits as f64 / (cache_stats.hits + cache_stats.misses) as f64);
+        }
+        let cache_entries = IntGauge::new(
+            "vault_dependency_graph_cache_entries",
+            "Dependency graphs kept in the cache.",
+        )
+        .unwrap();
+        cache_entries.set(i64::try_from(cache_stats.entries).unwrap_or(i64::MAX));
+        let cache_capacity = IntGauge::new(
+            "vault_dependency_graph_cache_capacity",
+            "Max number of dependency graphs kept in the cache.",
+        )
+        .unwrap();
+        cache_capacity.set(i64::try_from(cache_stats.capacity).unwrap_or(i64::MAX));
+
+        let load_report = state.load_report();
+        let load_duration = Gauge::new(
+            "vault_load_duration_seconds",
+            "Time taken to load the registry graph.",
+        )
+        .unwrap();
+        load_duration.set(load_report.seconds);
+        let load_phase_duration = GaugeVec::new(
+            Opts::new(
+                "vault_load_phase_duration_seconds",
+                "Time taken by each phase of loading the registry graph.",
+            ),
+            &["phase"],
+        )
+        .unwrap();
+        for phase in &load_report.phases {
+            load_phase_duration
+                .with_label_values(&[phase.name])
+                .set(phase.seconds);
+        }
+
+        vec![
+            Box::new(ready),
+            Box::new(dataset_age),
+            Box::new(cache_hits),
+            Box::new(cache_misses),
+            Box::new(cache_hit_ratio),
+            Box::new(cache_entries),
+            Box::new(cache_capacity),
+            Box::new(load_duration),
+            Box::new(load_phase_duration),
+        ]
+    }
+}
@@ -1,17 +1,36 @@
 pub mod categories;
 pub mod compiler;
 pub mod crates;
+pub mod health;
 pub mod keywords;
+pub mod metrics;
 pub mod state;
 pub mod utils;
 
-use utils::State;
+use utils::{ComputePool, SharedState};
 use warp::{Filter, Rejection, Reply};
 
-/// Wraps all routes.
+/// Wraps all routes, running expensive graph computations on a default `ComputePool`.
 #[must_use]
-pub fn get(state: State) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
-    crates::routes(state.clone())
+pub fn get(
+    state: impl Into<SharedState>,
+) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+    with_compute_pool(state, ComputePool::default())
+}
+
+/// Wraps all routes, running expensive graph computations on the given `ComputePool`.
+///
+/// Health checks and metrics are always served, while data routes are unavailable until the `Graph` has loaded.
+#[must_use]
+pub fn with_compute_pool(
+    state: impl Into<SharedState>,
+    pool: ComputePool,
+) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+    let state = state.into();
+
+    health::routes(state.clone())
+        .or(metrics::routes(state.clone()))
+        .or(crates::routes(state.clone(), pool))
         .or(compiler::routes(state.clone()))
         .or(state::routes(state.clone()))
         .or(keywords::routes(state.clone()))
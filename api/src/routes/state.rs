@@ -1,21 +1,34 @@
-use super::utils::State;
+use super::utils::{ready, SharedState, State};
 use warp::{Filter, Rejection, Reply};
 
 pub use handlers::LastUpdated;
 
 /// Wraps all `Graph` state routes.
 #[must_use]
-pub fn routes(state: State) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
-    time_since_last_update(state)
+pub fn routes(
+    state: SharedState,
+) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+    time_since_last_update(state.clone()).or(cache_stats(state))
 }
 
 /// Returns the time (in seconds) since the `Graph` was last updated.
 fn time_since_last_update(
-    state: State,
+    state: SharedState,
 ) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
     warp::path!("state" / "last-updated")
         .and(warp::get())
-        .and_then(move || handlers::time_since_last_update(state.clone()))
+        .and(ready(state.clone()))
+        .and_then(move || handlers::time_since_last_update(state.load()))
+}
+
+/// Returns the size and hit/miss counters of the dependency graph cache.
+fn cache_stats(
+    state: SharedState,
+) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+    warp::path!("state" / "cache")
+        .and(warp::get())
+        .and(ready(state.clone()))
+        .and_then(move || handlers::cache_stats(state.load()))
 }
 
 mod handlers {
@@ -30,6 +43,11 @@ mod handlers {
         }))
     }
 
+    /// Returns the size and hit/miss counters of the dependency graph cache.
+    pub async fn cache_stats(state: State) -> Result<impl Reply, Rejection> {
+        Ok(reply::json(&state.dependency_graph_cache().stats()))
+    }
+
     /// A struct containing the time since the `Graph` was last updated.
     #[derive(Deserialize, Serialize)]
     pub struct LastUpdated {
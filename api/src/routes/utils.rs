@@ -1,74 +1,547 @@
 #![allow(clippy::missing_errors_doc)]
 
-use std::{convert::Infallible, sync::Arc};
-use vault_graph::Graph;
-use warp::{http::StatusCode, reject::Reject, Rejection, Reply};
+use log::error;
+use serde::{Deserialize, Serialize};
+use std::{
+    convert::Infallible,
+    num::NonZeroUsize,
+    panic,
+    sync::{Arc, PoisonError, RwLock},
+    thread,
+    time::Duration,
+};
+use tokio::{sync::Semaphore, task, time};
+use vault_graph::{Graph, LoadError, LoadProgress, LoadStatus, Search};
+use warp::{
+    http::{header::RETRY_AFTER, HeaderValue, StatusCode},
+    reject::{self, Reject},
+    Filter, Rejection, Reply,
+};
 
 /// Shorthand for Arc<Graph>.
 pub type State = Arc<Graph>;
 
+/// A handle to the current `Graph`, which is replaced whenever the registry is refreshed.
+///
+/// The handle may be created before the first `Graph` has loaded, in which case data routes are unavailable until
+/// it is stored.
+#[derive(Clone)]
+pub struct SharedState {
+    /// The current `Graph`, once loaded.
+    state: Arc<RwLock<Option<State>>>,
+
+    /// The progress of loading the first `Graph`.
+    progress: LoadProgress,
+}
+
+impl SharedState {
+    /// Creates a new `SharedState`.
+    ///
+    /// # Arguments
+    /// * `state` - the initial `Graph`.
+    #[must_use]
+    pub fn new(state: State) -> Self {
+        Self {
+            state: Arc::new(RwLock::new(Some(state))),
+            progress: LoadProgress::default(),
+        }
+    }
+
+    /// Creates a new `SharedState` whose first `Graph` is still loading.
+    ///
+    /// # Arguments
+    /// * `progress` - the `LoadProgress` of the first `Graph`.
+    #[must_use]
+    pub fn loading(progress: LoadProgress) -> Self {
+        Self {
+            state: Arc::new(RwLock::new(None)),
+            progress,
+        }
+    }
+
+    /// Returns whether a `Graph` has been loaded.
+    #[must_use]
+    pub fn is_ready(&self) -> bool {
+        self.state
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .is_some()
+    }
+
+    /// Returns the progress of loading the first `Graph`.
+    #[must_use]
+    pub fn load_status(&self) -> LoadStatus {
+        self.progress.status()
+    }
+
+    /// Returns the current `Graph`.
+    ///
+    /// Requests keep using the `Graph` they started with, even if it is replaced in the meantime.
+    ///
+    /// # Panics
+    /// * Panics if no `Graph` has been loaded yet, which `ready` guards against.
+    #[must_use]
+    pub fn load(&self) -> State {
+        self.state
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+            .expect("Graph is not loaded yet")
+    }
+
+    /// Replaces the current `Graph`.
+    ///
+    /// # Arguments
+    /// * `state` - the new `Graph`.
+    pub fn store(&self, state: State) {
+        *self.state.write().unwrap_or_else(PoisonError::into_inner) = Some(state);
+    }
+
+    /// Loads a new `Graph` on a blocking thread and replaces the current `Graph` with it if loading succeeds.
+    ///
+    /// The current `Graph` keeps being served while the new one loads and is kept if loading fails. Both are held in
+    /// memory until the new one is stored and requests using the old one finish.
+    ///
+    /// # Arguments
+    /// * `load` - the function loading the new `Graph`.
+    pub async fn reload(
+        &self,
+        load: impl FnOnce() -> Result<Graph, LoadError> + Send + 'static,
+    ) -> Result<(), LoadError> {
+        let graph = task::spawn_blocking(load)
+            .await
+            .unwrap_or_else(|err| panic::resume_unwind(err.into_panic()))?;
+        self.store(Arc::new(graph));
+
+        Ok(())
+    }
+}
+
+impl From<State> for SharedState {
+    fn from(state: State) -> Self {
+        Self::new(state)
+    }
+}
+
+/// The time after which clients are asked to retry while the first `Graph` is loading, in seconds.
+const LOADING_RETRY_SECONDS: u64 = 10;
+
+/// A filter rejecting requests until the first `Graph` has loaded.
+///
+/// This is applied to each data route after its path and method are matched, so requests to unknown routes are still
+/// rejected as not found while loading.
+///
+/// # Arguments
+/// * `state` - the `SharedState` holding the served graph.
+#[must_use]
+pub fn ready(state: SharedState) -> impl Filter<Extract = (), Error = Rejection> + Clone {
+    warp::any()
+        .and_then(move || {
+            let is_ready = state.is_ready();
+
+            async move {
+                if is_ready {
+                    Ok(())
+                } else {
+                    Err(reject::custom(VaultError::Loading))
+                }
+            }
+        })
+        .untuple_one()
+}
+
+/// The default time a computation may wait for a free slot in a `ComputePool`.
+pub const DEFAULT_QUEUE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A bounded pool running expensive graph computations on blocking threads, off the async executor.
+#[derive(Clone)]
+pub struct ComputePool {
+    /// The slots available for concurrent computations.
+    permits: Arc<Semaphore>,
+
+    /// The max number of computations to run at once.
+    concurrency: usize,
+
+    /// The time a computation may wait for a free slot before being rejected.
+    queue_timeout: Duration,
+}
+
+impl ComputePool {
+    /// Creates a new `ComputePool`.
+    ///
+    /// # Arguments
+    /// * `concurrency` - the max number of computations to run at once.
+    /// * `queue_timeout` - the time a computation may wait for a free slot before being rejected.
+    #[must_use]
+    pub fn new(concurrency: usize, queue_timeout: Duration) -> Self {
+        Self {
+            permits: Arc::new(Semaphore::new(concurrency)),
+            concurrency,
+            queue_timeout,
+        }
+    }
+
+    /// Returns the max number of computations to run at once.
+    #[must_use]
+    pub const fn concurrency(&self) -> usize {
+        self.concurrency
+    }
+
+    /// Returns the time a computation may wait for a free slot before being rejected.
+    #[must_use]
+    pub const fn queue_timeout(&self) -> Duration {
+        self.queue_timeout
+    }
+
+    /// Runs a computation on a blocking thread once a slot is free.
+    ///
+    /// # Arguments
+    /// * `computation` - the computation to run.
+    ///
+    /// # Errors
+    /// * Returns a `503` error if no slot frees up within the queue timeout.
+    pub async fn run<T: Send + 'static>(
+        &self,
+        computation: impl FnOnce() -> T + Send + 'static,
+    ) -> Result<T, Rejection> {
+        let Ok(Ok(permit)) =
+            time::timeout(self.queue_timeout, self.permits.clone().acquire_owned()).await
+        else {
+            return Err(reject::custom(VaultError::Overloaded(
+                self.queue_timeout.as_secs().max(1),
+            )));
+        };
+
+        // the permit is held until the computation finishes, even if the request is dropped in the meantime
+        task::spawn_blocking(move || {
+            let _permit = permit;
+            computation()
+        })
+        .await
+        .map_or_else(|err| panic::resume_unwind(err.into_panic()), Ok)
+    }
+}
+
+impl Default for ComputePool {
+    /// Creates a `ComputePool` allowing one computation per available core.
+    fn default() -> Self {
+        Self::new(
+            thread::available_parallelism().map_or(1, NonZeroUsize::get),
+            DEFAULT_QUEUE_TIMEOUT,
+        )
+    }
+}
+
+/// The max number of similarly named crates suggested when a crate is not found.
+const MAX_SUGGESTIONS: usize = 5;
+
 /// An enum corresponding to custom errors which may occur.
 #[derive(Debug)]
 pub enum VaultError {
     /// If the provided `Category` does not exist.
     CategoryNotFound(String),
 
-    /// If the provided `Crate` does not exist.
-    CrateNotFound(String),
+    /// If the provided `Crate` does not exist, with the names of similarly named crates.
+    CrateNotFound(String, Vec<String>),
+
+    /// If an uploaded manifest cannot be parsed, with a description of the problem.
+    InvalidManifest(String),
+
+    /// If an uploaded lockfile cannot be parsed, with a description of the problem.
+    InvalidLockfile(String),
+
+    /// If uploaded `cargo metadata` output cannot be parsed, with a description of the problem.
+    InvalidMetadata(String),
+
+    /// If an uploaded graph query cannot be parsed, with a description of the problem and the improper fields.
+    InvalidGraphQuery(String, Vec<String>),
 
     /// If the provided `Keyword` does not exist.
     KeywordNotFound(String),
 
     /// If options passed in query parameters do not exist.
     NonexistentOptions(Vec<String>),
+
+    /// If more features were requested than can be analyzed at once, with the max number of features.
+    TooManyFeatures(usize),
+
+    /// If requested features are not exposed by the `Crate`, with the parameters which requested them.
+    UnknownFeatures(Vec<String>, Vec<String>),
+
+    /// If an uploaded manifest depends on crates not in the registry.
+    UnknownDependencies(Vec<String>),
+
+    /// If no slot for graph computation freed up in time, with the number of seconds after which to retry.
+    Overloaded(u64),
+
+    /// If the first `Graph` is still loading.
+    Loading,
 }
 
-impl Reject for VaultError {}
+impl VaultError {
+    /// Creates a `VaultError::CrateNotFound`, suggesting crates sharing the longest possible prefix with the id.
+    ///
+    /// # Arguments
+    /// * `crate_id` - the id of the `Crate` which was not found.
+    /// * `state` - the `Graph` to search for similarly named crates.
+    #[must_use]
+    pub fn crate_not_found(crate_id: String, state: &Graph) -> Self {
+        let mut prefix = crate_id.to_lowercase();
+        let mut suggestions: Vec<String> = vec![];
 
-/// A `warp` function to handle different errors.
-///
-/// # Arguments
-/// * `err` - the `Rejection` to handle.
-pub async fn handle_rejection(err: Rejection) -> Result<impl Reply, Infallible> {
-    let (code, message) = if err.is_not_found() {
-        (StatusCode::NOT_FOUND, String::from("Route not found."))
-    } else if let Some(e) = err.find::<VaultError>() {
-        match e {
-            VaultError::CategoryNotFound(category_id) => (
+        while suggestions.is_empty() && !prefix.is_empty() {
+            suggestions = state
+                .crate_names()
+                .search(&prefix, state.crates())
+                .into_iter()
+                .take(MAX_SUGGESTIONS)
+                .map(|crate_val| crate_val.name.clone())
+                .collect();
+            prefix.pop();
+        }
+
+        Self::CrateNotFound(crate_id, suggestions)
+    }
+
+    /// Returns the status code and `ErrorResponse` describing the error.
+    fn to_response(&self) -> (StatusCode, ErrorResponse) {
+        match self {
+            Self::CategoryNotFound(category_id) => (
                 StatusCode::NOT_FOUND,
-                format!("Category with id {category_id} not found."),
+                ErrorResponse::new(
+                    "category_not_found",
+                    format!("Category with id {category_id} not found."),
+                ),
             ),
 
-            VaultError::CrateNotFound(crate_id) => (
+            Self::CrateNotFound(crate_id, suggestions) => (
                 StatusCode::NOT_FOUND,
-                format!("Crate with id {crate_id} not found."),
+                ErrorResponse {
+                    suggestions: suggestions.clone(),
+                    ..ErrorResponse::new(
+                        "crate_not_found",
+                        format!("Crate with id {crate_id} not found."),
+                    )
+                },
+            ),
+
+            Self::InvalidManifest(message) => (
+                StatusCode::BAD_REQUEST,
+                ErrorResponse::new("invalid_manifest", message.clone()),
             ),
 
-            VaultError::KeywordNotFound(keyword_id) => (
+            Self::InvalidLockfile(message) => (
+                StatusCode::BAD_REQUEST,
+                ErrorResponse::new("invalid_lockfile", message.clone()),
+            ),
+
+            Self::InvalidMetadata(message) => (
+                StatusCode::BAD_REQUEST,
+                ErrorResponse::new("invalid_metadata", message.clone()),
+            ),
+
+            Self::InvalidGraphQuery(message, fields) => (
+                StatusCode::BAD_REQUEST,
+                ErrorResponse::new("invalid_graph_query", message.clone()).with_parameters(fields),
+            ),
+
+            Self::KeywordNotFound(keyword_id) => (
                 StatusCode::NOT_FOUND,
-                format!("Keyword with id {keyword_id} not found."),
+                ErrorResponse::new(
+                    "keyword_not_found",
+                    format!("Keyword with id {keyword_id} not found."),
+                ),
+            ),
+
+            Self::NonexistentOptions(nonexistent_options) => (
+                StatusCode::BAD_REQUEST,
+                ErrorResponse::new(
+                    "invalid_options",
+                    format!(
+                        "The following options were provided with improper values: {}.",
+                        nonexistent_options.join(", ")
+                    ),
+                )
+                .with_parameters(nonexistent_options),
+            ),
+
+            Self::TooManyFeatures(max_features) => (
+                StatusCode::BAD_REQUEST,
+                ErrorResponse::new(
+                    "too_many_features",
+                    format!(
+                        "At most {max_features} features can be analyzed at once, select some with the features \
+                        option."
+                    ),
+                )
+                .with_parameters(&[String::from("features")]),
             ),
 
-            VaultError::NonexistentOptions(nonexistent_options) => (
+            Self::UnknownFeatures(parameters, unknown_features) => (
                 StatusCode::BAD_REQUEST,
-                format!(
-                    "The following options were provided with improper values: {},",
-                    nonexistent_options.join(", ")
+                ErrorResponse::new(
+                    "unknown_features",
+                    format!(
+                        "The following features do not exist: {}.",
+                        unknown_features.join(", ")
+                    ),
+                )
+                .with_parameters(parameters),
+            ),
+
+            Self::UnknownDependencies(unknown_dependencies) => (
+                StatusCode::BAD_REQUEST,
+                ErrorResponse::new(
+                    "unknown_dependencies",
+                    format!(
+                        "The following dependencies do not exist: {}.",
+                        unknown_dependencies.join(", ")
+                    ),
+                ),
+            ),
+
+            Self::Overloaded(_) => (
+                StatusCode::SERVICE_UNAVAILABLE,
+                ErrorResponse::new(
+                    "overloaded",
+                    "The server is busy computing other graphs, please retry later.",
                 ),
             ),
+
+            Self::Loading => (
+                StatusCode::SERVICE_UNAVAILABLE,
+                ErrorResponse::new(
+                    "loading",
+                    "The registry is still loading, please retry later.",
+                ),
+            ),
+        }
+    }
+}
+
+impl Reject for VaultError {}
+
+/// A machine-readable error returned in place of a response.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ErrorResponse {
+    /// A stable identifier of the kind of error (e.g. `crate_not_found`).
+    pub code: String,
+
+    /// A human-readable description of the error.
+    pub message: String,
+
+    /// The names of the query options, fields or headers responsible for the error, if any.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub parameters: Vec<String>,
+
+    /// Similarly named alternatives to an id which was not found, if any.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub suggestions: Vec<String>,
+}
+
+impl ErrorResponse {
+    /// Creates a new `ErrorResponse` without parameters or suggestions.
+    ///
+    /// # Arguments
+    /// * `code` - the identifier of the kind of error.
+    /// * `message` - the description of the error.
+    fn new(code: &str, message: impl Into<String>) -> Self {
+        Self {
+            code: String::from(code),
+            message: message.into(),
+            parameters: vec![],
+            suggestions: vec![],
         }
-    } else if err.find::<warp::reject::MethodNotAllowed>().is_some() {
+    }
+
+    /// Sets the parameters responsible for the error.
+    ///
+    /// # Arguments
+    /// * `parameters` - the names of the parameters.
+    fn with_parameters(mut self, parameters: &[String]) -> Self {
+        self.parameters = parameters.to_vec();
+        self
+    }
+}
+
+/// A `warp` function to handle different errors.
+///
+/// Errors are returned as an `ErrorResponse`, including those raised by `warp` itself.
+///
+/// # Arguments
+/// * `err` - the `Rejection` to handle.
+pub async fn handle_rejection(err: Rejection) -> Result<impl Reply, Infallible> {
+    let mut retry_after: Option<u64> = None;
+
+    let (code, error) = if err.is_not_found() {
+        (
+            StatusCode::NOT_FOUND,
+            ErrorResponse::new("route_not_found", "Route not found."),
+        )
+    } else if let Some(e) = err.find::<VaultError>() {
+        retry_after = match e {
+            VaultError::Overloaded(seconds) => Some(*seconds),
+            VaultError::Loading => Some(LOADING_RETRY_SECONDS),
+            _ => None,
+        };
+        e.to_response()
+    } else if let Some(e) = err.find::<reject::InvalidQuery>() {
+        (
+            StatusCode::BAD_REQUEST,
+            ErrorResponse::new("invalid_query_string", e.to_string()),
+        )
+    } else if err.find::<reject::PayloadTooLarge>().is_some() {
+        (
+            StatusCode::PAYLOAD_TOO_LARGE,
+            ErrorResponse::new("payload_too_large", "The request body is too large."),
+        )
+    } else if err.find::<reject::LengthRequired>().is_some() {
+        (
+            StatusCode::LENGTH_REQUIRED,
+            ErrorResponse::new(
+                "length_required",
+                "The request body must have a Content-Length header.",
+            )
+            .with_parameters(&[String::from("content-length")]),
+        )
+    } else if let Some(e) = err.find::<reject::UnsupportedMediaType>() {
+        (
+            StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            ErrorResponse::new("unsupported_media_type", e.to_string())
+                .with_parameters(&[String::from("content-type")]),
+        )
+    } else if let Some(e) = err.find::<reject::InvalidHeader>() {
+        (
+            StatusCode::BAD_REQUEST,
+            ErrorResponse::new("invalid_header", e.to_string())
+                .with_parameters(&[String::from(e.name())]),
+        )
+    } else if let Some(e) = err.find::<reject::MissingHeader>() {
+        (
+            StatusCode::BAD_REQUEST,
+            ErrorResponse::new("missing_header", e.to_string())
+                .with_parameters(&[String::from(e.name())]),
+        )
+    } else if err.find::<reject::MethodNotAllowed>().is_some() {
         (
             StatusCode::METHOD_NOT_ALLOWED,
-            String::from("Method Not Allowed"),
+            ErrorResponse::new("method_not_allowed", "Method Not Allowed"),
         )
     } else {
-        eprintln!("unhandled error: {err:?}");
+        error!("Unhandled rejection: {err:?}");
         (
             StatusCode::INTERNAL_SERVER_ERROR,
-            String::from("Internal Server Error"),
+            ErrorResponse::new("internal_error", "Internal Server Error"),
         )
     };
 
-    Ok(warp::reply::with_status(warp::reply::json(&message), code))
+    let mut response = warp::reply::with_status(warp::reply::json(&error), code).into_response();
+
+    if let Some(seconds) = retry_after {
+        response
+            .headers_mut()
+            .insert(RETRY_AFTER, HeaderValue::from(seconds));
+    }
+
+    Ok(response)
 }
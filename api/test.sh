@@ -1 +1 @@
-cargo test --no-fail-fast --test categories --test compiler --test crates --test keywords --test state -- --test-threads 1
\ No newline at end of file
+cargo test --no-fail-fast --test categories --test compiler --test config --test crates --test health --test keywords --test lockfile --test manifest --test metadata --test metrics --test query --test sbom --test state -- --test-threads 1
\ No newline at end of file
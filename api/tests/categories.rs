@@ -8,7 +8,7 @@ use std::str;
 use vault_api::routes::{
     self,
     categories::CategoryResponse,
-    utils::{self, State},
+    utils::{self, ErrorResponse, State},
 };
 use vault_graph::{Category, Search};
 use warp::Filter;
@@ -63,10 +63,9 @@ async fn test_get_category_nonexistent() {
 
     assert_eq!(res.status(), 404);
 
-    assert_eq!(
-        res.body(),
-        "\"Category with id nonexistent not found.\"".as_bytes()
-    );
+    let error: ErrorResponse = serde_json::from_slice(res.body()).unwrap();
+    assert_eq!(error.code, "category_not_found");
+    assert_eq!(error.message, "Category with id nonexistent not found.");
 }
 
 #[tokio::test]
@@ -125,3 +124,38 @@ async fn test_search_category() {
             .as_bytes()
     )
 }
+
+#[tokio::test]
+async fn test_footprint_leaderboard() {
+    let filters = routes::get(STATE.clone()).recover(utils::handle_rejection);
+
+    let res = warp::test::request()
+        .path("/categories/cat1/footprints")
+        .reply(&filters)
+        .await;
+
+    assert_eq!(res.status(), 200);
+
+    assert_eq!(
+        res.body(),
+        serde_json::to_string(&STATE.get_footprint_leaderboard("cat1"))
+            .unwrap()
+            .as_bytes()
+    );
+
+    let leaderboard: serde_json::Value = serde_json::from_slice(res.body()).unwrap();
+    assert!(!leaderboard["heaviest"].as_array().unwrap().is_empty());
+    assert!(!leaderboard["lightest"].as_array().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_footprint_leaderboard_nonexistent() {
+    let filters = routes::get(STATE.clone()).recover(utils::handle_rejection);
+
+    let res = warp::test::request()
+        .path("/categories/nonexistent/footprints")
+        .reply(&filters)
+        .await;
+
+    assert_eq!(res.status(), 404);
+}
@@ -0,0 +1,189 @@
+use ahash::AHashMap;
+use clap::Parser;
+use std::{
+    env, fs,
+    net::{IpAddr, Ipv4Addr},
+    path::PathBuf,
+    time::Duration,
+};
+use vault_api::config::{Args, Config, DEFAULT_CORS_ORIGIN, DEFAULT_PORT};
+use vault_graph::{DataSource, DEFAULT_DUMP_URL, DEFAULT_TARGET};
+
+/// Writes a configuration file to the temporary directory, returning its path.
+fn write_config_file(name: &str, contents: &str) -> PathBuf {
+    let config_path = env::temp_dir().join(format!("vault_api_{name}.toml"));
+    fs::write(&config_path, contents).unwrap();
+    config_path
+}
+
+/// Loads a `Config` from command-line arguments and environment variables.
+fn load_config(args: &[&str], env_vars: &[(&str, &str)]) -> Result<Config, Vec<String>> {
+    let env_vars: AHashMap<&str, &str> = env_vars.iter().copied().collect();
+
+    Config::load(
+        Args::try_parse_from([&["vault_api"], args].concat()).unwrap(),
+        |name| env_vars.get(name).map(|&value| String::from(value)),
+    )
+    .map_err(|err| err.problems().to_vec())
+}
+
+#[test]
+fn test_config_defaults() {
+    let config = load_config(&[], &[]).unwrap();
+
+    assert_eq!(config.address, IpAddr::V4(Ipv4Addr::UNSPECIFIED));
+    assert_eq!(config.port, DEFAULT_PORT);
+    assert_eq!(config.cors_origins, vec![String::from(DEFAULT_CORS_ORIGIN)]);
+    assert_eq!(
+        config.graph.source,
+        DataSource::Remote(String::from(DEFAULT_DUMP_URL))
+    );
+    assert_eq!(config.graph.default_target, DEFAULT_TARGET);
+    assert_eq!(config.refresh_interval, None);
+}
+
+#[test]
+fn test_config_legacy_args() {
+    let config = load_config(&["8285", "-l"], &[]).unwrap();
+
+    assert_eq!(config.address, IpAddr::V4(Ipv4Addr::LOCALHOST));
+    assert_eq!(config.port, 8285);
+}
+
+#[test]
+fn test_config_precedence() {
+    let config_path = write_config_file(
+        "precedence",
+        r#"
+            [server]
+            port = 9000
+            cors_origins = ["https://a.example.com"]
+
+            [data]
+            refresh_interval_secs = 3600
+
+            [graph]
+            default_target = "wasm32-unknown-unknown"
+            default_cfg_name = "cargo_web"
+
+            [cache]
+            capacity = 16
+        "#,
+    );
+    let config_path = config_path.to_str().unwrap();
+
+    // the file overrides defaults
+    let config = load_config(&["--config", config_path], &[]).unwrap();
+    assert_eq!(config.port, 9000);
+    assert_eq!(config.refresh_interval, Some(Duration::from_secs(3600)));
+    assert_eq!(config.graph.default_target, "wasm32-unknown-unknown");
+    assert_eq!(config.graph.default_cfg_name, "cargo_web");
+    assert_eq!(config.graph.cache_capacity, 16);
+
+    // environment variables override the file, and can name it
+    let config = load_config(
+        &[],
+        &[
+            ("VAULT_CONFIG", config_path),
+            ("VAULT_PORT", "9001"),
+            (
+                "VAULT_CORS_ORIGINS",
+                "https://b.example.com, http://localhost:3000",
+            ),
+        ],
+    )
+    .unwrap();
+    assert_eq!(config.port, 9001);
+    assert_eq!(
+        config.cors_origins,
+        vec![
+            String::from("https://b.example.com"),
+            String::from("http://localhost:3000")
+        ]
+    );
+    assert_eq!(config.graph.cache_capacity, 16);
+
+    // command-line arguments override everything
+    let config = load_config(
+        &[
+            "--config",
+            config_path,
+            "--port",
+            "9002",
+            "--cache-capacity",
+            "0",
+        ],
+        &[("VAULT_PORT", "9001")],
+    )
+    .unwrap();
+    assert_eq!(config.port, 9002);
+    assert_eq!(config.graph.cache_capacity, 0);
+}
+
+#[test]
+fn test_config_data_source() {
+    let data_path = env::temp_dir();
+    let data_path_name = data_path.to_str().unwrap();
+
+    let problems = load_config(
+        &["--data-source", "snapshot", "--data-path", data_path_name],
+        &[],
+    )
+    .unwrap_err();
+    assert_eq!(problems.len(), 1);
+    assert!(problems[0].contains("is missing `categories.csv`"));
+
+    let problems = load_config(&["--data-source", "dump"], &[]).unwrap_err();
+    assert_eq!(
+        problems,
+        vec![String::from(
+            "`data.path` is required by the `dump` and `snapshot` data sources"
+        )]
+    );
+
+    let problems = load_config(&["--data-path", data_path_name], &[]).unwrap_err();
+    assert_eq!(problems.len(), 1);
+    assert!(problems[0].starts_with("`data.path` is only used by"));
+}
+
+#[test]
+fn test_config_invalid() {
+    let problems = load_config(
+        &[
+            "--cors-origin",
+            "example.com",
+            "--log-level",
+            "loud",
+            "--default-target",
+            "nonexistent",
+            "--default-cfg-name",
+            "feature = \"x\"",
+            "--compute-concurrency",
+            "0",
+            "--refresh-interval-secs",
+            "0",
+        ],
+        &[("VAULT_QUEUE_TIMEOUT_SECS", "soon")],
+    )
+    .unwrap_err();
+
+    // every problem is reported at once
+    assert_eq!(problems.len(), 7);
+    assert!(problems[0].starts_with("`VAULT_QUEUE_TIMEOUT_SECS`"));
+    assert!(problems[1].starts_with("`server.cors_origins`"));
+    assert!(problems[2].starts_with("`server.log_level`"));
+    assert!(problems[3].starts_with("`data.refresh_interval_secs`"));
+    assert!(problems[4].starts_with("`compute.concurrency`"));
+    assert!(problems[5].starts_with("default target `nonexistent`"));
+    assert!(problems[6].starts_with("default cfg name"));
+}
+
+#[test]
+fn test_config_file_unknown_field() {
+    let config_path = write_config_file("unknown_field", "[server]\nprot = 9000\n");
+
+    let problems = load_config(&["--config", config_path.to_str().unwrap()], &[]).unwrap_err();
+
+    assert_eq!(problems.len(), 1);
+    assert!(problems[0].contains("unknown field `prot`"));
+}
@@ -5,12 +5,14 @@ mod common;
 
 use chrono::NaiveDateTime;
 use serde::Deserialize;
-use std::str;
+use std::{collections::BTreeSet, convert::Infallible, str, time::Duration};
 use vault_api::routes::{
     self,
-    utils::{self, State},
+    utils::{self, ComputePool, ErrorResponse, State},
+};
+use vault_graph::{
+    DependencyGraphOptions, DependencyKind, GraphFormat, Resolver, Search, SearchRanking,
 };
-use vault_graph::Search;
 use warp::Filter;
 
 lazy_static! {
@@ -26,6 +28,31 @@ struct TestCrate {
     pub version: String,
 }
 
+/// Requests a dependency graph, returning it parsed.
+///
+/// # Arguments
+/// * `filters` - the routes to request the dependency graph from.
+/// * `path` - the path of the dependency graph.
+async fn get_graph(
+    filters: &(impl Filter<Extract = (impl warp::Reply,), Error = Infallible> + Clone + 'static),
+    path: &str,
+) -> serde_json::Value {
+    let res = warp::test::request().path(path).reply(filters).await;
+    assert_eq!(res.status(), 200);
+
+    serde_json::from_slice(res.body()).unwrap()
+}
+
+/// Returns the names of the crates in a serialized dependency graph.
+fn get_crate_names(dependency_graph: &serde_json::Value) -> Vec<&str> {
+    dependency_graph["crates"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|crate_distance| crate_distance["name"].as_str().unwrap())
+        .collect()
+}
+
 #[tokio::test]
 async fn test_get_crate_nonexistent() {
     let filters = routes::get(STATE.clone()).recover(utils::handle_rejection);
@@ -37,10 +64,42 @@ async fn test_get_crate_nonexistent() {
 
     assert_eq!(res.status(), 404);
 
-    assert_eq!(
-        res.body(),
-        "\"Crate with id nonexistent not found.\"".as_bytes()
-    );
+    let error: ErrorResponse = serde_json::from_slice(res.body()).unwrap();
+    assert_eq!(error.code, "crate_not_found");
+    assert_eq!(error.message, "Crate with id nonexistent not found.");
+}
+
+#[tokio::test]
+async fn test_get_crate_suggestions() {
+    let filters = routes::get(STATE.clone()).recover(utils::handle_rejection);
+
+    let res = warp::test::request()
+        .path("/graph/Libcc")
+        .reply(&filters)
+        .await;
+
+    assert_eq!(res.status(), 404);
+
+    // crates sharing the longest prefix with the misspelled id are suggested
+    let error: ErrorResponse = serde_json::from_slice(res.body()).unwrap();
+    assert_eq!(error.code, "crate_not_found");
+    assert!(error.suggestions.contains(&String::from("libc")));
+    assert!(error.suggestions.len() <= 5);
+    assert!(error
+        .suggestions
+        .iter()
+        .all(|suggestion| suggestion.starts_with("libc")));
+
+    let res = warp::test::request()
+        .path("/graph/libc/features")
+        .reply(&filters)
+        .await;
+
+    assert_eq!(res.status(), 404);
+
+    let error: ErrorResponse = serde_json::from_slice(res.body()).unwrap();
+    assert_eq!(error.code, "route_not_found");
+    assert!(error.suggestions.is_empty());
 }
 
 #[tokio::test]
@@ -73,7 +132,12 @@ async fn test_get_random_crate() {
 
     assert_eq!(res.status(), 200);
 
-    assert!(serde_json::from_str::<TestCrate>(str::from_utf8(res.body()).unwrap()).is_ok(),);
+    let random_crate: TestCrate = serde_json::from_slice(res.body()).unwrap();
+    let crate_val = &STATE.crates()[&random_crate.name];
+    assert_eq!(random_crate.created_at, crate_val.created_at);
+    assert_eq!(random_crate.description, crate_val.description);
+    assert_eq!(random_crate.downloads, crate_val.downloads);
+    assert_eq!(random_crate.version, crate_val.version);
 }
 
 #[tokio::test]
@@ -95,6 +159,29 @@ async fn test_search_crate() {
     )
 }
 
+#[tokio::test]
+async fn test_search_crate_centrality() {
+    let filters = routes::get(STATE.clone()).recover(utils::handle_rejection);
+
+    let res = warp::test::request()
+        .path("/search/crates/s?rank=centrality")
+        .reply(&filters)
+        .await;
+
+    assert_eq!(res.status(), 200);
+
+    assert_eq!(
+        res.body(),
+        serde_json::to_string(&STATE.crate_names().search_ranked(
+            "s",
+            STATE.crates(),
+            SearchRanking::Centrality,
+        ))
+        .unwrap()
+        .as_bytes()
+    )
+}
+
 #[tokio::test]
 async fn test_graph() {
     let filters = routes::get(STATE.clone()).recover(utils::handle_rejection);
@@ -108,9 +195,15 @@ async fn test_graph() {
 
     assert_eq!(
         res.body(),
-        serde_json::to_string(&STATE.get_dependency_graph("warp", vec![], &None, &None,))
-            .unwrap()
-            .as_bytes()
+        serde_json::to_string(&STATE.get_dependency_graph(
+            "warp",
+            vec![],
+            &None,
+            &None,
+            &DependencyGraphOptions::default(),
+        ))
+        .unwrap()
+        .as_bytes()
     )
 }
 
@@ -119,7 +212,7 @@ async fn test_graph_features() {
     let filters = routes::get(STATE.clone()).recover(utils::handle_rejection);
 
     let res = warp::test::request()
-        .path("/graph/warp?features=tls,websocket,compression")
+        .path("/graph/warp?features=f1,f2,c153")
         .reply(&filters)
         .await;
 
@@ -129,13 +222,10 @@ async fn test_graph_features() {
         res.body(),
         serde_json::to_string(&STATE.get_dependency_graph(
             "warp",
-            vec![
-                String::from("tls"),
-                String::from("websocket"),
-                String::from("compression")
-            ],
+            vec![String::from("f1"), String::from("f2"), String::from("c153")],
             &None,
             &None,
+            &DependencyGraphOptions::default(),
         ))
         .unwrap()
         .as_bytes()
@@ -147,7 +237,7 @@ async fn test_graph_features_platform() {
     let filters = routes::get(STATE.clone()).recover(utils::handle_rejection);
 
     let res = warp::test::request()
-        .path("/graph/chrono?features=wasmbind&target=x86_64-unknown-linux-gnu")
+        .path("/graph/chrono?features=f4&target=x86_64-unknown-linux-gnu")
         .reply(&filters)
         .await;
 
@@ -157,9 +247,10 @@ async fn test_graph_features_platform() {
         res.body(),
         serde_json::to_string(&STATE.get_dependency_graph(
             "chrono",
-            vec![String::from("wasmbind")],
+            vec![String::from("f4")],
             &Some(String::from("x86_64-unknown-linux-gnu")),
             &None,
+            &DependencyGraphOptions::default(),
         ))
         .unwrap()
         .as_bytes()
@@ -184,8 +275,585 @@ async fn test_graph_cfg_name() {
             vec![],
             &None,
             &Some(String::from("cargo_web")),
+            &DependencyGraphOptions::default(),
         ))
         .unwrap()
         .as_bytes()
     )
 }
+
+#[tokio::test]
+async fn test_graph_no_default_features() {
+    let filters = routes::get(STATE.clone()).recover(utils::handle_rejection);
+
+    // the `default` feature of libc enables `f4`, which activates its optional dependency on c97
+    let dependency_graph = get_graph(&filters, "/graph/libc").await;
+    assert!(get_crate_names(&dependency_graph).contains(&"c97"));
+
+    let dependency_graph = get_graph(&filters, "/graph/libc?default_features=false").await;
+    assert_eq!(get_crate_names(&dependency_graph), vec!["libc"]);
+    assert!(dependency_graph["dependencies"]
+        .as_array()
+        .unwrap()
+        .is_empty());
+}
+
+#[tokio::test]
+async fn test_graph_all_features() {
+    let filters = routes::get(STATE.clone()).recover(utils::handle_rejection);
+
+    // the optional dependency of c120 on c101 is only activated by its `f1` and `f3` features
+    let dependency_graph = get_graph(&filters, "/graph/c120").await;
+    assert!(!get_crate_names(&dependency_graph).contains(&"c101"));
+
+    let dependency_graph = get_graph(&filters, "/graph/c120?all_features=true").await;
+    assert!(get_crate_names(&dependency_graph).contains(&"c101"));
+    assert!(dependency_graph["dependencies"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .any(|dependency| dependency["from"] == "c120"
+            && dependency["to"] == "c101"
+            && dependency["active"] == true));
+}
+
+#[tokio::test]
+async fn test_graph_implicit_features() {
+    let filters = routes::get(STATE.clone()).recover(utils::handle_rejection);
+
+    // the optional dependency of c103 on c133 is not referenced by any feature, so only its implicit feature activates it
+    let dependency_graph = get_graph(&filters, "/graph/c103").await;
+    assert!(!get_crate_names(&dependency_graph).contains(&"c133"));
+
+    let dependency_graph = get_graph(&filters, "/graph/c103?features=c133").await;
+    assert!(get_crate_names(&dependency_graph).contains(&"c133"));
+
+    let dependency_graph = get_graph(&filters, "/graph/c103?all_features=true").await;
+    assert!(get_crate_names(&dependency_graph).contains(&"c133"));
+}
+
+#[tokio::test]
+async fn test_graph_unknown_features() {
+    let filters = routes::get(STATE.clone()).recover(utils::handle_rejection);
+
+    let res = warp::test::request()
+        .path("/graph/warp?features=c153,nonexistent")
+        .reply(&filters)
+        .await;
+
+    assert_eq!(res.status(), 400);
+
+    let error: ErrorResponse = serde_json::from_slice(res.body()).unwrap();
+    assert_eq!(error.code, "unknown_features");
+    assert_eq!(
+        error.message,
+        "The following features do not exist: nonexistent."
+    );
+    assert_eq!(error.parameters, vec!["features"]);
+}
+
+#[tokio::test]
+async fn test_feature_suggestion_unknown_features() {
+    let filters = routes::get(STATE.clone()).recover(utils::handle_rejection);
+
+    // whichever list requested the unknown features is reported
+    let res = warp::test::request()
+        .path("/crates/libc/feature-suggestion?remove=c97&features=f4&required=nonexistent")
+        .reply(&filters)
+        .await;
+
+    assert_eq!(res.status(), 400);
+
+    let error: ErrorResponse = serde_json::from_slice(res.body()).unwrap();
+    assert_eq!(error.code, "unknown_features");
+    assert_eq!(error.parameters, vec!["required"]);
+}
+
+#[tokio::test]
+async fn test_graph_improper_options() {
+    let filters = routes::get(STATE.clone()).recover(utils::handle_rejection);
+
+    let res = warp::test::request()
+        .path("/graph/warp?default_features=nope")
+        .reply(&filters)
+        .await;
+
+    assert_eq!(res.status(), 400);
+}
+
+#[tokio::test]
+async fn test_graph_formats() {
+    let filters = routes::get(STATE.clone()).recover(utils::handle_rejection);
+
+    let dependency_graph = STATE
+        .get_dependency_graph(
+            "warp",
+            vec![],
+            &None,
+            &None,
+            &DependencyGraphOptions::default(),
+        )
+        .unwrap();
+
+    // the format query option takes precedence over the Accept header
+    for (path, accept, format) in [
+        (
+            "/graph/warp?format=dot",
+            "application/json",
+            GraphFormat::Dot,
+        ),
+        ("/graph/warp?format=csv_nodes", "", GraphFormat::CsvNodes),
+        (
+            "/graph/warp",
+            "application/graphml+xml",
+            GraphFormat::GraphMl,
+        ),
+        (
+            "/graph/warp",
+            "text/csv;q=0.5, text/vnd.mermaid",
+            GraphFormat::Mermaid,
+        ),
+        ("/graph/warp", "text/csv", GraphFormat::CsvEdges),
+        ("/graph/warp", "text/html, */*", GraphFormat::Json),
+    ] {
+        let res = warp::test::request()
+            .path(path)
+            .header("accept", accept)
+            .reply(&filters)
+            .await;
+
+        assert_eq!(res.status(), 200);
+        assert_eq!(res.headers()["content-type"], format.content_type());
+        assert_eq!(res.body(), dependency_graph.export(format).as_bytes());
+    }
+
+    // crates are ranked by distance from the root crate
+    let dot = dependency_graph.to_dot();
+    assert!(dot.starts_with("digraph \"warp\" {"));
+    assert!(dot.contains("    { rank=same; \"warp\"; }"));
+
+    let edges = dependency_graph.to_csv_edges();
+    assert!(edges.starts_with("Source,Target,Type,kind,"));
+    assert_eq!(
+        edges.lines().count(),
+        dependency_graph.dependencies.len() + 1
+    );
+}
+
+#[tokio::test]
+async fn test_graph_format_unknown() {
+    let filters = routes::get(STATE.clone()).recover(utils::handle_rejection);
+
+    let res = warp::test::request()
+        .path("/graph/warp?format=png")
+        .reply(&filters)
+        .await;
+
+    assert_eq!(res.status(), 400);
+}
+
+#[tokio::test]
+async fn test_graph_overloaded() {
+    let filters = routes::with_compute_pool(
+        STATE.clone(),
+        ComputePool::new(0, Duration::from_millis(10)),
+    )
+    .recover(utils::handle_rejection);
+
+    let res = warp::test::request()
+        .path("/graph/warp")
+        .reply(&filters)
+        .await;
+
+    assert_eq!(res.status(), 503);
+    assert_eq!(res.headers()["retry-after"], "1");
+
+    // invalid requests are rejected before waiting for the pool
+    let res = warp::test::request()
+        .path("/graph/warp?default_features=nope")
+        .reply(&filters)
+        .await;
+
+    assert_eq!(res.status(), 400);
+}
+
+#[tokio::test]
+async fn test_graph_resolver_v2() {
+    let filters = routes::get(STATE.clone()).recover(utils::handle_rejection);
+
+    let res = warp::test::request()
+        .path("/graph/warp?resolver=2&target=wasm32-unknown-unknown")
+        .reply(&filters)
+        .await;
+
+    assert_eq!(res.status(), 200);
+
+    assert_eq!(
+        res.body(),
+        serde_json::to_string(&STATE.get_dependency_graph(
+            "warp",
+            vec![],
+            &Some(String::from("wasm32-unknown-unknown")),
+            &None,
+            &DependencyGraphOptions {
+                resolver: Resolver::V2,
+                ..DependencyGraphOptions::default()
+            },
+        ))
+        .unwrap()
+        .as_bytes()
+    )
+}
+
+#[tokio::test]
+async fn test_graph_infer_proc_macros() {
+    let filters = routes::get(STATE.clone()).recover(utils::handle_rejection);
+
+    // returns the compile kinds of c140, which mandatorily depends on proc-macro2 and quote
+    let get_compile_kinds = |dependency_graph: serde_json::Value| {
+        dependency_graph["crates"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|crate_distance| crate_distance["name"] == "c140")
+            .unwrap()["compile_kinds"]
+            .clone()
+    };
+
+    // c140 is a normal dependency of c75, but is only built for the host when procedural macros are inferred
+    let compile_kinds = get_compile_kinds(get_graph(&filters, "/graph/c75?resolver=2").await);
+    assert!(compile_kinds.get("target").is_some());
+
+    let compile_kinds = get_compile_kinds(
+        get_graph(&filters, "/graph/c75?resolver=2&infer_proc_macros=true").await,
+    );
+    assert!(compile_kinds.get("host").is_some());
+    assert!(compile_kinds.get("target").is_none());
+
+    let res = warp::test::request()
+        .path("/graph/c75?infer_proc_macros=nope")
+        .reply(&filters)
+        .await;
+
+    assert_eq!(res.status(), 400);
+}
+
+#[tokio::test]
+async fn test_graph_potential_dependencies() {
+    let filters = routes::get(STATE.clone()).recover(utils::handle_rejection);
+
+    let dependency_graph = get_graph(&filters, "/graph/c120?potential_dependencies=20").await;
+
+    // the inactive optional dependency on c101 is listed along with the features which would activate it
+    let potential_dependencies = dependency_graph["potential_dependencies"]
+        .as_array()
+        .unwrap();
+    let potential_dependency = potential_dependencies
+        .iter()
+        .find(|dependency| dependency["from"] == "c120" && dependency["to"] == "c101")
+        .unwrap();
+    assert_eq!(potential_dependency["active"], false);
+    assert_eq!(
+        potential_dependency["activated_by"],
+        serde_json::json!(["f1", "f3"])
+    );
+
+    // potential dependencies are not traversed, so neither c101 nor its default dependency on c151 is included
+    let crate_names = get_crate_names(&dependency_graph);
+    assert!(!crate_names.contains(&"c101"));
+    assert!(!crate_names.contains(&"c151"));
+    assert!(potential_dependencies
+        .iter()
+        .chain(dependency_graph["dependencies"].as_array().unwrap())
+        .all(|dependency| dependency["from"] != "c101"));
+
+    // potential dependencies are left out unless requested
+    let dependency_graph = get_graph(&filters, "/graph/c120").await;
+    assert!(dependency_graph.get("potential_dependencies").is_none());
+}
+
+#[tokio::test]
+async fn test_feature_costs() {
+    let filters = routes::get(STATE.clone()).recover(utils::handle_rejection);
+
+    let res = warp::test::request()
+        .path("/crates/warp/feature-costs")
+        .reply(&filters)
+        .await;
+
+    assert_eq!(res.status(), 200);
+
+    assert_eq!(
+        res.body(),
+        serde_json::to_string(&STATE.get_feature_costs(
+            "warp",
+            &[],
+            &None,
+            &None,
+            &DependencyGraphOptions::default(),
+        ))
+        .unwrap()
+        .as_bytes()
+    )
+}
+
+#[tokio::test]
+async fn test_feature_costs_default_feature() {
+    let filters = routes::get(STATE.clone()).recover(utils::handle_rejection);
+
+    // f0 of c191 is enabled by default, so it only adds crates when default features are disabled
+    let feature_costs = get_graph(&filters, "/crates/c191/feature-costs?features=f0").await;
+    let feature_cost = &feature_costs.as_array().unwrap()[0];
+
+    assert_eq!(feature_cost["feature"], "f0");
+    assert_eq!(feature_cost["over_default"]["crate_count"], 0);
+    assert_eq!(feature_cost["over_default"]["dependency_count"], 0);
+    assert!(
+        feature_cost["over_no_default"]["crate_count"]
+            .as_u64()
+            .unwrap()
+            > 0
+    );
+}
+
+#[tokio::test]
+async fn test_feature_costs_many_features() {
+    let filters = routes::get(STATE.clone()).recover(utils::handle_rejection);
+
+    // returns the names of the crates in the dependency graph of memchr
+    let get_crate_names = |features: Vec<String>, options: &DependencyGraphOptions| {
+        STATE
+            .get_dependency_graph("memchr", features, &None, &None, options)
+            .unwrap()
+            .crates
+            .into_iter()
+            .map(|crate_distance| crate_distance.name.clone())
+            .collect::<BTreeSet<String>>()
+    };
+
+    // memchr exposes the most features, and request options apply to every resolution
+    let feature_costs = get_graph(&filters, "/crates/memchr/feature-costs?resolver=2").await;
+    let feature_costs = feature_costs.as_array().unwrap();
+    assert_eq!(
+        feature_costs.len(),
+        STATE.crates()["memchr"].features.len() - 1
+    );
+
+    // sharing resolutions between features gives the same costs as resolving each feature separately
+    for (default_features, delta) in [(true, "over_default"), (false, "over_no_default")] {
+        let options = DependencyGraphOptions {
+            default_features,
+            resolver: Resolver::V2,
+            ..DependencyGraphOptions::default()
+        };
+        let baseline = get_crate_names(vec![], &options);
+
+        for feature_cost in feature_costs {
+            let feature_name = feature_cost["feature"].as_str().unwrap();
+            let added_crates: BTreeSet<String> =
+                get_crate_names(vec![String::from(feature_name)], &options)
+                    .difference(&baseline)
+                    .cloned()
+                    .collect();
+
+            assert_eq!(
+                feature_cost[delta]["crates"]
+                    .as_array()
+                    .unwrap()
+                    .iter()
+                    .map(|crate_id| crate_id.as_str().unwrap().to_owned())
+                    .collect::<BTreeSet<String>>(),
+                added_crates,
+                "{feature_name} {delta}"
+            );
+        }
+    }
+
+    // the features to analyze may be selected
+    let feature_costs = get_graph(
+        &filters,
+        "/crates/memchr/feature-costs?features=std,logging",
+    )
+    .await;
+    assert_eq!(feature_costs.as_array().unwrap().len(), 2);
+
+    // too many features cannot be analyzed at once
+    let features: Vec<String> = (0..100).map(|index| format!("f{index}")).collect();
+    let res = warp::test::request()
+        .path(&format!(
+            "/crates/memchr/feature-costs?features={}",
+            features.join(",")
+        ))
+        .reply(&filters)
+        .await;
+
+    assert_eq!(res.status(), 400);
+
+    let error: ErrorResponse = serde_json::from_slice(res.body()).unwrap();
+    assert_eq!(error.code, "too_many_features");
+    assert_eq!(error.parameters, vec!["features"]);
+
+    let res = warp::test::request()
+        .path("/crates/memchr/feature-costs?resolver=3")
+        .reply(&filters)
+        .await;
+
+    assert_eq!(res.status(), 400);
+}
+
+#[tokio::test]
+async fn test_feature_costs_nonexistent() {
+    let filters = routes::get(STATE.clone()).recover(utils::handle_rejection);
+
+    let res = warp::test::request()
+        .path("/crates/nonexistent/feature-costs")
+        .reply(&filters)
+        .await;
+
+    assert_eq!(res.status(), 404);
+}
+
+#[tokio::test]
+async fn test_feature_suggestion() {
+    let filters = routes::get(STATE.clone()).recover(utils::handle_rejection);
+
+    // the optional dependency of c120 on c101 is activated by f1 but not f0
+    let res = warp::test::request()
+        .path("/crates/c120/feature-suggestion?remove=c101&features=f0,f1&required=f0")
+        .reply(&filters)
+        .await;
+
+    assert_eq!(res.status(), 200);
+
+    assert_eq!(
+        res.body(),
+        serde_json::to_string(&STATE.get_feature_suggestion(
+            "c120",
+            &[String::from("f0"), String::from("f1")],
+            &[String::from("f0")],
+            "c101",
+            &None,
+            &None,
+            &DependencyGraphOptions::default(),
+        ))
+        .unwrap()
+        .as_bytes()
+    );
+
+    let feature_suggestion: serde_json::Value = serde_json::from_slice(res.body()).unwrap();
+    assert_eq!(feature_suggestion["status"], "removable");
+    assert_eq!(feature_suggestion["features"], serde_json::json!(["f0"]));
+    assert_eq!(
+        feature_suggestion["removed_features"],
+        serde_json::json!(["f1"])
+    );
+}
+
+#[tokio::test]
+async fn test_feature_suggestion_default_members() {
+    let filters = routes::get(STATE.clone()).recover(utils::handle_rejection);
+
+    // the default features of c191 are f0, f2 and c293/f2, where only f0 leads to quote
+    let feature_suggestion =
+        get_graph(&filters, "/crates/c191/feature-suggestion?remove=quote").await;
+    assert_eq!(
+        feature_suggestion,
+        serde_json::json!({
+            "status": "removable",
+            "default_features": false,
+            "features": ["f2"],
+            "disabled_default_features": true,
+            "removed_features": [],
+            "removed_default_features": ["f0"],
+            "exhaustive": true,
+        })
+    );
+
+    // the suggested selection removes the unwanted crate
+    let dependency_graph =
+        get_graph(&filters, "/graph/c191?default_features=false&features=f2").await;
+    assert!(!get_crate_names(&dependency_graph).contains(&"quote"));
+    let dependency_graph = get_graph(&filters, "/graph/c191").await;
+    assert!(get_crate_names(&dependency_graph).contains(&"quote"));
+
+    // required members of default are kept
+    let feature_suggestion = get_graph(
+        &filters,
+        "/crates/c191/feature-suggestion?remove=quote&required=f0",
+    )
+    .await;
+    assert_eq!(feature_suggestion["status"], "blocked");
+}
+
+#[tokio::test]
+async fn test_feature_suggestion_improper_options() {
+    let filters = routes::get(STATE.clone()).recover(utils::handle_rejection);
+
+    let res = warp::test::request()
+        .path("/crates/warp/feature-suggestion?remove=nonexistent")
+        .reply(&filters)
+        .await;
+
+    assert_eq!(res.status(), 400);
+}
+
+#[tokio::test]
+async fn test_dominator_tree() {
+    let filters = routes::get(STATE.clone()).recover(utils::handle_rejection);
+
+    let res = warp::test::request()
+        .path("/graph/warp/dominators")
+        .reply(&filters)
+        .await;
+
+    assert_eq!(res.status(), 200);
+
+    assert_eq!(
+        res.body(),
+        serde_json::to_string(
+            &STATE
+                .get_dependency_graph(
+                    "warp",
+                    vec![],
+                    &None,
+                    &None,
+                    &DependencyGraphOptions::default(),
+                )
+                .unwrap()
+                .dominator_tree()
+        )
+        .unwrap()
+        .as_bytes()
+    )
+}
+
+#[tokio::test]
+async fn test_cycles() {
+    let filters = routes::get(STATE.clone()).recover(utils::handle_rejection);
+
+    let res = warp::test::request()
+        .path("/cycles?kinds=normal,build")
+        .reply(&filters)
+        .await;
+
+    assert_eq!(res.status(), 200);
+
+    assert_eq!(
+        res.body(),
+        serde_json::to_string(&STATE.get_cycles(&[DependencyKind::Normal, DependencyKind::Build]))
+            .unwrap()
+            .as_bytes()
+    )
+}
+
+#[tokio::test]
+async fn test_cycles_improper_kinds() {
+    let filters = routes::get(STATE.clone()).recover(utils::handle_rejection);
+
+    let res = warp::test::request()
+        .path("/cycles?kinds=normal,optional")
+        .reply(&filters)
+        .await;
+
+    assert_eq!(res.status(), 400);
+}
@@ -0,0 +1,147 @@
+#[macro_use]
+extern crate lazy_static;
+
+mod common;
+
+use std::{path::PathBuf, sync::Arc};
+use vault_api::routes::{
+    self,
+    utils::{self, ErrorResponse, SharedState, State},
+};
+use vault_graph::{DataSource, Graph, GraphConfig, LoadError, LoadProgress};
+use warp::Filter;
+
+lazy_static! {
+    static ref STATE: State = common::get_data();
+}
+
+#[tokio::test]
+async fn test_health_loading() {
+    let state = SharedState::loading(LoadProgress::default());
+    let filters = routes::get(state.clone()).recover(utils::handle_rejection);
+
+    let res = warp::test::request()
+        .path("/health/live")
+        .reply(&filters)
+        .await;
+
+    assert_eq!(res.status(), 200);
+
+    let res = warp::test::request()
+        .path("/health/ready")
+        .reply(&filters)
+        .await;
+
+    assert_eq!(res.status(), 503);
+
+    let readiness: serde_json::Value = serde_json::from_slice(res.body()).unwrap();
+    assert_eq!(readiness["ready"], false);
+
+    // data routes are unavailable until the graph has loaded
+    let res = warp::test::request()
+        .path("/crates/libc")
+        .reply(&filters)
+        .await;
+
+    assert_eq!(res.status(), 503);
+    assert!(res.headers().contains_key("retry-after"));
+
+    let error: ErrorResponse = serde_json::from_slice(res.body()).unwrap();
+    assert_eq!(error.code, "loading");
+
+    // unknown routes are not found rather than loading
+    let res = warp::test::request()
+        .path("/nonexistent")
+        .reply(&filters)
+        .await;
+
+    assert_eq!(res.status(), 404);
+
+    // metrics are served while loading
+    let res = warp::test::request().path("/metrics").reply(&filters).await;
+
+    assert_eq!(res.status(), 200);
+
+    state.store(STATE.clone());
+
+    let res = warp::test::request()
+        .path("/health/ready")
+        .reply(&filters)
+        .await;
+
+    assert_eq!(res.status(), 200);
+
+    let res = warp::test::request()
+        .path("/crates/libc")
+        .reply(&filters)
+        .await;
+
+    assert_eq!(res.status(), 200);
+}
+
+#[tokio::test]
+async fn test_health_progress() {
+    let progress = LoadProgress::default();
+    let state = SharedState::loading(progress.clone());
+    let filters = routes::get(state.clone()).recover(utils::handle_rejection);
+
+    let graph = Graph::from_config_with_progress(
+        &GraphConfig {
+            source: DataSource::Snapshot(PathBuf::from("./tests/data")),
+            ..GraphConfig::default()
+        },
+        &progress,
+    )
+    .unwrap();
+    state.store(Arc::new(graph));
+
+    let res = warp::test::request()
+        .path("/health/ready")
+        .reply(&filters)
+        .await;
+
+    assert_eq!(res.status(), 200);
+
+    // every expected phase of loading has completed
+    let readiness: serde_json::Value = serde_json::from_slice(res.body()).unwrap();
+    assert_eq!(readiness["ready"], true);
+    assert_eq!(readiness["load"]["running"], serde_json::json!([]));
+    assert_eq!(readiness["load"]["completed"], readiness["load"]["total"]);
+    assert_eq!(readiness["load"]["progress"], 1.0);
+    assert!(readiness["load"]["seconds"].as_f64().unwrap() > 0.0);
+}
+
+#[tokio::test]
+async fn test_reload_failure_keeps_graph() {
+    let state = SharedState::new(STATE.clone());
+
+    let err = state
+        .reload(|| {
+            Graph::from_config(&GraphConfig {
+                source: DataSource::Snapshot(PathBuf::from("./tests/missing")),
+                ..GraphConfig::default()
+            })
+        })
+        .await
+        .unwrap_err();
+
+    assert!(matches!(err, LoadError::Read(..)));
+    assert!(Arc::ptr_eq(&state.load(), &STATE));
+
+    let filters = routes::get(state.clone()).recover(utils::handle_rejection);
+    let res = warp::test::request()
+        .path("/crates/libc")
+        .reply(&filters)
+        .await;
+
+    assert_eq!(res.status(), 200);
+}
+
+#[tokio::test]
+async fn test_reload_success_replaces_graph() {
+    let state = SharedState::new(STATE.clone());
+
+    state.reload(|| Ok(Graph::test())).await.unwrap();
+
+    assert!(!Arc::ptr_eq(&state.load(), &STATE));
+}
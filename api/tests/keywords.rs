@@ -7,7 +7,7 @@ use serde::Deserialize;
 use std::str;
 use vault_api::routes::{
     self,
-    utils::{self, State},
+    utils::{self, ErrorResponse, State},
 };
 use vault_graph::Search;
 use warp::Filter;
@@ -33,10 +33,9 @@ async fn test_get_keyword_nonexistent() {
 
     assert_eq!(res.status(), 404);
 
-    assert_eq!(
-        res.body(),
-        "\"Keyword with id nonexistent not found.\"".as_bytes()
-    );
+    let error: ErrorResponse = serde_json::from_slice(res.body()).unwrap();
+    assert_eq!(error.code, "keyword_not_found");
+    assert_eq!(error.message, "Keyword with id nonexistent not found.");
 }
 
 #[tokio::test]
@@ -69,7 +68,11 @@ async fn test_get_random_keyword() {
 
     assert_eq!(res.status(), 200);
 
-    assert!(serde_json::from_str::<TestKeyword>(str::from_utf8(res.body()).unwrap()).is_ok(),);
+    let random_keyword: TestKeyword = serde_json::from_slice(res.body()).unwrap();
+    assert_eq!(
+        random_keyword.crates_cnt,
+        STATE.keywords()[&random_keyword.keyword].crates_cnt
+    );
 }
 
 #[tokio::test]
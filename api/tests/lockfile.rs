@@ -0,0 +1,230 @@
+#[macro_use]
+extern crate lazy_static;
+
+mod common;
+
+use vault_api::routes::{
+    self,
+    utils::{self, ErrorResponse, State},
+};
+use vault_graph::{LockedSource, Lockfile, LockfileError};
+use warp::Filter;
+
+lazy_static! {
+    static ref STATE: State = common::get_data();
+}
+
+const LOCKFILE: &str = r#"
+# This file is automatically @generated by Cargo.
+# It is not intended for manual editing.
+version = 3
+
+[[package]]
+name = "chrono"
+version = "0.0.1"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+dependencies = [
+ "libc 0.0.1",
+]
+
+[[package]]
+name = "libc"
+version = "0.0.1"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+
+[[package]]
+name = "libc"
+version = "999.0.0"
+source = "sparse+https://index.crates.io/"
+
+[[package]]
+name = "my-app"
+version = "0.1.0"
+dependencies = [
+ "chrono",
+ "libc 999.0.0",
+ "nonexistent-crate-0",
+ "patched",
+]
+
+[[package]]
+name = "nonexistent-crate-0"
+version = "1.0.0"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+
+[[package]]
+name = "patched"
+version = "0.3.0"
+source = "git+https://github.com/example/patched#0123456789abcdef"
+"#;
+
+#[test]
+fn test_lockfile_parse() {
+    let lockfile = Lockfile::parse(LOCKFILE).unwrap();
+
+    let packages: Vec<(&str, &str, LockedSource)> = lockfile
+        .packages
+        .iter()
+        .map(|package| {
+            (
+                package.name.as_str(),
+                package.version.as_str(),
+                package.source_kind(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        packages,
+        vec![
+            ("chrono", "0.0.1", LockedSource::CratesIo),
+            ("libc", "0.0.1", LockedSource::CratesIo),
+            ("libc", "999.0.0", LockedSource::CratesIo),
+            ("my-app", "0.1.0", LockedSource::Local),
+            ("nonexistent-crate-0", "1.0.0", LockedSource::CratesIo),
+            ("patched", "0.3.0", LockedSource::Other),
+        ]
+    );
+
+    // dependencies are resolved by name, and by version where ambiguous
+    assert_eq!(lockfile.packages[0].dependencies, vec![1]);
+    assert_eq!(lockfile.packages[3].dependencies, vec![0, 2, 4, 5]);
+
+    // the first lockfile format qualifies every dependency with its version and source
+    let lockfile = Lockfile::parse(
+        r#"
+[[package]]
+name = "a"
+version = "0.1.0"
+dependencies = [
+ "b 1.0.0 (registry+https://github.com/rust-lang/crates.io-index)",
+]
+
+[[package]]
+name = "b"
+version = "1.0.0"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+"#,
+    )
+    .unwrap();
+    assert_eq!(lockfile.packages[0].dependencies, vec![1]);
+}
+
+#[test]
+fn test_lockfile_parse_invalid() {
+    assert!(matches!(
+        Lockfile::parse("[[package]]\nname = \"a\"\n").unwrap_err(),
+        LockfileError::Invalid(_)
+    ));
+
+    // dependencies must match exactly one package
+    assert_eq!(
+        Lockfile::parse(&LOCKFILE.replace("\"libc 999.0.0\"", "\"libc\"")).unwrap_err(),
+        LockfileError::UnknownDependency(String::from("my-app"), String::from("libc"))
+    );
+    assert_eq!(
+        Lockfile::parse(&LOCKFILE.replace("\"chrono\",", "\"chrono 0.0.2\",")).unwrap_err(),
+        LockfileError::UnknownDependency(String::from("my-app"), String::from("chrono 0.0.2"))
+    );
+}
+
+#[tokio::test]
+async fn test_lockfile_analysis() {
+    let filters = routes::get(STATE.clone()).recover(utils::handle_rejection);
+
+    let res = warp::test::request()
+        .method("POST")
+        .path("/lockfile/analysis")
+        .body(LOCKFILE)
+        .reply(&filters)
+        .await;
+
+    assert_eq!(res.status(), 200);
+
+    let analysis: serde_json::Value = serde_json::from_slice(res.body()).unwrap();
+
+    // packages keep their lockfile order, with distances from the local workspace
+    let crates = analysis["crates"].as_array().unwrap();
+    assert_eq!(crates.len(), 6);
+    assert_eq!(crates[3]["name"], "my-app");
+    assert_eq!(crates[3]["source"], "local");
+    assert_eq!(crates[3]["distance"], 0);
+    assert_eq!(crates[0]["distance"], 1);
+    assert_eq!(crates[1]["distance"], 2);
+
+    // crates found in the registry carry its metadata
+    let chrono = &STATE.crates()["chrono"];
+    assert_eq!(crates[0]["registry"]["latest_version"], chrono.version);
+    assert_eq!(crates[0]["registry"]["description"], chrono.description);
+    assert_eq!(crates[0]["registry"]["downloads"], chrono.downloads);
+    assert_eq!(
+        crates[0]["registry"]["categories"],
+        serde_json::to_value(&chrono.categories).unwrap()
+    );
+    assert!(crates[4].get("registry").is_none());
+    assert!(crates[5].get("registry").is_none());
+
+    // only crates.io packages can be missing
+    assert_eq!(
+        analysis["missing"],
+        serde_json::json!([{
+            "name": "nonexistent-crate-0",
+            "version": "1.0.0",
+            "source": "registry+https://github.com/rust-lang/crates.io-index",
+        }])
+    );
+
+    assert_eq!(
+        analysis["duplicates"],
+        serde_json::json!([{ "name": "libc", "versions": ["0.0.1", "999.0.0"] }])
+    );
+
+    // packages ahead of the registry are not outdated
+    assert_eq!(
+        analysis["outdated"],
+        serde_json::json!([
+            {
+                "name": "chrono",
+                "version": "0.0.1",
+                "latest_version": chrono.version,
+            },
+            {
+                "name": "libc",
+                "version": "0.0.1",
+                "latest_version": STATE.crates()["libc"].version,
+            },
+        ])
+    );
+
+    // edges are shaped as in a dependency graph, with the versions on either end
+    let dependencies = analysis["dependencies"].as_array().unwrap();
+    assert_eq!(dependencies.len(), 5);
+    assert_eq!(dependencies[0]["from"], "chrono");
+    assert_eq!(dependencies[0]["to"], "libc");
+    assert_eq!(dependencies[0]["to_version"], "0.0.1");
+    assert_eq!(dependencies[2]["from"], "my-app");
+    assert_eq!(dependencies[2]["to"], "libc");
+    assert_eq!(dependencies[2]["from_version"], "0.1.0");
+    assert_eq!(dependencies[2]["to_version"], "999.0.0");
+    assert_eq!(dependencies[2]["kind"], "normal");
+    assert_eq!(dependencies[2]["active"], true);
+}
+
+#[tokio::test]
+async fn test_lockfile_analysis_invalid() {
+    let filters = routes::get(STATE.clone()).recover(utils::handle_rejection);
+
+    let res = warp::test::request()
+        .method("POST")
+        .path("/lockfile/analysis")
+        .body("[[package]]\nname = \"a\"\nversion = \"0.1.0\"\ndependencies = [\"b\"]\n")
+        .reply(&filters)
+        .await;
+
+    assert_eq!(res.status(), 400);
+    let error: ErrorResponse = serde_json::from_slice(res.body()).unwrap();
+    assert_eq!(error.code, "invalid_lockfile");
+    assert_eq!(
+        error.message,
+        "Invalid lockfile: package a depends on b, which is not locked."
+    );
+}
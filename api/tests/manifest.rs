@@ -0,0 +1,358 @@
+#[macro_use]
+extern crate lazy_static;
+
+mod common;
+
+use std::collections::BTreeSet;
+use vault_api::routes::{
+    self,
+    utils::{self, ErrorResponse, State},
+};
+use vault_graph::{Crate, DependencyGraphOptions, DependencyKind, ManifestError};
+use warp::Filter;
+
+lazy_static! {
+    static ref STATE: State = common::get_data();
+}
+
+const MANIFEST: &str = r#"
+[package]
+name = "my-service"
+version = "0.1.0"
+description = "A service."
+
+[features]
+default = ["server"]
+server = ["web/tls", "dep:json"]
+compat = ["web_old?/compression"]
+
+[dependencies]
+web = { package = "warp", version = "0.3", default-features = false }
+web_old = { package = "warp", version = "0.2", optional = true }
+json = { package = "serde_json", version = "1.0", optional = true }
+chrono = { workspace = true, default_features = false, features = ["serde"] }
+sibling = { path = "../sibling" }
+
+[target.'cfg(unix)'.dependencies]
+libc = "0.2"
+
+[dev-dependencies]
+tokio = { version = "1", features = ["macros"] }
+"#;
+
+#[test]
+fn test_manifest_parse() {
+    let manifest = Crate::from_manifest(MANIFEST).unwrap();
+
+    assert_eq!(manifest.name, "my-service");
+    assert_eq!(manifest.version, "0.1.0");
+    assert_eq!(manifest.description, "A service.");
+
+    // path dependencies are left out, and dependencies are sorted by destination
+    let dependencies: Vec<(&str, DependencyKind, bool, bool)> = manifest
+        .dependencies
+        .iter()
+        .map(|dependency| {
+            (
+                dependency.to.as_str(),
+                dependency.kind,
+                dependency.optional,
+                dependency.default_features,
+            )
+        })
+        .collect();
+    assert_eq!(
+        dependencies,
+        vec![
+            ("chrono", DependencyKind::Normal, false, false),
+            ("libc", DependencyKind::Normal, false, true),
+            ("serde_json", DependencyKind::Normal, true, true),
+            ("tokio", DependencyKind::Dev, false, true),
+            ("warp", DependencyKind::Normal, false, false),
+            ("warp", DependencyKind::Normal, true, true),
+        ]
+    );
+    assert_eq!(manifest.dependencies[0].features, vec!["serde"]);
+    assert_eq!(
+        manifest.dependencies[1].target.as_deref(),
+        Some("cfg(unix)")
+    );
+    assert!(manifest
+        .dependencies
+        .iter()
+        .all(|dependency| dependency.from == "my-service"));
+
+    // renamed dependencies are rewritten, and unreferenced optional dependencies become features
+    assert_eq!(
+        manifest.features["server"],
+        vec!["warp/tls", "dep:serde_json"]
+    );
+    assert_eq!(manifest.features["compat"], vec!["warp?/compression"]);
+    assert_eq!(manifest.features["web_old"], vec!["dep:warp"]);
+    assert!(!manifest.features.contains_key("json"));
+}
+
+#[test]
+fn test_manifest_parse_invalid() {
+    assert_eq!(
+        Crate::from_manifest("[workspace]\nmembers = [\"a\"]\n").unwrap_err(),
+        ManifestError::MissingPackage
+    );
+
+    assert!(matches!(
+        Crate::from_manifest("[package\nname = \"a\"\n").unwrap_err(),
+        ManifestError::Invalid(_)
+    ));
+
+    assert!(matches!(
+        Crate::from_manifest("[package]\nname = \"a\"\n[dependencies]\nwarp = 3\n").unwrap_err(),
+        ManifestError::Invalid(_)
+    ));
+}
+
+#[tokio::test]
+async fn test_manifest_graph() {
+    let filters = routes::get(STATE.clone()).recover(utils::handle_rejection);
+
+    let res = warp::test::request()
+        .method("POST")
+        .path("/manifest/graph")
+        .body("[package]\nname = \"my-service\"\n\n[dependencies]\nwarp = \"0.3\"\n")
+        .reply(&filters)
+        .await;
+
+    assert_eq!(res.status(), 200);
+
+    let manifest_graph: serde_json::Value = serde_json::from_slice(res.body()).unwrap();
+    let manifest_crates = manifest_graph["crates"].as_array().unwrap();
+    assert_eq!(manifest_crates[0]["name"], "my-service");
+    assert_eq!(manifest_crates[0]["distance"], 0);
+
+    // the synthetic root depends on exactly what the registry crate depends on
+    let crate_names: BTreeSet<&str> = manifest_crates[1..]
+        .iter()
+        .map(|crate_distance| crate_distance["name"].as_str().unwrap())
+        .collect();
+    let dependency_graph = STATE
+        .get_dependency_graph(
+            "warp",
+            vec![],
+            &None,
+            &None,
+            &DependencyGraphOptions::default(),
+        )
+        .unwrap();
+    assert_eq!(
+        crate_names,
+        dependency_graph
+            .crates
+            .iter()
+            .map(|crate_distance| crate_distance.name.as_str())
+            .collect()
+    );
+
+    // the registry is left untouched
+    assert!(!STATE.crates().contains_key("my-service"));
+}
+
+#[tokio::test]
+async fn test_manifest_graph_features() {
+    let filters = routes::get(STATE.clone()).recover(utils::handle_rejection);
+
+    let res = warp::test::request()
+        .method("POST")
+        .path("/manifest/graph?default_features=false&features=compat")
+        .body(MANIFEST)
+        .reply(&filters)
+        .await;
+
+    assert_eq!(res.status(), 200);
+
+    let manifest = Crate::from_manifest(MANIFEST).unwrap();
+    assert_eq!(
+        res.body(),
+        serde_json::to_string(
+            &STATE
+                .get_manifest_dependency_graph(
+                    &manifest,
+                    vec![String::from("compat")],
+                    &None,
+                    &None,
+                    &DependencyGraphOptions {
+                        default_features: false,
+                        ..DependencyGraphOptions::default()
+                    },
+                )
+                .unwrap()
+        )
+        .unwrap()
+        .as_bytes()
+    );
+
+    let res = warp::test::request()
+        .method("POST")
+        .path("/manifest/graph?features=nonexistent")
+        .body(MANIFEST)
+        .reply(&filters)
+        .await;
+
+    assert_eq!(res.status(), 400);
+    let error: ErrorResponse = serde_json::from_slice(res.body()).unwrap();
+    assert_eq!(error.code, "unknown_features");
+    assert_eq!(
+        error.message,
+        "The following features do not exist: nonexistent."
+    );
+}
+
+#[tokio::test]
+async fn test_manifest_graph_target_declarations() {
+    let filters = routes::get(STATE.clone()).recover(utils::handle_rejection);
+
+    let manifest = r#"
+[package]
+name = "my-service"
+
+[target.x86_64-unknown-linux-gnu.dependencies]
+c151 = { version = "1", default-features = false, features = ["f1"] }
+
+[target.x86_64-pc-windows-msvc.dependencies]
+c151 = { version = "1", default-features = false, features = ["f0"], optional = true }
+"#;
+
+    // both declarations are kept, along with their own features and optional flags
+    let manifest_crate = Crate::from_manifest(manifest).unwrap();
+    let dependencies: Vec<(Option<&str>, &Vec<String>, bool)> = manifest_crate
+        .dependencies
+        .iter()
+        .map(|dependency| {
+            (
+                dependency.target.as_deref(),
+                &dependency.features,
+                dependency.optional,
+            )
+        })
+        .collect();
+    assert_eq!(
+        dependencies,
+        vec![
+            (
+                Some("x86_64-pc-windows-msvc"),
+                &vec![String::from("f0")],
+                true
+            ),
+            (
+                Some("x86_64-unknown-linux-gnu"),
+                &vec![String::from("f1")],
+                false
+            ),
+        ]
+    );
+
+    // only the declaration matching the requested platform adds its features
+    for (target, enabled_features) in [
+        ("x86_64-unknown-linux-gnu", vec!["f1"]),
+        ("x86_64-pc-windows-msvc", vec!["f0"]),
+    ] {
+        let res = warp::test::request()
+            .method("POST")
+            .path(&format!("/manifest/graph?features=c151&target={target}"))
+            .body(manifest)
+            .reply(&filters)
+            .await;
+
+        assert_eq!(res.status(), 200);
+
+        let manifest_graph: serde_json::Value = serde_json::from_slice(res.body()).unwrap();
+        let crate_distance = manifest_graph["crates"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|crate_distance| crate_distance["name"] == "c151")
+            .unwrap();
+        assert_eq!(
+            crate_distance["enabled_features"],
+            serde_json::json!(enabled_features),
+            "{target}"
+        );
+
+        let declarations: Vec<&serde_json::Value> = manifest_graph["dependencies"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter(|dependency| dependency["from"] == "my-service")
+            .collect();
+        assert_eq!(declarations.len(), 1, "{target}");
+        assert_eq!(
+            declarations[0]["features"],
+            serde_json::json!(enabled_features),
+            "{target}"
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_manifest_graph_unknown_dependencies() {
+    let filters = routes::get(STATE.clone()).recover(utils::handle_rejection);
+
+    let res = warp::test::request()
+        .method("POST")
+        .path("/manifest/graph")
+        .body("[package]\nname = \"a\"\n\n[dependencies]\nnonexistent = \"1\"\nwarp = \"0.3\"\n")
+        .reply(&filters)
+        .await;
+
+    assert_eq!(res.status(), 400);
+    let error: ErrorResponse = serde_json::from_slice(res.body()).unwrap();
+    assert_eq!(error.code, "unknown_dependencies");
+    assert_eq!(
+        error.message,
+        "The following dependencies do not exist: nonexistent."
+    );
+}
+
+#[tokio::test]
+async fn test_manifest_graph_invalid() {
+    let filters = routes::get(STATE.clone()).recover(utils::handle_rejection);
+
+    let res = warp::test::request()
+        .method("POST")
+        .path("/manifest/graph")
+        .body("[workspace]\nmembers = [\"a\"]\n")
+        .reply(&filters)
+        .await;
+
+    assert_eq!(res.status(), 400);
+    let error: ErrorResponse = serde_json::from_slice(res.body()).unwrap();
+    assert_eq!(error.code, "invalid_manifest");
+    assert_eq!(error.message, "Manifest has no [package] section.");
+}
+
+#[tokio::test]
+async fn test_manifest_graph_too_large() {
+    let filters = routes::get(STATE.clone()).recover(utils::handle_rejection);
+
+    let res = warp::test::request()
+        .method("POST")
+        .path("/manifest/graph")
+        .body(format!("{MANIFEST}\n{}", "#".repeat(256 * 1024)))
+        .reply(&filters)
+        .await;
+
+    assert_eq!(res.status(), 413);
+
+    let error: ErrorResponse = serde_json::from_slice(res.body()).unwrap();
+    assert_eq!(error.code, "payload_too_large");
+
+    let res = warp::test::request()
+        .method("PUT")
+        .path("/manifest/graph")
+        .body(MANIFEST)
+        .reply(&filters)
+        .await;
+
+    assert_eq!(res.status(), 405);
+
+    let error: ErrorResponse = serde_json::from_slice(res.body()).unwrap();
+    assert_eq!(error.code, "method_not_allowed");
+}
@@ -0,0 +1,179 @@
+#[macro_use]
+extern crate lazy_static;
+
+mod common;
+
+use std::{fs, path::Path};
+use vault_api::routes::{
+    self,
+    utils::{self, State},
+};
+use vault_graph::{Metadata, MetadataError};
+use warp::Filter;
+
+lazy_static! {
+    static ref STATE: State = common::get_data();
+}
+
+/// The directory of `cargo metadata` fixtures Vault's resolution must agree with.
+const FIXTURES_PATH: &str = "tests/fixtures/metadata";
+
+/// Returns the contents of the metadata fixture with the given name.
+fn read_fixture(name: &str) -> String {
+    fs::read_to_string(Path::new(FIXTURES_PATH).join(format!("{name}.json"))).unwrap()
+}
+
+/// Posts a metadata document for comparison, returning the status code and parsed body.
+async fn compare(path: &str, metadata: String) -> (u16, serde_json::Value) {
+    let filters = routes::get(STATE.clone()).recover(utils::handle_rejection);
+
+    let res = warp::test::request()
+        .method("POST")
+        .path(path)
+        .body(metadata)
+        .reply(&filters)
+        .await;
+
+    (
+        res.status().as_u16(),
+        serde_json::from_slice(res.body()).unwrap(),
+    )
+}
+
+#[test]
+fn test_metadata_parse() {
+    let metadata = Metadata::parse(&read_fixture("selected_features")).unwrap();
+
+    assert_eq!(metadata.root.name, "metadata-fixture");
+    assert!(metadata.root_features.is_empty());
+
+    // path dependencies are left out
+    let dependencies: Vec<(&str, bool)> = metadata
+        .root
+        .dependencies
+        .iter()
+        .map(|dependency| (dependency.to.as_str(), dependency.default_features))
+        .collect();
+    assert_eq!(
+        dependencies,
+        vec![
+            ("cfg-if", true),
+            ("itoa", true),
+            ("memchr", false),
+            ("ryu", true)
+        ]
+    );
+
+    assert_eq!(
+        Metadata::parse(r#"{"packages": [], "resolve": null}"#).unwrap_err(),
+        MetadataError::MissingResolve
+    );
+    assert_eq!(
+        Metadata::parse(r#"{"packages": [], "resolve": {"nodes": [], "root": null}}"#).unwrap_err(),
+        MetadataError::MissingRoot
+    );
+    assert!(matches!(
+        Metadata::parse(r#"{"packages": [], "resolve": {"nodes": [], "root": "a"}}"#).unwrap_err(),
+        MetadataError::Invalid(_)
+    ));
+}
+
+#[tokio::test]
+async fn test_metadata_fixtures() {
+    let mut fixture_names: Vec<String> = fs::read_dir(FIXTURES_PATH)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|extension| extension == "json")
+        })
+        .map(|path| path.file_stem().unwrap().to_string_lossy().into_owned())
+        .collect();
+    fixture_names.sort();
+    assert!(!fixture_names.is_empty());
+
+    for fixture_name in fixture_names {
+        let (status, comparison) = compare(
+            "/metadata/comparison?target=x86_64-unknown-linux-gnu",
+            read_fixture(&fixture_name),
+        )
+        .await;
+
+        assert_eq!(status, 200, "{fixture_name}: {comparison}");
+        assert_eq!(
+            comparison["agrees"], true,
+            "{fixture_name} disagrees: {comparison:#}"
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_metadata_mismatches() {
+    let fixture = read_fixture("default_features");
+
+    // pretend Cargo only enabled memchr's `alloc` feature and resolved an extra crate
+    let mut metadata: serde_json::Value = serde_json::from_str(&fixture).unwrap();
+    let nodes = metadata["resolve"]["nodes"].as_array_mut().unwrap();
+    for node in nodes.iter_mut() {
+        let id = node["id"].as_str().unwrap();
+        if id.contains("#memchr@") {
+            node["features"] = serde_json::json!(["alloc"]);
+        } else if id.contains("#cfg-if@") {
+            node["deps"] = serde_json::json!([{
+                "name": "itoa",
+                "pkg": "registry+https://github.com/rust-lang/crates.io-index#itoa@1.0.18",
+                "dep_kinds": [{ "kind": null, "target": null }],
+            }]);
+        }
+    }
+
+    let (status, comparison) = compare("/metadata/comparison", metadata.to_string()).await;
+
+    assert_eq!(status, 200);
+    assert_eq!(comparison["root"], "metadata-fixture");
+    assert_eq!(comparison["agrees"], false);
+    assert_eq!(
+        comparison["cargo_only_dependencies"],
+        serde_json::json!([{ "from": "cfg-if", "to": "itoa", "kind": "normal" }])
+    );
+    assert_eq!(comparison["vault_only_dependencies"], serde_json::json!([]));
+    assert_eq!(comparison["cargo_only_crates"], serde_json::json!([]));
+    assert_eq!(
+        comparison["feature_mismatches"],
+        serde_json::json!([{
+            "name": "memchr",
+            "cargo": ["alloc"],
+            "vault": ["alloc", "default", "std"],
+        }])
+    );
+}
+
+#[tokio::test]
+async fn test_metadata_invalid() {
+    let (status, error) = compare(
+        "/metadata/comparison",
+        String::from(r#"{"packages": [], "resolve": null}"#),
+    )
+    .await;
+
+    assert_eq!(status, 400);
+    assert_eq!(error["code"], "invalid_metadata");
+    assert_eq!(
+        error["message"],
+        "Metadata has no resolve section, as produced by `cargo metadata --no-deps`."
+    );
+
+    let (status, error) = compare(
+        "/metadata/comparison",
+        read_fixture("default_features")
+            .replace("\"name\": \"itoa\"", "\"name\": \"nonexistent-0\""),
+    )
+    .await;
+
+    assert_eq!(status, 400);
+    assert_eq!(error["code"], "unknown_dependencies");
+    assert_eq!(
+        error["message"],
+        "The following dependencies do not exist: nonexistent-0."
+    );
+}
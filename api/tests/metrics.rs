@@ -0,0 +1,140 @@
+use std::sync::Arc;
+use vault_api::routes::{
+    self, metrics,
+    utils::{self, SharedState},
+};
+use vault_graph::{Graph, LoadProgress};
+use warp::Filter;
+
+/// Returns the value of a metric in the Prometheus text format, if present.
+///
+/// # Arguments
+/// * `body` - the scraped metrics.
+/// * `name` - the name of the metric.
+/// * `labels` - the labels the metric must have, formatted as `name="value"`.
+fn get_value(body: &str, name: &str, labels: &[&str]) -> Option<f64> {
+    body.lines()
+        .filter(|line| !line.starts_with('#'))
+        .filter_map(|line| line.rsplit_once(' '))
+        .find(|(series, _)| {
+            let series_name = series.split('{').next().unwrap();
+            series_name == name && labels.iter().all(|label| series.contains(label))
+        })
+        .map(|(_, value)| value.parse().unwrap())
+}
+
+#[tokio::test]
+async fn test_metrics() {
+    let mut graph = Graph::test();
+    graph.set_dependency_graph_observer(metrics::observe_dependency_graph);
+    let filters = routes::get(Arc::new(graph))
+        .recover(utils::handle_rejection)
+        .with(warp::log::custom(|info| metrics::observe_request(&info)));
+
+    for path in [
+        "/graph/libc",
+        "/graph/libc",
+        "/crates/nonexistent",
+        "/nonexistent",
+    ] {
+        warp::test::request().path(path).reply(&filters).await;
+    }
+
+    let res = warp::test::request().path("/metrics").reply(&filters).await;
+
+    assert_eq!(res.status(), 200);
+    assert!(res.headers()["content-type"]
+        .to_str()
+        .unwrap()
+        .starts_with("text/plain"));
+
+    let body = String::from_utf8(res.body().to_vec()).unwrap();
+
+    // requests are counted by route rather than by path
+    let requests = |route: &str, status: &str| {
+        get_value(
+            &body,
+            "vault_http_requests_total",
+            &[
+                &format!("route=\"{route}\""),
+                &format!("status=\"{status}\""),
+            ],
+        )
+    };
+    assert_eq!(requests("/graph/{crate}", "200"), Some(2.0));
+    assert_eq!(requests("/crates/{crate}", "404"), Some(1.0));
+    assert_eq!(requests("unmatched", "404"), Some(1.0));
+    assert!(get_value(
+        &body,
+        "vault_http_request_duration_seconds_count",
+        &["route=\"/graph/{crate}\""]
+    )
+    .is_some());
+
+    // the second dependency graph is served from the cache
+    assert_eq!(
+        get_value(&body, "vault_dependency_graph_crates_count", &[]),
+        Some(1.0)
+    );
+    assert!(get_value(&body, "vault_dependency_graph_edges_sum", &[]).unwrap() > 0.0);
+    assert_eq!(
+        get_value(&body, "vault_dependency_graph_cache_hits_total", &[]),
+        Some(1.0)
+    );
+    assert_eq!(
+        get_value(&body, "vault_dependency_graph_cache_hit_ratio", &[]),
+        Some(0.5)
+    );
+
+    assert_eq!(get_value(&body, "vault_ready", &[]), Some(1.0));
+    assert!(get_value(&body, "vault_dataset_age_seconds", &[]).is_some());
+    assert!(get_value(
+        &body,
+        "vault_load_phase_duration_seconds",
+        &["phase=\"centrality\""]
+    )
+    .is_some());
+}
+
+#[tokio::test]
+async fn test_metrics_loading() {
+    let filters =
+        routes::get(SharedState::loading(LoadProgress::default())).recover(utils::handle_rejection);
+
+    let res = warp::test::request().path("/metrics").reply(&filters).await;
+
+    assert_eq!(res.status(), 200);
+
+    let body = String::from_utf8(res.body().to_vec()).unwrap();
+    assert_eq!(get_value(&body, "vault_ready", &[]), Some(0.0));
+    assert!(get_value(&body, "vault_dataset_age_seconds", &[]).is_none());
+}
+
+#[tokio::test]
+async fn test_metrics_cache_warmup() {
+    let mut graph = Graph::test();
+    let load_seconds = graph.load_report().seconds;
+    graph.warm_cache(2);
+
+    // the warm-up is recorded as the last phase of loading
+    let load_report = graph.load_report();
+    let phase = load_report.phases.last().unwrap();
+    assert_eq!(phase.name, "cache_warmup");
+    assert_eq!(phase.items, Some(2));
+    assert_eq!(phase.started_at, load_seconds);
+    assert_eq!(load_report.seconds, load_seconds + phase.seconds);
+    assert_eq!(graph.dependency_graph_cache().stats().entries, 2);
+
+    let filters = routes::get(Arc::new(graph)).recover(utils::handle_rejection);
+    let res = warp::test::request().path("/metrics").reply(&filters).await;
+
+    assert_eq!(res.status(), 200);
+
+    let body = String::from_utf8(res.body().to_vec()).unwrap();
+    assert!(get_value(
+        &body,
+        "vault_load_phase_duration_seconds",
+        &["phase=\"cache_warmup\""]
+    )
+    .is_some());
+}
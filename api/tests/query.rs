@@ -0,0 +1,371 @@
+#[macro_use]
+extern crate lazy_static;
+
+mod common;
+
+use std::collections::BTreeSet;
+use vault_api::routes::{
+    self,
+    utils::{self, ErrorResponse, State},
+};
+use vault_graph::{
+    DependencyGraphOptions, DependencyGraphQuery, DependencyGraphQueryError, DependencyGraphRoot,
+    DependencyKind, GraphFormat, QueryFieldError, Resolver,
+};
+use warp::Filter;
+
+lazy_static! {
+    static ref STATE: State = common::get_data();
+}
+
+/// Posts a graph query, returning the status code and body.
+async fn post_query(query: serde_json::Value) -> (u16, Vec<u8>) {
+    let filters = routes::get(STATE.clone()).recover(utils::handle_rejection);
+
+    let res = warp::test::request()
+        .method("POST")
+        .path("/graph")
+        .body(query.to_string())
+        .reply(&filters)
+        .await;
+
+    (res.status().as_u16(), res.body().to_vec())
+}
+
+/// Returns the names and distances of the crates in a serialized dependency graph.
+fn get_crate_distances(dependency_graph: &serde_json::Value) -> Vec<(String, u64)> {
+    dependency_graph["crates"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|crate_distance| {
+            (
+                crate_distance["name"].as_str().unwrap().to_owned(),
+                crate_distance["distance"].as_u64().unwrap(),
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn test_query_parse() {
+    let query = DependencyGraphQuery::parse(
+        r#"{
+            "roots": [
+                { "name": "libc", "features": ["f1"], "default_features": false },
+                { "name": "warp", "all_features": true }
+            ],
+            "target": "x86_64-unknown-linux-gnu",
+            "cfgs": ["unix"],
+            "resolver": 2,
+            "max_depth": 3,
+            "exclude": ["serde_json"],
+            "kinds": ["normal", "dev"],
+            "potential_dependencies": null,
+            "format": "dot"
+        }"#,
+    )
+    .unwrap();
+
+    assert_eq!(
+        query.roots,
+        vec![
+            DependencyGraphRoot {
+                name: String::from("libc"),
+                features: vec![String::from("f1")],
+                default_features: false,
+                all_features: false,
+            },
+            DependencyGraphRoot {
+                all_features: true,
+                ..DependencyGraphRoot::new("warp")
+            },
+        ]
+    );
+    assert_eq!(query.target.as_deref(), Some("x86_64-unknown-linux-gnu"));
+    assert_eq!(query.cfgs, vec!["unix"]);
+    assert_eq!(query.resolver, Resolver::V2);
+    assert_eq!(query.max_depth, Some(3));
+    assert_eq!(query.exclude, vec!["serde_json"]);
+    assert_eq!(
+        query.kinds,
+        Some(vec![DependencyKind::Normal, DependencyKind::Dev])
+    );
+    assert_eq!(query.potential_dependencies, None);
+    assert_eq!(query.format, GraphFormat::Dot);
+
+    // improper fields are reported by their path
+    let field_error = |field: &str, message: &str| {
+        DependencyGraphQueryError::Fields(vec![QueryFieldError {
+            field: String::from(field),
+            message: String::from(message),
+        }])
+    };
+    for (query, error) in [
+        (
+            r#"{ "roots": [{ "name": "libc", "features": "f1" }] }"#,
+            field_error(
+                "roots[0].features",
+                "invalid type: string \"f1\", expected a sequence",
+            ),
+        ),
+        (
+            r#"{ "roots": [{ "name": "libc" }, { "features": [] }] }"#,
+            field_error("roots[1].name", "missing field"),
+        ),
+        (
+            r#"{ "roots": [{ "name": "libc" }, "warp"] }"#,
+            field_error(
+                "roots[1]",
+                "invalid type: string \"warp\", expected a root crate object",
+            ),
+        ),
+        (
+            r#"{ "roots": [{ "name": "libc" }], "cfgs": ["unix", "not a cfg("] }"#,
+            field_error("cfgs[1]", "not a valid cfg"),
+        ),
+        (
+            r#"{ "roots": [{ "name": "libc" }], "resolver": 3 }"#,
+            field_error("resolver", "expected 1 or 2"),
+        ),
+        (
+            r#"{ "roots": [{ "name": "libc" }], "kinds": ["normal", "peer"] }"#,
+            field_error(
+                "kinds[1]",
+                "unknown variant `peer`, expected one of `normal`, `dev`, `build`",
+            ),
+        ),
+        (
+            r#"{ "roots": [{ "name": "libc", "default": true }] }"#,
+            field_error(
+                "roots[0].default",
+                "unknown field `default`, expected one of `name`, `features`, `default_features`, `all_features`",
+            ),
+        ),
+        (
+            r#"{ "roots": [{ "name": "libc" }], "max_depth": -1 }"#,
+            field_error("max_depth", "invalid value: integer `-1`, expected usize"),
+        ),
+        (
+            r#"{ "roots": [] }"#,
+            field_error("roots", "must list at least one root crate"),
+        ),
+    ] {
+        assert_eq!(DependencyGraphQuery::parse(query).unwrap_err(), error);
+    }
+    assert!(
+        DependencyGraphQuery::parse(r#"{ "roots": [{ "name": "libc" }], "depth": 2 }"#)
+            .unwrap_err()
+            .to_string()
+            .starts_with("Invalid graph query: depth: unknown field `depth`")
+    );
+    assert_eq!(
+        DependencyGraphQuery::parse(r#"{ "roots": [{ "name": "a" }, { "name": "a" }] }"#)
+            .unwrap_err()
+            .to_string(),
+        "Invalid graph query: roots[1].name: duplicate root crate."
+    );
+    assert_eq!(
+        DependencyGraphQuery::parse("{}").unwrap_err().to_string(),
+        "Invalid graph query: roots: missing field."
+    );
+    assert!(matches!(
+        DependencyGraphQuery::parse("[]").unwrap_err(),
+        DependencyGraphQueryError::Invalid(_)
+    ));
+}
+
+#[tokio::test]
+async fn test_query_matches_get() {
+    let filters = routes::get(STATE.clone()).recover(utils::handle_rejection);
+
+    for (path, query) in [
+        (
+            "/graph/libc",
+            serde_json::json!({ "roots": [{ "name": "libc" }] }),
+        ),
+        (
+            "/graph/libc?default_features=false&resolver=2&target=x86_64-pc-windows-msvc",
+            serde_json::json!({
+                "roots": [{ "name": "libc", "default_features": false }],
+                "resolver": 2,
+                "target": "x86_64-pc-windows-msvc",
+            }),
+        ),
+        (
+            "/graph/libc?format=mermaid",
+            serde_json::json!({ "roots": [{ "name": "libc" }], "format": "mermaid" }),
+        ),
+    ] {
+        let res = warp::test::request().path(path).reply(&filters).await;
+        assert_eq!(res.status(), 200);
+
+        let (status, body) = post_query(query).await;
+        assert_eq!(status, 200);
+        assert_eq!(body, res.body().to_vec(), "{path}");
+    }
+}
+
+#[tokio::test]
+async fn test_query_roots() {
+    let (status, body) = post_query(serde_json::json!({
+        "roots": [{ "name": "libc" }, { "name": "warp" }],
+    }))
+    .await;
+    assert_eq!(status, 200);
+
+    let dependency_graph: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    let crate_distances = get_crate_distances(&dependency_graph);
+
+    // both roots come first, and every crate of either graph is included
+    assert_eq!(crate_distances[0], (String::from("libc"), 0));
+    assert_eq!(crate_distances[1], (String::from("warp"), 0));
+
+    let names: BTreeSet<&String> = crate_distances.iter().map(|(name, _)| name).collect();
+    for crate_id in ["libc", "warp"] {
+        for crate_distance in STATE
+            .get_dependency_graph(
+                crate_id,
+                vec![],
+                &None,
+                &None,
+                &DependencyGraphOptions::default(),
+            )
+            .unwrap()
+            .crates
+        {
+            assert!(names.contains(crate_distance.name));
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_query_limits() {
+    let (_, body) = post_query(serde_json::json!({ "roots": [{ "name": "libc" }] })).await;
+    let full_graph: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    let full_distances = get_crate_distances(&full_graph);
+
+    // crates past the depth limit are left out, along with edges to them
+    let (status, body) = post_query(serde_json::json!({
+        "roots": [{ "name": "libc" }],
+        "max_depth": 1,
+    }))
+    .await;
+    assert_eq!(status, 200);
+
+    let dependency_graph: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    let crate_distances = get_crate_distances(&dependency_graph);
+    assert!(crate_distances.iter().all(|(_, distance)| *distance <= 1));
+    assert_eq!(
+        crate_distances.len(),
+        full_distances
+            .iter()
+            .filter(|(_, distance)| *distance <= 1)
+            .count()
+    );
+
+    let names: BTreeSet<&str> = crate_distances
+        .iter()
+        .map(|(name, _)| name.as_str())
+        .collect();
+    for dependency in dependency_graph["dependencies"].as_array().unwrap() {
+        assert!(names.contains(dependency["to"].as_str().unwrap()));
+    }
+
+    // excluded crates are left out
+    let excluded = &full_distances[1].0;
+    let (status, body) = post_query(serde_json::json!({
+        "roots": [{ "name": "libc" }],
+        "exclude": [excluded],
+    }))
+    .await;
+    assert_eq!(status, 200);
+
+    let dependency_graph: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert!(get_crate_distances(&dependency_graph)
+        .iter()
+        .all(|(name, _)| name != excluded));
+
+    // only the listed kinds of dependencies are followed
+    let (status, body) = post_query(serde_json::json!({
+        "roots": [{ "name": "libc" }],
+        "resolver": 2,
+        "kinds": ["normal"],
+    }))
+    .await;
+    assert_eq!(status, 200);
+
+    let dependency_graph: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert!(dependency_graph["dependencies"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .all(|dependency| dependency["kind"] == "normal"));
+}
+
+#[tokio::test]
+async fn test_query_invalid() {
+    let (status, body) = post_query(serde_json::json!({
+        "roots": [{ "name": "libc", "features": [1] }],
+        "format": "svg",
+    }))
+    .await;
+    assert_eq!(status, 400);
+
+    let error: ErrorResponse = serde_json::from_slice(&body).unwrap();
+    assert_eq!(error.code, "invalid_graph_query");
+    assert_eq!(
+        error.message,
+        "Invalid graph query: format: unknown format `svg`."
+    );
+    assert_eq!(error.parameters, vec!["format"]);
+
+    let (status, body) = post_query(serde_json::json!({
+        "roots": [{ "name": "libc", "features": [1] }],
+    }))
+    .await;
+    assert_eq!(status, 400);
+
+    let error: ErrorResponse = serde_json::from_slice(&body).unwrap();
+    assert_eq!(error.code, "invalid_graph_query");
+    assert_eq!(error.parameters, vec!["roots[0].features[0]"]);
+
+    let (status, body) = post_query(serde_json::json!({
+        "roots": [{ "name": "libc" }, { "name": "nonexistent-crate-0" }],
+    }))
+    .await;
+    assert_eq!(status, 404);
+
+    let error: ErrorResponse = serde_json::from_slice(&body).unwrap();
+    assert_eq!(error.code, "crate_not_found");
+
+    let (status, body) = post_query(serde_json::json!({
+        "roots": [{ "name": "libc" }],
+        "target": "nonexistent-target",
+        "exclude": ["nonexistent-crate-0"],
+    }))
+    .await;
+    assert_eq!(status, 400);
+
+    let error: ErrorResponse = serde_json::from_slice(&body).unwrap();
+    assert_eq!(error.code, "invalid_options");
+    assert_eq!(
+        error.message,
+        "The following options were provided with improper values: target, exclude[0]."
+    );
+    assert_eq!(error.parameters, vec!["target", "exclude[0]"]);
+
+    // unknown features are reported by the fields which requested them
+    let (status, body) = post_query(serde_json::json!({
+        "roots": [
+            { "name": "warp" },
+            { "name": "libc", "features": ["f4", "nonexistent-feature"] },
+        ],
+    }))
+    .await;
+    assert_eq!(status, 400);
+
+    let error: ErrorResponse = serde_json::from_slice(&body).unwrap();
+    assert_eq!(error.code, "unknown_features");
+    assert_eq!(error.parameters, vec!["roots[1].features[1]"]);
+}
@@ -0,0 +1,216 @@
+#[macro_use]
+extern crate lazy_static;
+
+mod common;
+
+use std::collections::BTreeSet;
+use vault_api::routes::{
+    self,
+    utils::{self, ErrorResponse, State},
+};
+use vault_graph::{DependencyGraphOptions, SbomFormat};
+use warp::Filter;
+
+lazy_static! {
+    static ref STATE: State = common::get_data();
+}
+
+const MANIFEST: &str = r#"
+[package]
+name = "my-service"
+version = "0.1.0"
+license = "MIT"
+
+[features]
+json = ["dep:serde_json"]
+
+[dependencies]
+chrono = "0.4"
+serde_json = { version = "1.0", optional = true }
+"#;
+
+/// Requests an SBOM, returning the status code, `Content-Type` header and parsed body.
+async fn get_sbom(method: &str, path: &str, body: &str) -> (u16, String, serde_json::Value) {
+    let filters = routes::get(STATE.clone()).recover(utils::handle_rejection);
+
+    let res = warp::test::request()
+        .method(method)
+        .path(path)
+        .body(String::from(body))
+        .reply(&filters)
+        .await;
+
+    (
+        res.status().as_u16(),
+        res.headers()["content-type"].to_str().unwrap().to_owned(),
+        serde_json::from_slice(res.body()).unwrap(),
+    )
+}
+
+/// Returns the names of the crates in the default `DependencyGraph` of the crate with the given id.
+fn get_crate_names(crate_id: &str) -> BTreeSet<String> {
+    STATE
+        .get_dependency_graph(
+            crate_id,
+            vec![],
+            &None,
+            &None,
+            &DependencyGraphOptions::default(),
+        )
+        .unwrap()
+        .crates
+        .iter()
+        .map(|crate_distance| crate_distance.name.clone())
+        .collect()
+}
+
+#[tokio::test]
+async fn test_sbom_cyclonedx() {
+    let (status, content_type, bom) = get_sbom("GET", "/sbom/libc", "").await;
+
+    assert_eq!(status, 200);
+    assert_eq!(content_type, SbomFormat::CycloneDx.content_type());
+    assert_eq!(bom["bomFormat"], "CycloneDX");
+    assert_eq!(bom["specVersion"], "1.5");
+    assert!(bom["serialNumber"]
+        .as_str()
+        .unwrap()
+        .starts_with("urn:uuid:"));
+
+    // the root crate is described by the metadata, and its dependencies are components
+    let libc = &STATE.crates()["libc"];
+    let root = &bom["metadata"]["component"];
+    assert_eq!(root["name"], "libc");
+    assert_eq!(root["purl"], format!("pkg:cargo/libc@{}", libc.version));
+    assert_eq!(root["bom-ref"], root["purl"]);
+
+    let mut names: BTreeSet<String> = bom["components"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|component| component["name"].as_str().unwrap().to_owned())
+        .collect();
+    names.insert(String::from("libc"));
+    assert_eq!(names, get_crate_names("libc"));
+
+    // licenses are listed as SPDX expressions where declared
+    for component in bom["components"].as_array().unwrap() {
+        let crate_val = &STATE.crates()[component["name"].as_str().unwrap()];
+        match crate_val.license.as_deref() {
+            Some(license) => assert_eq!(
+                component["licenses"][0]["expression"],
+                license.replace('/', " OR ")
+            ),
+            None => assert!(component.get("licenses").is_none()),
+        }
+    }
+    assert!(bom["components"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .any(|component| component.get("licenses").is_some()));
+
+    // every component lists the components it depends on
+    let dependencies = bom["dependencies"].as_array().unwrap();
+    assert_eq!(dependencies.len(), names.len());
+    assert_eq!(dependencies[0]["ref"], root["purl"]);
+    assert!(!dependencies[0]["dependsOn"].as_array().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_sbom_spdx() {
+    let (status, content_type, document) = get_sbom("GET", "/sbom/libc?format=spdx", "").await;
+
+    assert_eq!(status, 200);
+    assert_eq!(content_type, SbomFormat::Spdx.content_type());
+    assert_eq!(document["spdxVersion"], "SPDX-2.3");
+    assert_eq!(document["dataLicense"], "CC0-1.0");
+    assert_eq!(document["SPDXID"], "SPDXRef-DOCUMENT");
+
+    let packages = document["packages"].as_array().unwrap();
+    assert_eq!(packages.len(), get_crate_names("libc").len());
+    assert_eq!(packages[0]["name"], "libc");
+    assert_eq!(
+        packages[0]["externalRefs"][0]["referenceLocator"],
+        format!("pkg:cargo/libc@{}", STATE.crates()["libc"].version)
+    );
+
+    // the document describes the root crate, which depends on the rest
+    let relationships = document["relationships"].as_array().unwrap();
+    assert_eq!(
+        relationships[0],
+        serde_json::json!({
+            "spdxElementId": "SPDXRef-DOCUMENT",
+            "relationshipType": "DESCRIBES",
+            "relatedSpdxElement": packages[0]["SPDXID"],
+        })
+    );
+    let spdx_ids: BTreeSet<&str> = packages
+        .iter()
+        .map(|package| package["SPDXID"].as_str().unwrap())
+        .collect();
+    assert_eq!(spdx_ids.len(), packages.len());
+    for relationship in &relationships[1..] {
+        assert!(spdx_ids.contains(relationship["spdxElementId"].as_str().unwrap()));
+        assert!(spdx_ids.contains(relationship["relatedSpdxElement"].as_str().unwrap()));
+    }
+}
+
+#[tokio::test]
+async fn test_sbom_manifest() {
+    let (status, _, bom) = get_sbom("POST", "/manifest/sbom?features=json", MANIFEST).await;
+
+    assert_eq!(status, 200);
+    assert_eq!(bom["metadata"]["component"]["name"], "my-service");
+    assert_eq!(
+        bom["metadata"]["component"]["licenses"],
+        serde_json::json!([{ "expression": "MIT" }])
+    );
+
+    let names: BTreeSet<&str> = bom["components"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|component| component["name"].as_str().unwrap())
+        .collect();
+    assert!(names.contains("chrono"));
+    assert!(names.contains("serde_json"));
+
+    // the manifest is not published, so it cannot be downloaded
+    let (status, _, document) = get_sbom("POST", "/manifest/sbom?format=spdx", MANIFEST).await;
+
+    assert_eq!(status, 200);
+    assert_eq!(document["name"], "my-service-0.1.0");
+    assert_eq!(document["packages"][0]["downloadLocation"], "NOASSERTION");
+    assert_eq!(document["packages"][0]["licenseDeclared"], "MIT");
+    assert!(document["packages"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .all(|package| package["name"] != "serde_json"));
+}
+
+#[tokio::test]
+async fn test_sbom_invalid() {
+    let filters = routes::get(STATE.clone()).recover(utils::handle_rejection);
+
+    let res = warp::test::request()
+        .path("/sbom/warp?format=swid")
+        .reply(&filters)
+        .await;
+
+    assert_eq!(res.status(), 400);
+    let error: ErrorResponse = serde_json::from_slice(res.body()).unwrap();
+    assert_eq!(error.code, "invalid_options");
+    assert_eq!(
+        error.message,
+        "The following options were provided with improper values: format."
+    );
+
+    let res = warp::test::request()
+        .path("/sbom/nonexistent-crate-0")
+        .reply(&filters)
+        .await;
+
+    assert_eq!(res.status(), 404);
+}
@@ -8,6 +8,7 @@ use vault_api::routes::{
     state::LastUpdated,
     utils::{self, State},
 };
+use vault_graph::CacheStats;
 use warp::Filter;
 
 lazy_static! {
@@ -34,3 +35,38 @@ async fn test_time_since_last_update() {
         .as_bytes()
     );
 }
+
+#[tokio::test]
+async fn test_cache_stats() {
+    let filters = routes::get(STATE.clone()).recover(utils::handle_rejection);
+
+    let stats_before: CacheStats = serde_json::from_slice(
+        warp::test::request()
+            .path("/state/cache")
+            .reply(&filters)
+            .await
+            .body(),
+    )
+    .unwrap();
+
+    // the first request may or may not be cached already, but the second must be
+    for _ in 0..2 {
+        let res = warp::test::request()
+            .path("/graph/tokio?features=f0,f4")
+            .reply(&filters)
+            .await;
+
+        assert_eq!(res.status(), 200);
+    }
+
+    let res = warp::test::request()
+        .path("/state/cache")
+        .reply(&filters)
+        .await;
+
+    assert_eq!(res.status(), 200);
+
+    let stats_after: CacheStats = serde_json::from_slice(res.body()).unwrap();
+    assert!(stats_after.hits > stats_before.hits);
+    assert!(stats_after.entries > 0);
+}
@@ -4,6 +4,7 @@ import {
   CfgNameLIst,
   Crate,
   DependencyGraph,
+  ErrorResponse,
   LastUpdated,
   Response,
   TargetList,
@@ -23,13 +24,16 @@ const wrapResponse = <T>(
           }
         : {
             success: false as const,
-            error: String(response.data),
+            error: String(
+              (response.data as unknown as ErrorResponse)?.message ??
+                response.data
+            ),
           }
     )
     .catch((error) => ({
       success: false as const,
       error:
-        error?.response?.data ??
+        (error?.response?.data as ErrorResponse | undefined)?.message ??
         "Server error - please post an issue at https://github.com/arpanlaha/vault/issues",
     }));
 
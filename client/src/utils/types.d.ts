@@ -5,6 +5,13 @@ export type Response<T> =
     }
   | { error: string; success: false };
 
+export interface ErrorResponse {
+  code: string;
+  message: string;
+  parameters?: string[];
+  suggestions?: string[];
+}
+
 export interface Crate {
   categories: string[];
   created_at: string;
@@ -12,7 +19,11 @@ export interface Crate {
   downloads: number;
   features: Record<string, string[]>;
   keywords: string[];
+  max_depth: number;
   name: string;
+  pagerank: number;
+  transitive_dependencies: number;
+  transitive_dependents: number;
   version: string;
 }
 
@@ -22,7 +33,13 @@ export interface CrateDistance extends Crate {
 }
 
 export interface Dependency {
+  activated_by: string[];
+  active: boolean;
+  default_features: boolean;
+  features: string[];
   from: string;
+  kind: "normal" | "dev" | "build";
+  optional: boolean;
   target?: string;
   to: string;
 }
@@ -30,6 +47,7 @@ export interface Dependency {
 export interface DependencyGraph {
   crates: CrateDistance[];
   dependencies: Dependency[];
+  potential_dependencies?: Dependency[];
 }
 
 export interface CrateInfo {
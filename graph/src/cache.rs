@@ -0,0 +1,216 @@
+use super::{DependencyGraphOptions, DependencyGraphQuery, Graph, LoadPhase};
+use lru::LruCache;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::{
+    cmp::Reverse,
+    num::NonZeroUsize,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::Instant,
+};
+
+/// The default max number of serialized dependency graphs kept in the cache.
+pub const DEFAULT_CACHE_CAPACITY: usize = 256;
+
+/// A bounded least-recently-used cache of serialized dependency graphs.
+pub struct DependencyGraphCache {
+    /// The cached dependency graphs by normalized query, or `None` if caching is disabled.
+    entries: Option<Mutex<LruCache<DependencyGraphQuery, Arc<String>>>>,
+
+    /// The number of lookups which found a cached dependency graph.
+    hits: AtomicU64,
+
+    /// The number of lookups which required computing a dependency graph.
+    misses: AtomicU64,
+}
+
+impl DependencyGraphCache {
+    /// Creates a new `DependencyGraphCache`.
+    ///
+    /// # Arguments
+    /// * `capacity` - the max number of dependency graphs to keep, where `0` disables caching.
+    #[must_use]
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            entries: NonZeroUsize::new(capacity)
+                .map(|capacity| Mutex::new(LruCache::new(capacity))),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    /// Removes every cached dependency graph, leaving the counters intact.
+    pub fn clear(&self) {
+        if let Some(entries) = &self.entries {
+            entries.lock().unwrap().clear();
+        }
+    }
+
+    /// Returns the current size and hit/miss counters of the cache.
+    #[must_use]
+    pub fn stats(&self) -> CacheStats {
+        let (capacity, entries) = self.entries.as_ref().map_or((0, 0), |entries| {
+            let entries = entries.lock().unwrap();
+            (entries.cap().get(), entries.len())
+        });
+
+        CacheStats {
+            capacity,
+            entries,
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Returns the cached dependency graph for a query, computing and inserting it if absent.
+    ///
+    /// The lock is not held while computing, so concurrent misses for the same query may compute it twice.
+    ///
+    /// # Arguments
+    /// * `key` - the normalized query.
+    /// * `compute` - a function returning the serialized dependency graph, or `None` if the crate does not exist.
+    fn get_or_insert(
+        &self,
+        key: DependencyGraphQuery,
+        compute: impl FnOnce() -> Option<String>,
+    ) -> Option<Arc<String>> {
+        let Some(entries) = &self.entries else {
+            self.misses.fetch_add(1, Ordering::Relaxed);
+            return compute().map(Arc::new);
+        };
+
+        if let Some(dependency_graph) = entries.lock().unwrap().get(&key) {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            return Some(dependency_graph.clone());
+        }
+
+        self.misses.fetch_add(1, Ordering::Relaxed);
+        let dependency_graph = Arc::new(compute()?);
+        entries.lock().unwrap().put(key, dependency_graph.clone());
+
+        Some(dependency_graph)
+    }
+}
+
+impl Default for DependencyGraphCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_CACHE_CAPACITY)
+    }
+}
+
+impl Graph {
+    /// Returns the serialized dependency graph of the specified crate, using the cache if possible.
+    ///
+    /// The query is normalized first, so requests differing only in feature order share an entry. As such, the order
+    /// of the root crate's enabled features follows whichever request populated the entry.
+    ///
+    /// If no crate matches the specified name, returns `None`.
+    ///
+    /// # Arguments
+    /// * `crate_id` - the name of the crate to analyze.
+    /// * `features` - the list of features to enable.
+    /// * `target` - the target triple to resolve platform-specific dependencies for.
+    /// * `cfg_name` - an additional cfg name to enable when resolving platform-specific dependencies.
+    /// * `options` - the `DependencyGraphOptions` controlling how the root crate's features are selected.
+    #[must_use]
+    pub fn get_cached_dependency_graph(
+        &self,
+        crate_id: &str,
+        features: Vec<String>,
+        target: &Option<String>,
+        cfg_name: &Option<String>,
+        options: &DependencyGraphOptions,
+    ) -> Option<Arc<String>> {
+        self.query_cached_dependency_graph(&DependencyGraphQuery::new(
+            crate_id, features, target, cfg_name, options,
+        ))
+    }
+
+    /// Returns the serialized dependency graph described by a `DependencyGraphQuery`, using the cache if possible.
+    ///
+    /// The query is normalized first, as with `get_cached_dependency_graph`. Its format is ignored, as dependency
+    /// graphs are always cached as JSON.
+    ///
+    /// If any root crate is not found in the registry, returns `None`.
+    ///
+    /// # Arguments
+    /// * `query` - the `DependencyGraphQuery` describing the root crates and how to resolve their dependencies.
+    #[must_use]
+    pub fn query_cached_dependency_graph(
+        &self,
+        query: &DependencyGraphQuery,
+    ) -> Option<Arc<String>> {
+        self.dependency_graph_cache
+            .get_or_insert(query.normalized(&self.default_target), || {
+                self.query_dependency_graph(query)
+                    .map(|dependency_graph| serde_json::to_string(&dependency_graph).unwrap())
+            })
+    }
+
+    /// Returns an immutable reference to the dependency graph cache.
+    #[must_use]
+    pub const fn dependency_graph_cache(&self) -> &DependencyGraphCache {
+        &self.dependency_graph_cache
+    }
+
+    /// Replaces the dependency graph cache with an empty one of the given capacity.
+    ///
+    /// # Arguments
+    /// * `capacity` - the max number of dependency graphs to keep, where `0` disables caching.
+    pub fn set_cache_capacity(&mut self, capacity: usize) {
+        self.dependency_graph_cache = DependencyGraphCache::new(capacity);
+    }
+
+    /// Computes and caches the default dependency graphs of the most downloaded crates, in parallel.
+    ///
+    /// The warm-up is recorded as the `cache_warmup` phase of the `Graph`'s `LoadReport`, as the
+    /// graph is not ready to serve until it finishes.
+    ///
+    /// # Arguments
+    /// * `crate_count` - the number of crates to pre-warm the cache with.
+    pub fn warm_cache(&mut self, crate_count: usize) {
+        let start = Instant::now();
+
+        let mut crate_ids: Vec<&String> = self.crates().keys().collect();
+        crate_ids.sort_by_key(|&crate_id| Reverse(self.crates()[crate_id].downloads));
+        crate_ids.truncate(crate_count);
+
+        crate_ids.par_iter().for_each(|crate_id| {
+            let _ = self.get_cached_dependency_graph(
+                crate_id,
+                vec![],
+                &None,
+                &None,
+                &DependencyGraphOptions::default(),
+            );
+        });
+
+        let phase = LoadPhase {
+            name: "cache_warmup",
+            items: Some(crate_ids.len()),
+            started_at: self.load_report.seconds,
+            seconds: start.elapsed().as_secs_f64(),
+        };
+        self.load_report.seconds += phase.seconds;
+        self.load_report.phases.push(phase);
+    }
+}
+
+/// The size and hit/miss counters of a `DependencyGraphCache`.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct CacheStats {
+    /// The max number of dependency graphs kept.
+    pub capacity: usize,
+
+    /// The number of dependency graphs currently kept.
+    pub entries: usize,
+
+    /// The number of lookups which found a cached dependency graph.
+    pub hits: u64,
+
+    /// The number of lookups which required computing a dependency graph.
+    pub misses: u64,
+}
@@ -0,0 +1,149 @@
+use super::schema::{Crate, DependencyKind};
+use ahash::AHashMap;
+
+/// The probability of following a dependency rather than jumping to a random crate.
+const DAMPING_FACTOR: f64 = 0.85;
+
+/// The max number of `PageRank` iterations to perform.
+const MAX_ITERATIONS: usize = 100;
+
+/// The total change in scores below which `PageRank` is considered converged.
+const CONVERGENCE_THRESHOLD: f64 = 1e-9;
+
+/// Computes and assigns the `PageRank` and transitive dependent count of each crate.
+///
+/// Only normal and build dependencies are followed, as dev dependencies do not propagate to dependents.
+///
+/// # Arguments
+/// * `crates` - the `AHashMap` containing the crate values.
+pub fn assign_centrality(crates: &mut AHashMap<String, Crate>) {
+    let mut crate_ids: Vec<String> = crates.keys().cloned().collect();
+    crate_ids.sort_unstable();
+
+    let crate_indices: AHashMap<&String, usize> = crate_ids
+        .iter()
+        .enumerate()
+        .map(|(index, crate_id)| (crate_id, index))
+        .collect();
+
+    // the indices of the crates each crate depends on
+    let dependencies: Vec<Vec<usize>> = crate_ids
+        .iter()
+        .map(|crate_id| {
+            let mut crate_dependencies: Vec<usize> = crates[crate_id]
+                .dependencies
+                .iter()
+                .filter(|dependency| dependency.kind != DependencyKind::Dev)
+                .filter_map(|dependency| crate_indices.get(&dependency.to).copied())
+                .filter(|&index| crate_id != &crate_ids[index])
+                .collect();
+
+            crate_dependencies.sort_unstable();
+            crate_dependencies.dedup();
+            crate_dependencies
+        })
+        .collect();
+
+    // the indices of the crates depending on each crate
+    let mut dependents: Vec<Vec<usize>> = vec![vec![]; crate_ids.len()];
+    for (index, crate_dependencies) in dependencies.iter().enumerate() {
+        for &dependency in crate_dependencies {
+            dependents[dependency].push(index);
+        }
+    }
+
+    let pageranks = get_pageranks(&dependencies);
+    let transitive_dependents = get_transitive_dependent_counts(&dependents);
+
+    for (index, crate_id) in crate_ids.iter().enumerate() {
+        let crate_val = crates.get_mut(crate_id).unwrap();
+        crate_val.pagerank = pageranks[index];
+        crate_val.transitive_dependents = transitive_dependents[index];
+    }
+}
+
+/// Returns the `PageRank` of each crate, scaled so that the average crate scores `1`.
+///
+/// Importance flows from each crate to its dependencies, and crates without dependencies spread theirs evenly.
+///
+/// # Arguments
+/// * `dependencies` - the indices of the crates each crate depends on.
+#[allow(clippy::cast_precision_loss)]
+fn get_pageranks(dependencies: &[Vec<usize>]) -> Vec<f64> {
+    let crate_count = dependencies.len();
+    if crate_count == 0 {
+        return vec![];
+    }
+
+    let crate_count_f64 = crate_count as f64;
+    let mut pageranks: Vec<f64> = vec![1.0 / crate_count_f64; crate_count];
+
+    for _ in 0..MAX_ITERATIONS {
+        let dangling_rank: f64 = dependencies
+            .iter()
+            .zip(&pageranks)
+            .filter(|(crate_dependencies, _)| crate_dependencies.is_empty())
+            .map(|(_, pagerank)| pagerank)
+            .sum();
+
+        let base_rank =
+            DAMPING_FACTOR.mul_add(dangling_rank, 1.0 - DAMPING_FACTOR) / crate_count_f64;
+        let mut next_pageranks: Vec<f64> = vec![base_rank; crate_count];
+
+        for (crate_dependencies, pagerank) in dependencies.iter().zip(&pageranks) {
+            if !crate_dependencies.is_empty() {
+                let shared_rank = DAMPING_FACTOR * pagerank / crate_dependencies.len() as f64;
+                for &dependency in crate_dependencies {
+                    next_pageranks[dependency] += shared_rank;
+                }
+            }
+        }
+
+        let change: f64 = next_pageranks
+            .iter()
+            .zip(&pageranks)
+            .map(|(next_pagerank, pagerank)| (next_pagerank - pagerank).abs())
+            .sum();
+
+        pageranks = next_pageranks;
+
+        if change < CONVERGENCE_THRESHOLD {
+            break;
+        }
+    }
+
+    pageranks
+        .into_iter()
+        .map(|pagerank| pagerank * crate_count_f64)
+        .collect()
+}
+
+/// Returns the number of crates depending on each crate, directly or transitively.
+///
+/// # Arguments
+/// * `dependents` - the indices of the crates depending on each crate.
+fn get_transitive_dependent_counts(dependents: &[Vec<usize>]) -> Vec<usize> {
+    // the last search each crate was visited in, to avoid clearing between searches
+    let mut visited_in: Vec<usize> = vec![usize::MAX; dependents.len()];
+    let mut crate_stack: Vec<usize> = vec![];
+
+    (0..dependents.len())
+        .map(|root| {
+            let mut count = 0;
+            visited_in[root] = root;
+            crate_stack.push(root);
+
+            while let Some(index) = crate_stack.pop() {
+                for &dependent in &dependents[index] {
+                    if visited_in[dependent] != root {
+                        visited_in[dependent] = root;
+                        count += 1;
+                        crate_stack.push(dependent);
+                    }
+                }
+            }
+
+            count
+        })
+        .collect()
+}
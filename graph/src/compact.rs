@@ -0,0 +1,882 @@
+use super::{
+    schema::{Crate, Dependency},
+    DependencyKind,
+};
+use ahash::{AHashMap, RandomState};
+use cargo_platform::Platform;
+use std::{
+    borrow::Borrow,
+    collections::BTreeMap,
+    mem,
+    ops::{Index, Range},
+    ptr, slice,
+    str::FromStr,
+};
+
+/// The id of the `default` feature, which is always interned first.
+pub const DEFAULT_FEATURE: u32 = 0;
+
+/// The marker of an empty slot in the id index of a `CrateMap`.
+const EMPTY_SLOT: u32 = u32::MAX;
+
+/// The registry's crates and dependency graph, keyed by integer ids rather than names.
+///
+/// Crate ids follow the alphabetical order of crate names, so ordering crates by id matches ordering them by name.
+/// The dependency declarations and features of every crate are stored contiguously in shared arrays, indexed by
+/// per-crate offsets.
+///
+/// This is the only copy of the registry's crates and their dependency declarations: each crate name is stored once,
+/// in its `Crate`, and the `Dependency` lists used while loading are dropped once compacted. Traversing dependency
+/// graphs does not hash or clone crate names, scan dependency lists or re-parse targets.
+pub struct CompactGraph {
+    /// The crates, by id.
+    crates: CrateMap,
+
+    /// The offsets of each crate's dependency declarations in `dependencies`, followed by the total count.
+    dependency_offsets: Vec<u32>,
+
+    /// The dependency declarations of every crate, in the order of each `Crate`'s `dependencies`.
+    dependencies: Vec<CompactDependency>,
+
+    /// The offsets of each crate's features in `features`, followed by the total count.
+    feature_offsets: Vec<u32>,
+
+    /// The features of every crate, in alphabetical order per crate.
+    features: Vec<CompactFeature>,
+
+    /// The actions taken when enabling each feature.
+    feature_actions: Vec<FeatureAction>,
+
+    /// The interned feature names.
+    feature_names: Interner,
+
+    /// The parsed platforms of dependency targets by id, or `None` if a target cannot be parsed.
+    platforms: Vec<Option<Platform>>,
+
+    /// If each crate is likely a procedural macro.
+    proc_macros: Vec<bool>,
+
+    /// The features requested by dependency declarations.
+    requested_features: Vec<u32>,
+
+    /// The interned dependency targets (triples or cfg expressions), with ids matching those of `platforms`.
+    targets: Interner,
+}
+
+impl CompactGraph {
+    /// Creates a new `CompactGraph`, taking ownership of the crates.
+    ///
+    /// The dependencies of each `Crate` are moved into the `CompactGraph`, leaving its `dependencies` empty.
+    ///
+    /// # Arguments
+    /// * `crates` - the `AHashMap` containing the crate values, with dependencies sorted by destination.
+    #[must_use]
+    pub fn new(crates: AHashMap<String, Crate>) -> Self {
+        let crate_count = crates.len();
+
+        let mut feature_names = Interner::default();
+        feature_names.intern("default");
+
+        let mut compact_graph = Self {
+            crates: CrateMap::new(crates.into_values().collect()),
+            dependency_offsets: Vec::with_capacity(crate_count + 1),
+            dependencies: vec![],
+            feature_offsets: Vec::with_capacity(crate_count + 1),
+            features: vec![],
+            feature_actions: vec![],
+            feature_names,
+            platforms: vec![],
+            proc_macros: Vec::with_capacity(crate_count),
+            requested_features: vec![],
+            targets: Interner::default(),
+        };
+
+        for crate_id in 0..to_u32(crate_count) {
+            // the declarations are only needed as names until compacted
+            let crate_dependencies =
+                mem::take(&mut compact_graph.crates.crates[crate_id as usize].dependencies);
+
+            compact_graph
+                .dependency_offsets
+                .push(to_u32(compact_graph.dependencies.len()));
+            compact_graph
+                .feature_offsets
+                .push(to_u32(compact_graph.features.len()));
+            compact_graph
+                .proc_macros
+                .push(is_proc_macro(&crate_dependencies));
+
+            compact_graph.push_dependencies(&crate_dependencies);
+            compact_graph.push_features(crate_id, &crate_dependencies);
+        }
+
+        compact_graph
+            .dependency_offsets
+            .push(to_u32(compact_graph.dependencies.len()));
+        compact_graph
+            .feature_offsets
+            .push(to_u32(compact_graph.features.len()));
+
+        compact_graph
+    }
+
+    /// Appends the dependency declarations of the next crate.
+    ///
+    /// # Arguments
+    /// * `crate_dependencies` - the dependencies of the crate.
+    fn push_dependencies(&mut self, crate_dependencies: &[Dependency]) {
+        for dependency in crate_dependencies {
+            let requested_start = to_u32(self.requested_features.len());
+            for feature_name in &dependency.features {
+                let feature_id = self.feature_names.intern(feature_name);
+                self.requested_features.push(feature_id);
+            }
+
+            let target = dependency.target.as_ref().map(|target| {
+                let platform_id = self.targets.intern(target);
+                if platform_id as usize == self.platforms.len() {
+                    self.platforms.push(Platform::from_str(target).ok());
+                }
+
+                platform_id
+            });
+
+            self.dependencies.push(CompactDependency {
+                default_features: dependency.default_features,
+                kind: dependency.kind,
+                optional: dependency.optional,
+                requested_features: Span {
+                    start: requested_start,
+                    end: to_u32(self.requested_features.len()),
+                },
+                target,
+                to: self
+                    .crates
+                    .id(&dependency.to)
+                    .unwrap_or_else(|| panic!("Crate with id {} not found", dependency.to)),
+            });
+        }
+    }
+
+    /// Appends the features of the next crate.
+    ///
+    /// # Arguments
+    /// * `crate_id` - the id of the crate exposing the features.
+    /// * `crate_dependencies` - the dependencies of the crate.
+    fn push_features(&mut self, crate_id: u32, crate_dependencies: &[Dependency]) {
+        let crate_features = &self.crates.crates[crate_id as usize].features;
+
+        for (feature_name, feature_dependencies) in crate_features {
+            let name = self.feature_names.intern(feature_name);
+            let actions_start = to_u32(self.feature_actions.len());
+
+            for feature_dependency in feature_dependencies {
+                let action = feature_action(
+                    crate_features,
+                    crate_dependencies,
+                    feature_dependency,
+                    |feature_name| self.feature_names.intern(feature_name),
+                );
+
+                self.feature_actions.push(action);
+            }
+
+            self.features.push(CompactFeature {
+                actions: Span {
+                    start: actions_start,
+                    end: to_u32(self.feature_actions.len()),
+                },
+                name,
+            });
+        }
+    }
+
+    /// Returns the number of crates.
+    #[must_use]
+    pub const fn crate_count(&self) -> usize {
+        self.crates.len()
+    }
+
+    /// Returns the id of the crate with the specified name, if found.
+    ///
+    /// # Arguments
+    /// * `crate_name` - the name of the crate.
+    #[must_use]
+    pub fn crate_id(&self, crate_name: &str) -> Option<u32> {
+        self.crates.id(crate_name)
+    }
+
+    /// Returns the name of the crate with the specified id.
+    ///
+    /// # Arguments
+    /// * `crate_id` - the id of the crate.
+    #[must_use]
+    pub fn crate_name(&self, crate_id: u32) -> &String {
+        &self.crates.crate_val(crate_id).name
+    }
+
+    /// Returns an immutable reference to the crates.
+    #[must_use]
+    pub const fn crates(&self) -> &CrateMap {
+        &self.crates
+    }
+
+    /// Returns a mutable reference to the crates.
+    pub const fn crates_mut(&mut self) -> &mut CrateMap {
+        &mut self.crates
+    }
+
+    /// Returns the dependency declarations of a crate.
+    ///
+    /// # Arguments
+    /// * `crate_id` - the id of the crate.
+    #[must_use]
+    pub fn dependencies(&self, crate_id: u32) -> &[CompactDependency] {
+        &self.dependencies[offset_range(&self.dependency_offsets, crate_id)]
+    }
+
+    /// Returns the features exposed by a crate, in alphabetical order.
+    ///
+    /// # Arguments
+    /// * `crate_id` - the id of the crate.
+    #[must_use]
+    pub fn features(&self, crate_id: u32) -> &[CompactFeature] {
+        &self.features[offset_range(&self.feature_offsets, crate_id)]
+    }
+
+    /// Returns the actions taken when enabling a feature.
+    ///
+    /// # Arguments
+    /// * `feature` - the `CompactFeature` being enabled.
+    #[must_use]
+    pub fn feature_actions(&self, feature: &CompactFeature) -> &[FeatureAction] {
+        &self.feature_actions[feature.actions.range()]
+    }
+
+    /// Returns the interned feature names.
+    #[must_use]
+    pub const fn feature_names(&self) -> &Interner {
+        &self.feature_names
+    }
+
+    /// Returns the features requested of the destination crate by a dependency declaration.
+    ///
+    /// # Arguments
+    /// * `dependency` - the `CompactDependency` requesting the features.
+    #[must_use]
+    pub fn requested_features(&self, dependency: &CompactDependency) -> &[u32] {
+        &self.requested_features[dependency.requested_features.range()]
+    }
+
+    /// Returns the dependency target (triple or cfg expression) with the specified id.
+    ///
+    /// # Arguments
+    /// * `target_id` - the id of the target.
+    #[must_use]
+    pub fn target(&self, target_id: u32) -> &String {
+        self.targets.name(target_id)
+    }
+
+    /// Returns the number of distinct dependency targets.
+    #[must_use]
+    pub const fn target_count(&self) -> usize {
+        self.targets.len()
+    }
+
+    /// Returns if a crate is likely a procedural macro.
+    ///
+    /// # Arguments
+    /// * `crate_id` - the id of the crate.
+    #[must_use]
+    pub fn is_proc_macro(&self, crate_id: u32) -> bool {
+        self.proc_macros[crate_id as usize]
+    }
+}
+
+/// The crates of the registry, ordered by name so that the index of each `Crate` is its id in the `CompactGraph`.
+///
+/// This offers the lookups of a map keyed by crate name without storing the names again: crate ids are found through
+/// an open-addressing hash table of ids, comparing names against the crates themselves.
+pub struct CrateMap {
+    /// The crates, ordered by name.
+    crates: Vec<Crate>,
+
+    /// The hasher of crate names.
+    hasher: RandomState,
+
+    /// A hash table of crate ids by name with linear probing, kept at most half full.
+    ids: Vec<u32>,
+}
+
+impl CrateMap {
+    /// Creates a new `CrateMap`.
+    ///
+    /// # Arguments
+    /// * `crates` - the crates, in any order.
+    fn new(mut crates: Vec<Crate>) -> Self {
+        crates.sort_unstable_by(|first, second| first.name.cmp(&second.name));
+
+        let mut crate_map = Self {
+            ids: vec![EMPTY_SLOT; (crates.len() * 2).next_power_of_two()],
+            crates,
+            hasher: RandomState::new(),
+        };
+
+        for crate_id in 0..to_u32(crate_map.crates.len()) {
+            let mut slot = crate_map.slot(&crate_map.crates[crate_id as usize].name);
+            while crate_map.ids[slot] != EMPTY_SLOT {
+                slot = (slot + 1) % crate_map.ids.len();
+            }
+
+            crate_map.ids[slot] = crate_id;
+        }
+
+        crate_map
+    }
+
+    /// Returns the slot of the id index at which probing for a crate name starts.
+    ///
+    /// # Arguments
+    /// * `crate_name` - the name of the crate.
+    #[allow(clippy::cast_possible_truncation)]
+    fn slot(&self, crate_name: &str) -> usize {
+        // only the lowest bits of the hash are needed to pick a slot
+        self.hasher.hash_one(crate_name) as usize % self.ids.len()
+    }
+
+    /// Returns the id of the crate with the specified name, if found.
+    ///
+    /// # Arguments
+    /// * `crate_name` - the name of the crate.
+    #[must_use]
+    pub fn id(&self, crate_name: &str) -> Option<u32> {
+        let mut slot = self.slot(crate_name);
+
+        loop {
+            match self.ids[slot] {
+                EMPTY_SLOT => return None,
+                crate_id if self.crates[crate_id as usize].name == crate_name => {
+                    return Some(crate_id)
+                }
+                _ => slot = (slot + 1) % self.ids.len(),
+            }
+        }
+    }
+
+    /// Returns the id of a `Crate` if it is stored in the map, as opposed to a crate outside the registry sharing its
+    /// name (such as one built from a manifest).
+    ///
+    /// # Arguments
+    /// * `crate_val` - the `Crate` to find.
+    #[must_use]
+    pub fn id_of(&self, crate_val: &Crate) -> Option<u32> {
+        self.id(&crate_val.name)
+            .filter(|&crate_id| ptr::eq(self.crate_val(crate_id), crate_val))
+    }
+
+    /// Returns the `Crate` with the specified id.
+    ///
+    /// # Arguments
+    /// * `crate_id` - the id of the crate.
+    #[must_use]
+    pub fn crate_val(&self, crate_id: u32) -> &Crate {
+        &self.crates[crate_id as usize]
+    }
+
+    /// Returns the `Crate` with the specified name, if found.
+    ///
+    /// # Arguments
+    /// * `crate_name` - the name of the crate.
+    #[must_use]
+    pub fn get(&self, crate_name: &str) -> Option<&Crate> {
+        self.id(crate_name).map(|crate_id| self.crate_val(crate_id))
+    }
+
+    /// Returns if a crate with the specified name is present.
+    ///
+    /// # Arguments
+    /// * `crate_name` - the name of the crate.
+    #[must_use]
+    pub fn contains_key(&self, crate_name: &str) -> bool {
+        self.id(crate_name).is_some()
+    }
+
+    /// Returns the crates whose names start with the specified prefix, in alphabetical order.
+    ///
+    /// # Arguments
+    /// * `prefix` - the prefix to match.
+    #[must_use]
+    pub fn prefixed(&self, prefix: &str) -> &[Crate] {
+        let start = self
+            .crates
+            .partition_point(|crate_val| crate_val.name.as_str() < prefix);
+        let length = self.crates[start..]
+            .iter()
+            .take_while(|crate_val| crate_val.name.starts_with(prefix))
+            .count();
+
+        &self.crates[start..start + length]
+    }
+
+    /// Returns the names of the crates, in alphabetical order.
+    pub fn keys(&self) -> impl Iterator<Item = &String> {
+        self.crates.iter().map(|crate_val| &crate_val.name)
+    }
+
+    /// Returns the crates, in alphabetical order of names.
+    pub fn values(&self) -> slice::Iter<'_, Crate> {
+        self.crates.iter()
+    }
+
+    /// Returns the crates as mutable references, in alphabetical order of names.
+    pub fn values_mut(&mut self) -> slice::IterMut<'_, Crate> {
+        self.crates.iter_mut()
+    }
+
+    /// Returns the number of crates.
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.crates.len()
+    }
+
+    /// Returns if there are no crates.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.crates.is_empty()
+    }
+}
+
+impl<Q: Borrow<str> + ?Sized> Index<&Q> for CrateMap {
+    type Output = Crate;
+
+    /// Returns the `Crate` with the specified name.
+    ///
+    /// Panics if no crate matches the name.
+    fn index(&self, crate_name: &Q) -> &Crate {
+        let crate_name = crate_name.borrow();
+
+        self.get(crate_name)
+            .unwrap_or_else(|| panic!("Crate with id {crate_name} not found"))
+    }
+}
+
+/// A crate outside the registry (such as one built from a manifest) compacted against a `CompactGraph`.
+///
+/// The crate is assigned the id past those of registry crates, and the targets of its dependencies are assigned
+/// platform ids past those of the registry. Its features use the ids of the dependency graph being constructed, so
+/// features unknown to the registry are supported.
+pub struct CompactRoot {
+    /// The dependency declarations of the crate.
+    dependencies: Vec<CompactDependency>,
+
+    /// The features of the crate, in alphabetical order.
+    features: Vec<CompactFeature>,
+
+    /// The actions taken when enabling each feature.
+    feature_actions: Vec<FeatureAction>,
+
+    /// The parsed platforms of the crate's dependency targets, by id past those of the registry.
+    platforms: Vec<Option<Platform>>,
+
+    /// The features requested by dependency declarations.
+    requested_features: Vec<u32>,
+}
+
+impl CompactRoot {
+    /// Creates a new `CompactRoot`.
+    ///
+    /// Every dependency of the crate must be present in the registry.
+    ///
+    /// # Arguments
+    /// * `crate_val` - the `Crate`, with dependencies sorted by destination.
+    /// * `graph` - the `CompactGraph` of the registry.
+    /// * `feature_id` - a function returning the id of a feature name.
+    #[must_use]
+    pub fn new(
+        crate_val: &Crate,
+        graph: &CompactGraph,
+        mut feature_id: impl FnMut(&str) -> u32,
+    ) -> Self {
+        let mut root = Self {
+            dependencies: Vec::with_capacity(crate_val.dependencies.len()),
+            features: Vec::with_capacity(crate_val.features.len()),
+            feature_actions: vec![],
+            platforms: vec![],
+            requested_features: vec![],
+        };
+
+        let mut platform_ids: AHashMap<&str, u32> = AHashMap::new();
+
+        for dependency in &crate_val.dependencies {
+            let requested_start = to_u32(root.requested_features.len());
+            for feature_name in &dependency.features {
+                root.requested_features.push(feature_id(feature_name));
+            }
+
+            let target = dependency.target.as_ref().map(|target| {
+                *platform_ids.entry(target).or_insert_with(|| {
+                    root.platforms.push(Platform::from_str(target).ok());
+                    to_u32(graph.platforms.len() + root.platforms.len() - 1)
+                })
+            });
+
+            root.dependencies.push(CompactDependency {
+                default_features: dependency.default_features,
+                kind: dependency.kind,
+                optional: dependency.optional,
+                requested_features: Span {
+                    start: requested_start,
+                    end: to_u32(root.requested_features.len()),
+                },
+                target,
+                to: graph
+                    .crate_id(&dependency.to)
+                    .unwrap_or_else(|| panic!("Crate with id {} not found", dependency.to)),
+            });
+        }
+
+        for (feature_name, feature_dependencies) in &crate_val.features {
+            let name = feature_id(feature_name);
+            let actions_start = to_u32(root.feature_actions.len());
+
+            for feature_dependency in feature_dependencies {
+                let action = feature_action(
+                    &crate_val.features,
+                    &crate_val.dependencies,
+                    feature_dependency,
+                    &mut feature_id,
+                );
+                root.feature_actions.push(action);
+            }
+
+            root.features.push(CompactFeature {
+                actions: Span {
+                    start: actions_start,
+                    end: to_u32(root.feature_actions.len()),
+                },
+                name,
+            });
+        }
+
+        root
+    }
+}
+
+/// A `CompactGraph`, optionally extended with a `CompactRoot` assigned the id past those of registry crates.
+#[derive(Clone, Copy)]
+pub struct CompactView<'a> {
+    /// The `CompactGraph` of the registry.
+    graph: &'a CompactGraph,
+
+    /// The crate outside the registry, if any.
+    root: Option<&'a CompactRoot>,
+}
+
+impl<'a> CompactView<'a> {
+    /// Creates a new `CompactView`.
+    ///
+    /// # Arguments
+    /// * `graph` - the `CompactGraph` of the registry.
+    /// * `root` - the crate outside the registry, if any.
+    #[must_use]
+    pub const fn new(graph: &'a CompactGraph, root: Option<&'a CompactRoot>) -> Self {
+        Self { graph, root }
+    }
+
+    /// Returns the `CompactRoot` if it has the specified id.
+    ///
+    /// # Arguments
+    /// * `crate_id` - the id of the crate.
+    fn root(self, crate_id: u32) -> Option<&'a CompactRoot> {
+        self.root
+            .filter(|_| crate_id as usize == self.graph.crate_count())
+    }
+
+    /// Returns the dependency declarations of a crate.
+    ///
+    /// # Arguments
+    /// * `crate_id` - the id of the crate.
+    #[must_use]
+    pub fn dependencies(self, crate_id: u32) -> &'a [CompactDependency] {
+        self.root(crate_id).map_or_else(
+            || self.graph.dependencies(crate_id),
+            |root| &root.dependencies,
+        )
+    }
+
+    /// Returns the features exposed by a crate, in alphabetical order.
+    ///
+    /// # Arguments
+    /// * `crate_id` - the id of the crate.
+    #[must_use]
+    pub fn features(self, crate_id: u32) -> &'a [CompactFeature] {
+        self.root(crate_id)
+            .map_or_else(|| self.graph.features(crate_id), |root| &root.features)
+    }
+
+    /// Returns the actions taken when enabling a feature.
+    ///
+    /// # Arguments
+    /// * `crate_id` - the id of the crate exposing the feature.
+    /// * `feature` - the `CompactFeature` being enabled.
+    #[must_use]
+    pub fn feature_actions(self, crate_id: u32, feature: &CompactFeature) -> &'a [FeatureAction] {
+        self.root(crate_id).map_or_else(
+            || self.graph.feature_actions(feature),
+            |root| &root.feature_actions[feature.actions.range()],
+        )
+    }
+
+    /// Returns the features requested of the destination crate by a dependency declaration.
+    ///
+    /// # Arguments
+    /// * `crate_id` - the id of the crate declaring the dependency.
+    /// * `dependency` - the `CompactDependency` requesting the features.
+    #[must_use]
+    pub fn requested_features(self, crate_id: u32, dependency: &CompactDependency) -> &'a [u32] {
+        self.root(crate_id).map_or_else(
+            || self.graph.requested_features(dependency),
+            |root| &root.requested_features[dependency.requested_features.range()],
+        )
+    }
+
+    /// Returns the number of platforms, including those of the `CompactRoot`.
+    #[must_use]
+    pub fn platform_count(self) -> usize {
+        self.graph.platforms.len() + self.root.map_or(0, |root| root.platforms.len())
+    }
+
+    /// Returns the parsed platform of a dependency target, or `None` if the target cannot be parsed.
+    ///
+    /// # Arguments
+    /// * `platform_id` - the id of the dependency target.
+    #[must_use]
+    pub fn platform(self, platform_id: u32) -> Option<&'a Platform> {
+        let platform_index = platform_id as usize;
+
+        if platform_index < self.graph.platforms.len() {
+            self.graph.platforms[platform_index].as_ref()
+        } else {
+            self.root?.platforms[platform_index - self.graph.platforms.len()].as_ref()
+        }
+    }
+
+    /// Returns if a crate in the registry is likely a procedural macro.
+    ///
+    /// # Arguments
+    /// * `crate_id` - the id of the crate.
+    #[must_use]
+    pub fn is_proc_macro(self, crate_id: u32) -> bool {
+        self.graph.is_proc_macro(crate_id)
+    }
+}
+
+/// A dependency declaration in a `CompactGraph`.
+pub struct CompactDependency {
+    /// If the dependency relies on default features.
+    pub default_features: bool,
+
+    /// The kind of the dependency.
+    pub kind: DependencyKind,
+
+    /// If the dependency is optional.
+    pub optional: bool,
+
+    /// The range of the features requested through the dependency.
+    requested_features: Span,
+
+    /// The id of the specific target of the dependency, if one is present.
+    pub target: Option<u32>,
+
+    /// The id of the destination crate.
+    pub to: u32,
+}
+
+/// A feature exposed by a crate in a `CompactGraph`.
+pub struct CompactFeature {
+    /// The range of the actions taken when enabling the feature.
+    actions: Span,
+
+    /// The id of the feature name.
+    pub name: u32,
+}
+
+/// An action taken when enabling a feature, corresponding to an entry of the feature's list.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum FeatureAction {
+    /// Enables another feature of the same crate.
+    Feature(u32),
+
+    /// Enables the dependency declarations in the range, activating them if optional.
+    Dependency {
+        /// The range of the declarations among the crate's dependencies.
+        declarations: Span,
+    },
+
+    /// Enables a feature of the dependency declarations in the range, activating them if optional.
+    DependencyFeature {
+        /// The range of the declarations among the crate's dependencies.
+        declarations: Span,
+
+        /// The id of the feature enabled.
+        feature: u32,
+    },
+}
+
+/// A range of indices into one of the arrays of a `CompactGraph`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Span {
+    /// The first index in the range.
+    start: u32,
+
+    /// The index past the end of the range.
+    end: u32,
+}
+
+impl Span {
+    /// Returns the indices in the range.
+    #[must_use]
+    pub const fn range(self) -> Range<usize> {
+        self.start as usize..self.end as usize
+    }
+}
+
+/// A mapping of strings to dense `u32` ids, assigned in insertion order.
+#[derive(Default)]
+pub struct Interner {
+    /// A mapping of strings to ids.
+    ids: AHashMap<String, u32>,
+
+    /// The strings by id.
+    names: Vec<String>,
+}
+
+impl Interner {
+    /// Returns the id of a string, interning it if not already present.
+    ///
+    /// # Arguments
+    /// * `name` - the string to intern.
+    fn intern(&mut self, name: &str) -> u32 {
+        if let Some(&id) = self.ids.get(name) {
+            return id;
+        }
+
+        let id = to_u32(self.names.len());
+        self.ids.insert(String::from(name), id);
+        self.names.push(String::from(name));
+        id
+    }
+
+    /// Returns the id of a string, if interned.
+    ///
+    /// # Arguments
+    /// * `name` - the string to look up.
+    #[must_use]
+    pub fn get(&self, name: &str) -> Option<u32> {
+        self.ids.get(name).copied()
+    }
+
+    /// Returns the string with the specified id.
+    ///
+    /// # Arguments
+    /// * `id` - the id of the string.
+    #[must_use]
+    pub fn name(&self, id: u32) -> &String {
+        &self.names[id as usize]
+    }
+
+    /// Returns the number of interned strings.
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.names.len()
+    }
+}
+
+/// Returns the action taken by an entry of a feature's list.
+///
+/// # Arguments
+/// * `crate_features` - the features of the crate exposing the feature.
+/// * `crate_dependencies` - the dependencies of the crate exposing the feature, sorted by destination.
+/// * `feature_dependency` - the entry of the feature's list.
+/// * `feature_id` - a function returning the id of a feature name.
+fn feature_action(
+    crate_features: &BTreeMap<String, Vec<String>>,
+    crate_dependencies: &[Dependency],
+    feature_dependency: &str,
+    mut feature_id: impl FnMut(&str) -> u32,
+) -> FeatureAction {
+    if crate_features.contains_key(feature_dependency) {
+        FeatureAction::Feature(feature_id(feature_dependency))
+    } else if let Some((dependency_name, dependency_feature)) = feature_dependency.split_once('/') {
+        FeatureAction::DependencyFeature {
+            declarations: declaration_span(crate_dependencies, dependency_name),
+            feature: feature_id(dependency_feature),
+        }
+    } else {
+        FeatureAction::Dependency {
+            declarations: declaration_span(
+                crate_dependencies,
+                feature_dependency
+                    .strip_prefix("dep:")
+                    .unwrap_or(feature_dependency),
+            ),
+        }
+    }
+}
+
+/// Determines if a crate is likely a procedural macro.
+///
+/// The database dump does not record crate types, so crates with mandatory dependencies on both `proc-macro2` and
+/// `quote` are assumed to be procedural macros. This misclassifies libraries generating code at build time or in a
+/// separate binary (e.g. `prost-build`, `bindgen`) as procedural macros, and misses procedural macros using neither
+/// crate or only optionally. It is only used when explicitly requested through
+/// `DependencyGraphOptions::infer_proc_macros`.
+///
+/// # Arguments
+/// * `crate_dependencies` - the dependencies of the crate.
+fn is_proc_macro(crate_dependencies: &[Dependency]) -> bool {
+    let depends_on = |name: &str| {
+        crate_dependencies.iter().any(|dependency| {
+            dependency.to == name
+                && dependency.kind == DependencyKind::Normal
+                && !dependency.optional
+        })
+    };
+
+    depends_on("proc-macro2") && depends_on("quote")
+}
+
+/// Returns the range of a crate's dependency declarations on the specified crate.
+///
+/// This relies on the dependencies of each crate being sorted by destination when loaded.
+///
+/// # Arguments
+/// * `dependencies` - the dependencies of the crate.
+/// * `dependency_name` - the name of the destination crate.
+fn declaration_span(dependencies: &[Dependency], dependency_name: &str) -> Span {
+    Span {
+        start: to_u32(
+            dependencies.partition_point(|dependency| dependency.to.as_str() < dependency_name),
+        ),
+        end: to_u32(
+            dependencies.partition_point(|dependency| dependency.to.as_str() <= dependency_name),
+        ),
+    }
+}
+
+/// Returns the range of a crate's entries in a CSR-style array.
+///
+/// # Arguments
+/// * `offsets` - the offsets of each crate's entries, followed by the total count.
+/// * `crate_id` - the id of the crate.
+fn offset_range(offsets: &[u32], crate_id: u32) -> Range<usize> {
+    offsets[crate_id as usize] as usize..offsets[crate_id as usize + 1] as usize
+}
+
+/// Converts an index into a `u32` id.
+///
+/// # Arguments
+/// * `index` - the index to convert.
+fn to_u32(index: usize) -> u32 {
+    u32::try_from(index).unwrap_or_else(|_| panic!("{index} does not fit in a u32 id"))
+}
@@ -0,0 +1,132 @@
+use super::{load, DEFAULT_CACHE_CAPACITY};
+use cargo_platform::Cfg;
+use std::{path::PathBuf, str::FromStr};
+
+/// The URL of the official crates.io database dump.
+pub const DEFAULT_DUMP_URL: &str = "https://cloudfront-static.crates.io/db-dump.tar.gz";
+
+/// The target triple used when none is specified.
+pub const DEFAULT_TARGET: &str = "x86_64-unknown-linux-gnu";
+
+/// The additional cfg name enabled when none is specified.
+pub const DEFAULT_CFG_NAME: &str = "unix";
+
+/// The location of a crates.io database dump to load a `Graph` from.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum DataSource {
+    /// A tarballed database dump to download, by URL.
+    Remote(String),
+
+    /// A tarballed database dump on the local filesystem.
+    Dump(PathBuf),
+
+    /// The `data` directory of an unpacked database dump on the local filesystem.
+    Snapshot(PathBuf),
+}
+
+impl Default for DataSource {
+    fn default() -> Self {
+        Self::Remote(String::from(DEFAULT_DUMP_URL))
+    }
+}
+
+/// Options controlling how a `Graph` is loaded and how dependency graphs are resolved by default.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GraphConfig {
+    /// The location of the database dump to load.
+    pub source: DataSource,
+
+    /// The target triple used when a query does not specify one.
+    pub default_target: String,
+
+    /// The additional cfg name enabled when a query does not specify one.
+    pub default_cfg_name: String,
+
+    /// The max number of serialized dependency graphs kept in the cache, where `0` disables caching.
+    pub cache_capacity: usize,
+}
+
+impl GraphConfig {
+    /// Checks that the `GraphConfig` can be used to load a `Graph`.
+    ///
+    /// # Errors
+    /// * Returns a description of each problem found, such as a missing dump file or an unknown default target.
+    pub fn validate(&self) -> Result<(), Vec<String>> {
+        let mut problems = vec![];
+
+        match &self.source {
+            DataSource::Remote(url) => {
+                if !url.starts_with("https://") && !url.starts_with("http://") {
+                    problems.push(format!(
+                        "data source URL `{url}` must start with `http://` or `https://`"
+                    ));
+                }
+            }
+
+            DataSource::Dump(dump_path) => {
+                if !dump_path.is_file() {
+                    problems.push(format!(
+                        "database dump `{}` is not a file",
+                        dump_path.display()
+                    ));
+                }
+            }
+
+            DataSource::Snapshot(data_path) => {
+                if data_path.is_dir() {
+                    let missing_collections = load::get_missing_collections(data_path);
+
+                    if !missing_collections.is_empty() {
+                        problems.push(format!(
+                            "snapshot directory `{}` is missing {}",
+                            data_path.display(),
+                            missing_collections
+                                .iter()
+                                .map(|collection_name| format!("`{collection_name}.csv`"))
+                                .collect::<Vec<String>>()
+                                .join(", ")
+                        ));
+                    }
+                } else {
+                    problems.push(format!(
+                        "snapshot directory `{}` is not a directory",
+                        data_path.display()
+                    ));
+                }
+            }
+        }
+
+        match load::get_targets(env!("VAULT_TARGETS_PATH")) {
+            Ok(targets) if !targets.contains_key(&self.default_target) => problems.push(format!(
+                "default target `{}` is not a known target triple",
+                self.default_target
+            )),
+            Ok(_) => {}
+            Err(err) => problems.push(err.to_string()),
+        }
+
+        if !matches!(Cfg::from_str(&self.default_cfg_name), Ok(Cfg::Name(_))) {
+            problems.push(format!(
+                "default cfg name `{}` is not a valid cfg name",
+                self.default_cfg_name
+            ));
+        }
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(problems)
+        }
+    }
+}
+
+impl Default for GraphConfig {
+    fn default() -> Self {
+        Self {
+            source: DataSource::default(),
+            default_target: String::from(DEFAULT_TARGET),
+            default_cfg_name: String::from(DEFAULT_CFG_NAME),
+            cache_capacity: DEFAULT_CACHE_CAPACITY,
+        }
+    }
+}
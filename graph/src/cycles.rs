@@ -0,0 +1,172 @@
+use super::{DependencyGraph, DependencyKind, Graph};
+use ahash::AHashMap;
+use serde::Serialize;
+use std::cmp::Reverse;
+
+impl Graph {
+    /// Returns the strongly connected components of the registry containing a cycle, ranked by size.
+    ///
+    /// Every declared dependency of the given kinds counts as an edge, irrespective of features and targets.
+    ///
+    /// # Arguments
+    /// * `kinds` - the `DependencyKind`s of the dependencies to follow.
+    #[must_use]
+    pub fn get_cycles(&self, kinds: &[DependencyKind]) -> Vec<CrateCycle<'_>> {
+        let crate_ids: Vec<&String> = (0..self.compact.crate_count())
+            .map(|index| self.compact.crate_name(u32::try_from(index).unwrap()))
+            .collect();
+
+        let successors: Vec<Vec<usize>> = (0..crate_ids.len())
+            .map(|index| {
+                let mut crate_successors: Vec<usize> = self
+                    .compact
+                    .dependencies(u32::try_from(index).unwrap())
+                    .iter()
+                    .filter(|dependency| kinds.contains(&dependency.kind))
+                    .map(|dependency| dependency.to as usize)
+                    .collect();
+
+                crate_successors.sort_unstable();
+                crate_successors.dedup();
+                crate_successors
+            })
+            .collect();
+
+        get_cycles(&crate_ids, &successors)
+    }
+}
+
+impl<'a> DependencyGraph<'a> {
+    /// Returns the strongly connected components of the dependency graph containing a cycle, ranked by size.
+    #[must_use]
+    pub fn cycles(&self) -> Vec<CrateCycle<'a>> {
+        let crate_ids: Vec<&'a String> = self
+            .crates
+            .iter()
+            .map(|crate_distance| crate_distance.name)
+            .collect();
+        let crate_indices: AHashMap<&String, usize> = crate_ids
+            .iter()
+            .enumerate()
+            .map(|(index, &crate_id)| (crate_id, index))
+            .collect();
+
+        let mut successors: Vec<Vec<usize>> = vec![vec![]; crate_ids.len()];
+        for dependency in &self.dependencies {
+            let to = crate_indices[dependency.to];
+            let from_successors = &mut successors[crate_indices[dependency.from]];
+
+            if !from_successors.contains(&to) {
+                from_successors.push(to);
+            }
+        }
+
+        get_cycles(&crate_ids, &successors)
+    }
+}
+
+/// Returns the strongly connected components containing a cycle, ranked by size and then alphabetically.
+///
+/// This is an iterative form of Tarjan's algorithm, so deep dependency chains cannot overflow the stack.
+///
+/// # Arguments
+/// * `crate_ids` - the names of the crates by index.
+/// * `successors` - the indices of the dependencies of each crate.
+fn get_cycles<'a>(crate_ids: &[&'a String], successors: &[Vec<usize>]) -> Vec<CrateCycle<'a>> {
+    let crate_count = successors.len();
+
+    // the order in which each crate was discovered, and the lowest discovery order reachable from it
+    let mut discovery: Vec<Option<usize>> = vec![None; crate_count];
+    let mut low_links: Vec<usize> = vec![0; crate_count];
+    let mut on_stack: Vec<bool> = vec![false; crate_count];
+    let mut component_stack: Vec<usize> = vec![];
+    let mut discovered = 0;
+
+    let mut cycles: Vec<CrateCycle> = vec![];
+
+    for root in 0..crate_count {
+        if discovery[root].is_some() {
+            continue;
+        }
+
+        // a stack of crates being visited along with the position of their next dependency to visit
+        let mut call_stack: Vec<(usize, usize)> = vec![(root, 0)];
+        discovery[root] = Some(discovered);
+        low_links[root] = discovered;
+        discovered += 1;
+        component_stack.push(root);
+        on_stack[root] = true;
+
+        while let Some((index, position)) = call_stack.pop() {
+            if let Some(&next) = successors[index].get(position) {
+                call_stack.push((index, position + 1));
+
+                match discovery[next] {
+                    None => {
+                        discovery[next] = Some(discovered);
+                        low_links[next] = discovered;
+                        discovered += 1;
+                        component_stack.push(next);
+                        on_stack[next] = true;
+                        call_stack.push((next, 0));
+                    }
+                    Some(next_discovery) if on_stack[next] => {
+                        low_links[index] = low_links[index].min(next_discovery);
+                    }
+                    Some(_) => {}
+                }
+
+                continue;
+            }
+
+            // every dependency has been visited, so propagate the low link to the caller
+            if let Some(&(caller, _)) = call_stack.last() {
+                low_links[caller] = low_links[caller].min(low_links[index]);
+            }
+
+            if Some(low_links[index]) == discovery[index] {
+                let mut component: Vec<usize> = vec![];
+                while let Some(member) = component_stack.pop() {
+                    on_stack[member] = false;
+                    component.push(member);
+
+                    if member == index {
+                        break;
+                    }
+                }
+
+                // a single crate only forms a cycle if it depends on itself
+                if component.len() > 1 || successors[index].contains(&index) {
+                    let mut crates: Vec<&String> = component
+                        .into_iter()
+                        .map(|member| crate_ids[member])
+                        .collect();
+                    crates.sort();
+
+                    cycles.push(CrateCycle {
+                        crate_count: crates.len(),
+                        crates,
+                    });
+                }
+            }
+        }
+    }
+
+    cycles.sort_by(|first, second| {
+        Reverse(first.crate_count)
+            .cmp(&Reverse(second.crate_count))
+            .then_with(|| first.crates.cmp(&second.crates))
+    });
+
+    cycles
+}
+
+/// A strongly connected component of crates containing at least one cycle.
+#[derive(Serialize)]
+pub struct CrateCycle<'a> {
+    /// The number of crates in the component.
+    pub crate_count: usize,
+
+    /// The names of the crates in the component, in alphabetical order.
+    pub crates: Vec<&'a String>,
+}
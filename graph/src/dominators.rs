@@ -0,0 +1,155 @@
+use super::DependencyGraph;
+use ahash::AHashMap;
+use serde::Serialize;
+
+impl<'a> DependencyGraph<'a> {
+    /// Returns the dominator tree of the dependency graph, rooted at its root crate.
+    ///
+    /// A crate dominates another if every path from the root crate to the other crate goes through it, so dropping
+    /// it would drop every crate it dominates. Crates are listed in the same order as in the dependency graph.
+    #[must_use]
+    pub fn dominator_tree(&self) -> Vec<CrateDominator<'a>> {
+        let crate_indices: AHashMap<&String, usize> = self
+            .crates
+            .iter()
+            .enumerate()
+            .map(|(index, crate_distance)| (crate_distance.name, index))
+            .collect();
+
+        let mut successors: Vec<Vec<usize>> = vec![vec![]; self.crates.len()];
+        let mut predecessors: Vec<Vec<usize>> = vec![vec![]; self.crates.len()];
+        for dependency in &self.dependencies {
+            let from = crate_indices[dependency.from];
+            let to = crate_indices[dependency.to];
+
+            if !successors[from].contains(&to) {
+                successors[from].push(to);
+                predecessors[to].push(from);
+            }
+        }
+
+        let postorder = get_postorder(&successors);
+        let mut postorder_numbers: Vec<usize> = vec![0; self.crates.len()];
+        for (number, &index) in postorder.iter().enumerate() {
+            postorder_numbers[index] = number;
+        }
+
+        // iterate in reverse postorder until the immediate dominators settle
+        let mut immediate_dominators: Vec<Option<usize>> = vec![None; self.crates.len()];
+        if !self.crates.is_empty() {
+            immediate_dominators[0] = Some(0);
+        }
+
+        let mut changed = true;
+        while changed {
+            changed = false;
+
+            for &index in postorder.iter().rev().skip(1) {
+                let new_dominator = predecessors[index]
+                    .iter()
+                    .copied()
+                    .filter(|&predecessor| immediate_dominators[predecessor].is_some())
+                    .reduce(|first, second| {
+                        intersect(first, second, &immediate_dominators, &postorder_numbers)
+                    });
+
+                if new_dominator.is_some() && immediate_dominators[index] != new_dominator {
+                    immediate_dominators[index] = new_dominator;
+                    changed = true;
+                }
+            }
+        }
+
+        // accumulate the size of each subtree in postorder, so children are counted before their dominators
+        let mut dominated_counts: Vec<usize> = vec![0; self.crates.len()];
+        for &index in &postorder {
+            if let Some(dominator) =
+                immediate_dominators[index].filter(|&dominator| dominator != index)
+            {
+                dominated_counts[dominator] += dominated_counts[index] + 1;
+            }
+        }
+
+        self.crates
+            .iter()
+            .enumerate()
+            .map(|(index, crate_distance)| CrateDominator {
+                dominated_count: dominated_counts[index],
+                immediate_dominator: immediate_dominators[index]
+                    .filter(|&dominator| dominator != index)
+                    .map(|dominator| self.crates[dominator].name),
+                name: crate_distance.name,
+            })
+            .collect()
+    }
+}
+
+/// Returns the crates reachable from the root crate in postorder of a depth-first search.
+///
+/// # Arguments
+/// * `successors` - the indices of the dependencies of each crate, with the root crate at index `0`.
+fn get_postorder(successors: &[Vec<usize>]) -> Vec<usize> {
+    let mut postorder: Vec<usize> = vec![];
+    if successors.is_empty() {
+        return postorder;
+    }
+
+    let mut visited: Vec<bool> = vec![false; successors.len()];
+    // a stack of crates being visited along with the position of their next dependency to visit
+    let mut stack: Vec<(usize, usize)> = vec![(0, 0)];
+    visited[0] = true;
+
+    while let Some((index, position)) = stack.pop() {
+        if let Some(&next) = successors[index].get(position) {
+            stack.push((index, position + 1));
+
+            if !visited[next] {
+                visited[next] = true;
+                stack.push((next, 0));
+            }
+        } else {
+            postorder.push(index);
+        }
+    }
+
+    postorder
+}
+
+/// Returns the nearest common dominator of two crates.
+///
+/// # Arguments
+/// * `first` - the index of the first crate.
+/// * `second` - the index of the second crate.
+/// * `immediate_dominators` - the immediate dominators found so far.
+/// * `postorder_numbers` - the position of each crate in postorder.
+fn intersect(
+    mut first: usize,
+    mut second: usize,
+    immediate_dominators: &[Option<usize>],
+    postorder_numbers: &[usize],
+) -> usize {
+    while first != second {
+        while postorder_numbers[first] < postorder_numbers[second] {
+            first = immediate_dominators[first].unwrap();
+        }
+
+        while postorder_numbers[second] < postorder_numbers[first] {
+            second = immediate_dominators[second].unwrap();
+        }
+    }
+
+    first
+}
+
+/// A crate in the dominator tree of a `DependencyGraph`.
+#[derive(Serialize)]
+pub struct CrateDominator<'a> {
+    /// The number of crates dominated by the crate, excluding itself.
+    pub dominated_count: usize,
+
+    /// The closest crate which every path from the root crate goes through, or `None` for the root crate.
+    pub immediate_dominator: Option<&'a String>,
+
+    /// The name of the crate.
+    pub name: &'a String,
+}
@@ -0,0 +1,486 @@
+use super::{DependencyEdge, DependencyGraph, DependencyKind};
+use ahash::AHashMap;
+use std::{
+    collections::BTreeMap,
+    error::Error,
+    fmt::{self, Display, Formatter, Write},
+    str::FromStr,
+};
+
+/// A format a `DependencyGraph` can be exported to.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum GraphFormat {
+    /// The JSON shape of `DependencyGraph`.
+    Json,
+
+    /// A Graphviz DOT digraph, with crates ranked by distance from the root crate.
+    Dot,
+
+    /// A `GraphML` document.
+    GraphMl,
+
+    /// A Mermaid flowchart.
+    Mermaid,
+
+    /// A CSV node list, with Gephi's `Id` and `Label` columns.
+    CsvNodes,
+
+    /// A CSV edge list, with Gephi's `Source`, `Target` and `Type` columns.
+    CsvEdges,
+}
+
+/// An error returned when parsing an unknown `GraphFormat` name.
+#[derive(Debug, Eq, PartialEq)]
+pub struct UnknownGraphFormat(pub String);
+
+impl Display for UnknownGraphFormat {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "Unknown graph format: {}", self.0)
+    }
+}
+
+impl Error for UnknownGraphFormat {}
+
+impl FromStr for GraphFormat {
+    type Err = UnknownGraphFormat;
+
+    /// Parses a `GraphFormat` from its name, as used by the `format` query option.
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name {
+            "json" => Ok(Self::Json),
+            "dot" => Ok(Self::Dot),
+            "graphml" => Ok(Self::GraphMl),
+            "mermaid" => Ok(Self::Mermaid),
+            "csv_nodes" => Ok(Self::CsvNodes),
+            "csv" | "csv_edges" => Ok(Self::CsvEdges),
+            _ => Err(UnknownGraphFormat(String::from(name))),
+        }
+    }
+}
+
+impl GraphFormat {
+    /// Returns the `GraphFormat` served for a media type, if any.
+    ///
+    /// Parameters of the media type are ignored, except for `text/csv;kind=nodes` selecting the node list.
+    ///
+    /// # Arguments
+    /// * `media_type` - the media type, as found in an `Accept` header.
+    #[must_use]
+    pub fn from_media_type(media_type: &str) -> Option<Self> {
+        let mut parts = media_type.split(';').map(str::trim);
+        let essence = parts.next()?.to_ascii_lowercase();
+
+        match essence.as_str() {
+            "application/json" => Some(Self::Json),
+            "text/vnd.graphviz" => Some(Self::Dot),
+            "application/graphml+xml" => Some(Self::GraphMl),
+            "text/vnd.mermaid" => Some(Self::Mermaid),
+            "text/csv" if parts.any(|parameter| parameter == "kind=nodes") => Some(Self::CsvNodes),
+            "text/csv" => Some(Self::CsvEdges),
+            _ => None,
+        }
+    }
+
+    /// Returns the `GraphFormat` preferred by an `Accept` header, if any is acceptable.
+    ///
+    /// Media types are ranked by quality value, and then by order of appearance.
+    ///
+    /// # Arguments
+    /// * `accept` - the value of the `Accept` header.
+    #[must_use]
+    pub fn from_accept_header(accept: &str) -> Option<Self> {
+        let mut best: Option<(Self, f32)> = None;
+
+        for media_range in accept.split(',') {
+            let Some(format) = Self::from_media_type(media_range) else {
+                continue;
+            };
+
+            let quality = media_range
+                .split(';')
+                .filter_map(|parameter| parameter.trim().strip_prefix("q="))
+                .find_map(|quality| quality.parse::<f32>().ok())
+                .unwrap_or(1.0);
+
+            if quality > 0.0 && best.is_none_or(|(_, best_quality)| quality > best_quality) {
+                best = Some((format, quality));
+            }
+        }
+
+        best.map(|(format, _)| format)
+    }
+
+    /// Returns the `Content-Type` of documents in the format.
+    #[must_use]
+    pub const fn content_type(self) -> &'static str {
+        match self {
+            Self::Json => "application/json",
+            Self::Dot => "text/vnd.graphviz; charset=utf-8",
+            Self::GraphMl => "application/graphml+xml; charset=utf-8",
+            Self::Mermaid => "text/vnd.mermaid; charset=utf-8",
+            Self::CsvNodes | Self::CsvEdges => "text/csv; charset=utf-8",
+        }
+    }
+}
+
+impl DependencyGraph<'_> {
+    /// Serializes the dependency graph to the specified format.
+    ///
+    /// Only active dependencies are exported, as inactive ones may point to crates outside the dependency graph.
+    ///
+    /// # Arguments
+    /// * `format` - the `GraphFormat` to serialize to.
+    #[must_use]
+    pub fn export(&self, format: GraphFormat) -> String {
+        match format {
+            GraphFormat::Json => serde_json::to_string(self).unwrap(),
+            GraphFormat::Dot => self.to_dot(),
+            GraphFormat::GraphMl => self.to_graphml(),
+            GraphFormat::Mermaid => self.to_mermaid(),
+            GraphFormat::CsvNodes => self.to_csv_nodes(),
+            GraphFormat::CsvEdges => self.to_csv_edges(),
+        }
+    }
+
+    /// Serializes the dependency graph to a Graphviz DOT digraph.
+    ///
+    /// Crates at the same distance from the root crate share a rank, and each dependency is drawn once per pair of
+    /// crates and kind. Build dependencies are dashed and optional dependencies are hollow-headed.
+    #[must_use]
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::new();
+        let root_name = self
+            .crates
+            .first()
+            .map_or("", |crate_distance| crate_distance.name);
+
+        writeln!(dot, "digraph {} {{", dot_id(root_name)).unwrap();
+        writeln!(dot, "    rankdir=TB;").unwrap();
+        writeln!(dot, "    node [shape=box];").unwrap();
+
+        let mut ranks: BTreeMap<usize, Vec<&String>> = BTreeMap::new();
+        for crate_distance in &self.crates {
+            writeln!(
+                dot,
+                "    {} [label={}];",
+                dot_id(crate_distance.name),
+                dot_id(&format!(
+                    "{}\n{}",
+                    crate_distance.name, crate_distance.version
+                ))
+            )
+            .unwrap();
+
+            ranks
+                .entry(crate_distance.distance)
+                .or_default()
+                .push(crate_distance.name);
+        }
+
+        for names in ranks.values() {
+            let names: Vec<String> = names.iter().map(|name| dot_id(name)).collect();
+            writeln!(dot, "    {{ rank=same; {}; }}", names.join("; ")).unwrap();
+        }
+
+        for dependency in self.unique_edges() {
+            let mut attributes: Vec<&str> = vec![];
+            if dependency.kind == DependencyKind::Build {
+                attributes.push("style=dashed");
+            }
+            if dependency.optional {
+                attributes.push("arrowhead=empty");
+            }
+
+            write!(
+                dot,
+                "    {} -> {}",
+                dot_id(dependency.from),
+                dot_id(dependency.to)
+            )
+            .unwrap();
+            if !attributes.is_empty() {
+                write!(dot, " [{}]", attributes.join(", ")).unwrap();
+            }
+            writeln!(dot, ";").unwrap();
+        }
+
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// Serializes the dependency graph to a `GraphML` document.
+    ///
+    /// Crates are keyed by name, and every dependency declaration becomes an edge.
+    #[must_use]
+    pub fn to_graphml(&self) -> String {
+        let mut graphml = String::new();
+        let root_name = self
+            .crates
+            .first()
+            .map_or("", |crate_distance| crate_distance.name);
+
+        graphml.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        graphml.push_str("<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n");
+        for (id, domain, attribute_type) in [
+            ("version", "node", "string"),
+            ("distance", "node", "int"),
+            ("downloads", "node", "long"),
+            ("enabled_features", "node", "string"),
+            ("kind", "edge", "string"),
+            ("optional", "edge", "boolean"),
+            ("target", "edge", "string"),
+            ("features", "edge", "string"),
+        ] {
+            writeln!(
+                graphml,
+                "  <key id=\"{id}\" for=\"{domain}\" attr.name=\"{id}\" attr.type=\"{attribute_type}\"/>"
+            )
+            .unwrap();
+        }
+        writeln!(
+            graphml,
+            "  <graph id=\"{}\" edgedefault=\"directed\">",
+            xml_escape(root_name)
+        )
+        .unwrap();
+
+        for crate_distance in &self.crates {
+            writeln!(
+                graphml,
+                "    <node id=\"{}\">",
+                xml_escape(crate_distance.name)
+            )
+            .unwrap();
+            write_graphml_data(&mut graphml, "version", crate_distance.version);
+            write_graphml_data(
+                &mut graphml,
+                "distance",
+                &crate_distance.distance.to_string(),
+            );
+            write_graphml_data(
+                &mut graphml,
+                "downloads",
+                &crate_distance.downloads.to_string(),
+            );
+            write_graphml_data(
+                &mut graphml,
+                "enabled_features",
+                &crate_distance.enabled_features.join(" "),
+            );
+            graphml.push_str("    </node>\n");
+        }
+
+        for (index, dependency) in self.active_edges().enumerate() {
+            writeln!(
+                graphml,
+                "    <edge id=\"e{index}\" source=\"{}\" target=\"{}\">",
+                xml_escape(dependency.from),
+                xml_escape(dependency.to)
+            )
+            .unwrap();
+            write_graphml_data(&mut graphml, "kind", kind_name(dependency.kind));
+            write_graphml_data(&mut graphml, "optional", &dependency.optional.to_string());
+            if let Some(target) = dependency.target {
+                write_graphml_data(&mut graphml, "target", target);
+            }
+            write_graphml_data(&mut graphml, "features", &dependency.features.join(" "));
+            graphml.push_str("    </edge>\n");
+        }
+
+        graphml.push_str("  </graph>\n</graphml>\n");
+        graphml
+    }
+
+    /// Serializes the dependency graph to a Mermaid flowchart.
+    ///
+    /// Each dependency is drawn once per pair of crates and kind, with build dependencies dotted.
+    #[must_use]
+    pub fn to_mermaid(&self) -> String {
+        let mut mermaid = String::from("flowchart TD\n");
+
+        let node_ids: AHashMap<&String, usize> = self
+            .crates
+            .iter()
+            .enumerate()
+            .map(|(index, crate_distance)| (crate_distance.name, index))
+            .collect();
+
+        for (index, crate_distance) in self.crates.iter().enumerate() {
+            writeln!(
+                mermaid,
+                "    n{index}[\"{} {}\"]",
+                mermaid_escape(crate_distance.name),
+                mermaid_escape(crate_distance.version)
+            )
+            .unwrap();
+        }
+
+        for dependency in self.unique_edges() {
+            let arrow = if dependency.kind == DependencyKind::Build {
+                "-.->"
+            } else {
+                "-->"
+            };
+
+            writeln!(
+                mermaid,
+                "    n{} {arrow} n{}",
+                node_ids[dependency.from], node_ids[dependency.to]
+            )
+            .unwrap();
+        }
+
+        mermaid
+    }
+
+    /// Serializes the crates of the dependency graph to a CSV node list.
+    #[must_use]
+    pub fn to_csv_nodes(&self) -> String {
+        let mut writer = csv::Writer::from_writer(vec![]);
+
+        writer
+            .write_record([
+                "Id",
+                "Label",
+                "version",
+                "distance",
+                "downloads",
+                "enabled_features",
+            ])
+            .unwrap();
+
+        for crate_distance in &self.crates {
+            writer
+                .write_record([
+                    crate_distance.name.as_str(),
+                    crate_distance.name.as_str(),
+                    crate_distance.version.as_str(),
+                    &crate_distance.distance.to_string(),
+                    &crate_distance.downloads.to_string(),
+                    &crate_distance.enabled_features.join(" "),
+                ])
+                .unwrap();
+        }
+
+        String::from_utf8(writer.into_inner().unwrap()).unwrap()
+    }
+
+    /// Serializes the dependencies of the dependency graph to a CSV edge list, with a row per declaration.
+    #[must_use]
+    pub fn to_csv_edges(&self) -> String {
+        let mut writer = csv::Writer::from_writer(vec![]);
+
+        writer
+            .write_record([
+                "Source",
+                "Target",
+                "Type",
+                "kind",
+                "optional",
+                "default_features",
+                "features",
+                "target",
+            ])
+            .unwrap();
+
+        for dependency in self.active_edges() {
+            writer
+                .write_record([
+                    dependency.from.as_str(),
+                    dependency.to.as_str(),
+                    "Directed",
+                    kind_name(dependency.kind),
+                    &dependency.optional.to_string(),
+                    &dependency.default_features.to_string(),
+                    &dependency.features.join(" "),
+                    dependency.target.map_or("", String::as_str),
+                ])
+                .unwrap();
+        }
+
+        String::from_utf8(writer.into_inner().unwrap()).unwrap()
+    }
+
+    /// Returns the active dependencies of the dependency graph.
+    fn active_edges(&self) -> impl Iterator<Item = &DependencyEdge<'_>> {
+        self.dependencies
+            .iter()
+            .filter(|dependency| dependency.active)
+    }
+
+    /// Returns the active dependencies of the dependency graph, keeping the first per pair of crates and kind.
+    fn unique_edges(&self) -> Vec<&DependencyEdge<'_>> {
+        let mut seen: Vec<(&String, &String, DependencyKind)> = vec![];
+
+        self.active_edges()
+            .filter(|dependency| {
+                let key = (dependency.from, dependency.to, dependency.kind);
+                let is_new = !seen.contains(&key);
+                if is_new {
+                    seen.push(key);
+                }
+                is_new
+            })
+            .collect()
+    }
+}
+
+/// Returns the lowercase name of a `DependencyKind`, as serialized.
+///
+/// # Arguments
+/// * `kind` - the `DependencyKind`.
+const fn kind_name(kind: DependencyKind) -> &'static str {
+    match kind {
+        DependencyKind::Normal => "normal",
+        DependencyKind::Dev => "dev",
+        DependencyKind::Build => "build",
+    }
+}
+
+/// Returns a quoted DOT identifier.
+///
+/// # Arguments
+/// * `id` - the identifier to quote.
+fn dot_id(id: &str) -> String {
+    format!(
+        "\"{}\"",
+        id.replace('\\', "\\\\")
+            .replace('"', "\\\"")
+            .replace('\n', "\\n")
+    )
+}
+
+/// Escapes text for use in XML attributes and content.
+///
+/// # Arguments
+/// * `text` - the text to escape.
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+/// Escapes text for use in a quoted Mermaid label.
+///
+/// # Arguments
+/// * `text` - the text to escape.
+fn mermaid_escape(text: &str) -> String {
+    text.replace('"', "#quot;")
+}
+
+/// Writes a `GraphML` `data` element.
+///
+/// # Arguments
+/// * `graphml` - the document being written.
+/// * `key` - the key of the data.
+/// * `value` - the value of the data.
+fn write_graphml_data(graphml: &mut String, key: &str, value: &str) {
+    writeln!(
+        graphml,
+        "      <data key=\"{key}\">{}</data>",
+        xml_escape(value)
+    )
+    .unwrap();
+}
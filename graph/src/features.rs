@@ -0,0 +1,734 @@
+use super::{Crate, DependencyEdge, DependencyGraph, DependencyGraphOptions, Graph};
+use ahash::{AHashMap, AHashSet};
+use serde::Serialize;
+use std::{
+    cmp::Reverse,
+    collections::{BTreeSet, VecDeque},
+};
+
+/// The maximum number of feature selections to try before settling for an irreducible, rather than smallest, change.
+const MAX_FEATURE_SELECTIONS: usize = 1024;
+
+impl Graph {
+    /// Returns the cost of enabling each of the given features of the specified crate, ranked by the number of crates
+    /// added on top of the default features.
+    ///
+    /// The default and no default feature selections are each resolved once, and each distinct set of features a
+    /// feature enables on top of them is resolved once, so features already enabled (e.g. by default) cost nothing to
+    /// compute. Every other feature still takes a full resolution per selection, so callers should limit the number
+    /// of features requested.
+    ///
+    /// If no crate matches the specified name, returns `None`.
+    ///
+    /// # Arguments
+    /// * `crate_id` - the name of the crate to analyze.
+    /// * `features` - the features to compute the cost of, or every feature apart from `default` if empty.
+    /// * `target` - the target triple to resolve platform-specific dependencies for.
+    /// * `cfg_name` - an additional cfg name to enable when resolving platform-specific dependencies.
+    /// * `options` - the `DependencyGraphOptions` to use, apart from the root crate's feature selection.
+    #[must_use]
+    pub fn get_feature_costs(
+        &self,
+        crate_id: &str,
+        features: &[String],
+        target: &Option<String>,
+        cfg_name: &Option<String>,
+        options: &DependencyGraphOptions,
+    ) -> Option<Vec<FeatureCost<'_>>> {
+        let crate_val = self.crates().get(crate_id)?;
+
+        let feature_names: Vec<&String> = if features.is_empty() {
+            crate_val
+                .features
+                .keys()
+                .filter(|feature_name| *feature_name != "default")
+                .collect()
+        } else {
+            crate_val
+                .features
+                .keys()
+                .filter(|feature_name| features.contains(feature_name))
+                .collect()
+        };
+
+        let default_options = DependencyGraphOptions {
+            default_features: true,
+            all_features: false,
+            potential_dependencies: None,
+            ..options.clone()
+        };
+
+        let no_default_options = DependencyGraphOptions {
+            default_features: false,
+            ..default_options.clone()
+        };
+
+        let get_feature_deltas = |options: &DependencyGraphOptions| {
+            get_feature_deltas(
+                crate_val,
+                &feature_names,
+                options.default_features,
+                |features| {
+                    self.get_dependency_graph(crate_id, features, target, cfg_name, options)
+                        .unwrap()
+                },
+            )
+        };
+
+        let mut feature_costs: Vec<FeatureCost> = feature_names
+            .iter()
+            .zip(get_feature_deltas(&default_options))
+            .zip(get_feature_deltas(&no_default_options))
+            .map(
+                |((feature_name, over_default), over_no_default)| FeatureCost {
+                    feature: feature_name,
+                    over_default,
+                    over_no_default,
+                },
+            )
+            .collect();
+
+        // sort by crates added over default features, then over no default features
+        // ties are kept in alphabetical order
+        feature_costs.sort_by_key(|feature_cost| {
+            Reverse((
+                feature_cost.over_default.crate_count,
+                feature_cost.over_no_default.crate_count,
+            ))
+        });
+
+        Some(feature_costs)
+    }
+
+    /// Returns the smallest change to the feature selection of the specified crate which removes an unwanted crate
+    /// from its dependency graph, or the path which keeps it in place if no such change exists.
+    ///
+    /// Since enabling features can only add crates, a change may only remove explicitly enabled features or stop
+    /// enabling features through `default`. The latter disables default features and explicitly enables the members
+    /// of `default` which are kept, while entries of `default` which are not features (e.g. `dep:` entries) can only
+    /// be dropped together. Required features are kept in every selection.
+    ///
+    /// Changes are tried in order of the number of features they stop enabling. If more than
+    /// `MAX_FEATURE_SELECTIONS` selections would need to be tried, the search is cut short and an irreducible change is
+    /// returned instead, which removes the unwanted crate but is not guaranteed to be the smallest. The `exhaustive`
+    /// flag of the returned change reports which of the two was found.
+    ///
+    /// If no crate matches the specified name, returns `None`.
+    ///
+    /// # Arguments
+    /// * `crate_id` - the name of the root crate.
+    /// * `features` - the features currently enabled for the root crate.
+    /// * `required_features` - the features of the root crate which must stay enabled.
+    /// * `unwanted_crate_id` - the name of the crate to remove from the dependency graph.
+    /// * `target` - the target triple to resolve platform-specific dependencies for.
+    /// * `cfg_name` - an additional cfg name to enable when resolving platform-specific dependencies.
+    /// * `options` - the `DependencyGraphOptions` currently in use.
+    #[must_use]
+    #[allow(clippy::too_many_arguments)]
+    pub fn get_feature_suggestion(
+        &self,
+        crate_id: &str,
+        features: &[String],
+        required_features: &[String],
+        unwanted_crate_id: &str,
+        target: &Option<String>,
+        cfg_name: &Option<String>,
+        options: &DependencyGraphOptions,
+    ) -> Option<FeatureSuggestion<'_>> {
+        let crate_val = self.crates().get(crate_id)?;
+
+        // all features count as explicitly enabled, so each may be removed
+        let mut current_features: Vec<String> = if options.all_features {
+            crate_val.features.keys().cloned().collect()
+        } else {
+            features.to_vec()
+        };
+
+        let default_features = options.default_features
+            || options.all_features
+            || current_features.iter().any(|feature| feature == "default");
+        current_features.retain(|feature| feature != "default");
+        current_features.sort();
+        current_features.dedup();
+
+        let keep_default = required_features.iter().any(|feature| feature == "default");
+        let feature_options = FeatureOption::get_all(
+            crate_val,
+            &current_features,
+            required_features,
+            default_features,
+            default_features && !keep_default,
+        );
+
+        let get_dependency_graph = |dropped: &[usize]| {
+            let selection = FeatureSelection::new(
+                &current_features,
+                required_features,
+                &feature_options,
+                dropped,
+                default_features,
+            );
+
+            let dependency_graph = self
+                .get_dependency_graph(
+                    crate_id,
+                    selection.features.clone(),
+                    target,
+                    cfg_name,
+                    &DependencyGraphOptions {
+                        default_features: selection.default_features,
+                        all_features: false,
+                        potential_dependencies: None,
+                        ..options.clone()
+                    },
+                )
+                .unwrap();
+
+            (selection, dependency_graph)
+        };
+
+        let contains_unwanted = |dependency_graph: &DependencyGraph| {
+            dependency_graph
+                .crates
+                .iter()
+                .any(|crate_distance| crate_distance.name == unwanted_crate_id)
+        };
+
+        // dropping every option yields the smallest graph, so if it still contains the unwanted crate no change can
+        // remove it
+        let all_dropped: Vec<usize> = (0..feature_options.len()).collect();
+        let (_, smallest_graph) = get_dependency_graph(&all_dropped);
+
+        if contains_unwanted(&smallest_graph) {
+            return Some(FeatureSuggestion::Blocked {
+                path: get_path(smallest_graph, crate_id, unwanted_crate_id),
+            });
+        }
+
+        // try every change in order of size
+        let mut selections_tried = 0;
+
+        'search: for change_size in 0..=feature_options.len() {
+            for dropped in Combinations::new(feature_options.len(), change_size) {
+                selections_tried += 1;
+                if selections_tried > MAX_FEATURE_SELECTIONS {
+                    break 'search;
+                }
+
+                let (selection, dependency_graph) = get_dependency_graph(&dropped);
+                if !contains_unwanted(&dependency_graph) {
+                    return Some(FeatureSuggestion::Removable(selection.into_change(true)));
+                }
+            }
+        }
+
+        // too many selections to try, so restore options one at a time while the unwanted crate stays removed
+        let mut dropped = all_dropped;
+
+        for index in (0..feature_options.len()).rev() {
+            let restored: Vec<usize> = dropped.iter().copied().filter(|&i| i != index).collect();
+            let (_, dependency_graph) = get_dependency_graph(&restored);
+
+            if !contains_unwanted(&dependency_graph) {
+                dropped = restored;
+            }
+        }
+
+        let (selection, _) = get_dependency_graph(&dropped);
+        Some(FeatureSuggestion::Removable(selection.into_change(false)))
+    }
+}
+
+/// Returns the crates and edges each feature adds on top of a baseline feature selection, in the order given.
+///
+/// Enabling a feature enables every feature it transitively refers to, so the graph only depends on which of
+/// those the baseline did not already enable. Each distinct set is resolved once, and features adding none are
+/// not resolved at all.
+///
+/// # Arguments
+/// * `crate_val` - the `Crate` to analyze.
+/// * `feature_names` - the features to compute the cost of.
+/// * `default_features` - if the baseline feature selection enables default features.
+/// * `get_dependency_graph` - a function resolving the baseline feature selection with additional features.
+fn get_feature_deltas<'a>(
+    crate_val: &Crate,
+    feature_names: &[&String],
+    default_features: bool,
+    get_dependency_graph: impl Fn(Vec<String>) -> DependencyGraph<'a>,
+) -> Vec<FeatureCostDelta<'a>> {
+    // the baseline enables `default` and every feature it transitively refers to, if anything
+    let default_feature = String::from("default");
+    let baseline_features: AHashSet<&String> = if default_features {
+        get_enabled_features(crate_val, &default_feature)
+            .into_iter()
+            .collect()
+    } else {
+        AHashSet::new()
+    };
+
+    let baseline = get_dependency_graph(vec![]);
+    let baseline_footprint = GraphFootprint::new(&baseline);
+
+    // features which enable the same features on top of the baseline share a resolution
+    let mut deltas: AHashMap<BTreeSet<&String>, FeatureCostDelta> = AHashMap::new();
+
+    feature_names
+        .iter()
+        .map(|feature_name| {
+            let added_features: BTreeSet<&String> = get_enabled_features(crate_val, feature_name)
+                .into_iter()
+                .filter(|enabled_feature| !baseline_features.contains(enabled_feature))
+                .collect();
+
+            deltas
+                .entry(added_features)
+                .or_insert_with_key(|added_features| {
+                    if added_features.is_empty() {
+                        FeatureCostDelta::default()
+                    } else {
+                        GraphFootprint::new(&get_dependency_graph(vec![(*feature_name).clone()]))
+                            .difference(&baseline_footprint)
+                    }
+                })
+                .clone()
+        })
+        .collect()
+}
+
+/// Returns the features of a crate enabled by enabling a feature, including the feature itself.
+///
+/// # Arguments
+/// * `crate_val` - the `Crate` exposing the feature.
+/// * `feature_name` - the name of the feature.
+fn get_enabled_features<'a>(
+    crate_val: &'a Crate,
+    feature_name: &'a String,
+) -> BTreeSet<&'a String> {
+    let mut enabled_features: BTreeSet<&String> = BTreeSet::new();
+    let mut feature_queue: Vec<&String> = vec![feature_name];
+
+    while let Some(feature_name) = feature_queue.pop() {
+        if let Some((feature_name, feature_dependencies)) =
+            crate_val.features.get_key_value(feature_name)
+        {
+            if enabled_features.insert(feature_name) {
+                feature_queue.extend(feature_dependencies);
+            }
+        }
+    }
+
+    enabled_features
+}
+
+/// Returns the shortest path of edges from the root crate to the specified crate in a `DependencyGraph`.
+///
+/// # Arguments
+/// * `dependency_graph` - the `DependencyGraph` to search.
+/// * `crate_id` - the name of the root crate.
+/// * `target_crate_id` - the name of the crate to find a path to.
+fn get_path<'a>(
+    dependency_graph: DependencyGraph<'a>,
+    crate_id: &str,
+    target_crate_id: &str,
+) -> Vec<DependencyEdge<'a>> {
+    let mut edges_by_source: AHashMap<&str, Vec<usize>> = AHashMap::new();
+    for (index, dependency) in dependency_graph.dependencies.iter().enumerate() {
+        edges_by_source
+            .entry(dependency.from)
+            .or_default()
+            .push(index);
+    }
+
+    // the edge used to reach each crate, with the root reached by none
+    let mut reached_by: AHashMap<&str, Option<usize>> = AHashMap::new();
+    let mut crate_queue: VecDeque<&str> = VecDeque::new();
+    reached_by.insert(crate_id, None);
+    crate_queue.push_back(crate_id);
+
+    while let Some(from) = crate_queue.pop_front() {
+        if from == target_crate_id {
+            break;
+        }
+
+        for &index in edges_by_source.get(from).into_iter().flatten() {
+            let to = dependency_graph.dependencies[index].to.as_str();
+            if !reached_by.contains_key(to) {
+                reached_by.insert(to, Some(index));
+                crate_queue.push_back(to);
+            }
+        }
+    }
+
+    let mut path_indices: Vec<usize> = vec![];
+    let mut current = target_crate_id;
+    while let Some(Some(index)) = reached_by.get(current) {
+        path_indices.push(*index);
+        current = dependency_graph.dependencies[*index].from;
+    }
+
+    let mut dependencies: Vec<Option<DependencyEdge>> = dependency_graph
+        .dependencies
+        .into_iter()
+        .map(Some)
+        .collect();
+
+    path_indices
+        .into_iter()
+        .rev()
+        .filter_map(|index| dependencies[index].take())
+        .collect()
+}
+
+/// An iterator over the combinations of a given size of the indices below a given count, in lexicographic order.
+struct Combinations {
+    /// The number of indices to choose from.
+    count: usize,
+
+    /// The next combination to yield, if any remain.
+    next: Option<Vec<usize>>,
+}
+
+impl Combinations {
+    /// Creates a new `Combinations` iterator.
+    ///
+    /// # Arguments
+    /// * `count` - the number of indices to choose from.
+    /// * `size` - the number of indices in each combination.
+    fn new(count: usize, size: usize) -> Self {
+        Self {
+            count,
+            next: (size <= count).then(|| (0..size).collect()),
+        }
+    }
+}
+
+impl Iterator for Combinations {
+    type Item = Vec<usize>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let current = self.next.take()?;
+        let size = current.len();
+
+        // advance the rightmost index which has room to move
+        let mut following = current.clone();
+        if let Some(position) = (0..size)
+            .rev()
+            .find(|&i| following[i] < self.count - size + i)
+        {
+            following[position] += 1;
+            for i in position + 1..size {
+                following[i] = following[i - 1] + 1;
+            }
+            self.next = Some(following);
+        }
+
+        Some(current)
+    }
+}
+
+/// A way in which a change to the feature selection of the root crate may stop enabling features.
+enum FeatureOption<'a> {
+    /// Removes an explicitly enabled feature.
+    Explicit(&'a String),
+
+    /// Stops enabling a member of `default`, by disabling default features and explicitly enabling the other members.
+    DefaultMember(&'a String),
+
+    /// Drops the entries of `default` which are not features, by disabling default features.
+    DefaultEntries,
+}
+
+impl<'a> FeatureOption<'a> {
+    /// Returns every `FeatureOption` available for the current selection, explicit features first.
+    ///
+    /// # Arguments
+    /// * `crate_val` - the root `Crate`.
+    /// * `current_features` - the features currently enabled explicitly, apart from `default`.
+    /// * `required_features` - the features which must stay enabled.
+    /// * `default_features` - if default features are currently enabled.
+    /// * `can_disable_default` - if default features are enabled and may be disabled.
+    fn get_all(
+        crate_val: &'a Crate,
+        current_features: &'a [String],
+        required_features: &[String],
+        default_features: bool,
+        can_disable_default: bool,
+    ) -> Vec<Self> {
+        let mut default_entries: Vec<&String> = vec![];
+        if default_features {
+            for entry in crate_val.features.get("default").into_iter().flatten() {
+                if !default_entries.contains(&entry) {
+                    default_entries.push(entry);
+                }
+            }
+        }
+        let is_default_member = |feature: &String| {
+            default_entries.contains(&feature) && crate_val.features.contains_key(feature)
+        };
+
+        // removing an explicit feature which is also a member of default requires disabling default features
+        let mut feature_options: Vec<Self> = current_features
+            .iter()
+            .filter(|feature| !required_features.contains(feature) && !is_default_member(feature))
+            .map(FeatureOption::Explicit)
+            .collect();
+
+        if can_disable_default {
+            feature_options.extend(
+                default_entries
+                    .iter()
+                    .filter(|entry| is_default_member(entry) && !required_features.contains(entry))
+                    .map(|entry| FeatureOption::DefaultMember(entry)),
+            );
+
+            if default_entries
+                .iter()
+                .any(|entry| !crate_val.features.contains_key(*entry))
+            {
+                feature_options.push(FeatureOption::DefaultEntries);
+            }
+        }
+
+        feature_options
+    }
+}
+
+/// A selection of features for the root crate, derived from the current selection by dropping `FeatureOption`s.
+struct FeatureSelection {
+    /// If default features are enabled.
+    default_features: bool,
+
+    /// The features enabled.
+    features: Vec<String>,
+
+    /// If default features were disabled compared to the current selection.
+    disabled_default_features: bool,
+
+    /// The explicitly enabled features removed compared to the current selection.
+    removed_features: Vec<String>,
+
+    /// The members of `default` no longer enabled compared to the current selection.
+    removed_default_features: Vec<String>,
+}
+
+impl FeatureSelection {
+    /// Creates a new `FeatureSelection`.
+    ///
+    /// # Arguments
+    /// * `current_features` - the features currently enabled explicitly, apart from `default`.
+    /// * `required_features` - the features which must stay enabled.
+    /// * `feature_options` - the `FeatureOption`s available for the current selection.
+    /// * `dropped` - the indices of the dropped options in `feature_options`.
+    /// * `default_features` - if default features are currently enabled.
+    fn new(
+        current_features: &[String],
+        required_features: &[String],
+        feature_options: &[FeatureOption],
+        dropped: &[usize],
+        default_features: bool,
+    ) -> Self {
+        let mut removed_features: Vec<String> = vec![];
+        let mut removed_default_features: Vec<String> = vec![];
+        let mut disable_default = false;
+
+        for &index in dropped {
+            match feature_options[index] {
+                FeatureOption::Explicit(feature) => removed_features.push(feature.clone()),
+                FeatureOption::DefaultMember(feature) => {
+                    if current_features.contains(feature) {
+                        removed_features.push(feature.clone());
+                    }
+                    removed_default_features.push(feature.clone());
+                    disable_default = true;
+                }
+                FeatureOption::DefaultEntries => disable_default = true,
+            }
+        }
+
+        let mut features: Vec<String> = current_features
+            .iter()
+            .filter(|feature| !removed_features.contains(feature))
+            .cloned()
+            .collect();
+
+        // kept members of default are enabled explicitly once default features are disabled
+        if disable_default {
+            for feature_option in feature_options {
+                if let FeatureOption::DefaultMember(feature) = feature_option {
+                    if !removed_default_features.contains(feature) && !features.contains(feature) {
+                        features.push((*feature).clone());
+                    }
+                }
+            }
+        }
+
+        // required features are enabled even if they were not part of the current selection
+        for feature in required_features {
+            if feature != "default" && !features.contains(feature) {
+                features.push(feature.clone());
+            }
+        }
+
+        Self {
+            default_features: default_features && !disable_default,
+            features,
+            disabled_default_features: default_features && disable_default,
+            removed_features,
+            removed_default_features,
+        }
+    }
+
+    /// Converts the `FeatureSelection` into a `FeatureChange`.
+    ///
+    /// # Arguments
+    /// * `exhaustive` - if every smaller change was tried, so the change is known to be the smallest possible.
+    fn into_change(self, exhaustive: bool) -> FeatureChange {
+        FeatureChange {
+            default_features: self.default_features,
+            features: self.features,
+            disabled_default_features: self.disabled_default_features,
+            removed_features: self.removed_features,
+            removed_default_features: self.removed_default_features,
+            exhaustive,
+        }
+    }
+}
+
+/// The sets of crates and edges present in a `DependencyGraph`.
+struct GraphFootprint<'a> {
+    /// The names of the crates in the `DependencyGraph`.
+    crates: Vec<&'a String>,
+
+    /// The edges in the `DependencyGraph` by source and destination name.
+    dependencies: Vec<(&'a String, &'a String)>,
+}
+
+impl<'a> GraphFootprint<'a> {
+    /// Creates a new `GraphFootprint` from a `DependencyGraph`.
+    ///
+    /// # Arguments
+    /// * `dependency_graph` - the `DependencyGraph` to summarize.
+    fn new(dependency_graph: &DependencyGraph<'a>) -> Self {
+        let mut dependencies_seen: AHashSet<(&String, &String)> = AHashSet::new();
+
+        Self {
+            crates: dependency_graph
+                .crates
+                .iter()
+                .map(|crate_distance| crate_distance.name)
+                .collect(),
+            dependencies: dependency_graph
+                .dependencies
+                .iter()
+                .map(|dependency| (dependency.from, dependency.to))
+                .filter(|&dependency_tuple| dependencies_seen.insert(dependency_tuple))
+                .collect(),
+        }
+    }
+
+    /// Returns the crates and edges present in this `GraphFootprint` but not in another.
+    ///
+    /// # Arguments
+    /// * `other` - the `GraphFootprint` to compare against.
+    fn difference(self, other: &Self) -> FeatureCostDelta<'a> {
+        let other_crates: AHashSet<&String> = other.crates.iter().copied().collect();
+        let other_dependencies: AHashSet<(&String, &String)> =
+            other.dependencies.iter().copied().collect();
+
+        let added_crates: Vec<&String> = self
+            .crates
+            .into_iter()
+            .filter(|crate_id| !other_crates.contains(crate_id))
+            .collect();
+
+        let added_dependencies: Vec<CrateEdge> = self
+            .dependencies
+            .into_iter()
+            .filter(|dependency_tuple| !other_dependencies.contains(dependency_tuple))
+            .map(|(from, to)| CrateEdge { from, to })
+            .collect();
+
+        FeatureCostDelta {
+            crate_count: added_crates.len(),
+            crates: added_crates,
+            dependency_count: added_dependencies.len(),
+            dependencies: added_dependencies,
+        }
+    }
+}
+
+/// The cost of enabling a feature of a crate.
+#[derive(Serialize)]
+pub struct FeatureCost<'a> {
+    /// The name of the feature.
+    pub feature: &'a String,
+
+    /// The crates and edges the feature adds on top of the default features.
+    pub over_default: FeatureCostDelta<'a>,
+
+    /// The crates and edges the feature adds on top of no default features.
+    pub over_no_default: FeatureCostDelta<'a>,
+}
+
+/// The crates and edges added to a `DependencyGraph` by enabling a feature.
+#[derive(Clone, Default, Serialize)]
+pub struct FeatureCostDelta<'a> {
+    /// The number of crates added.
+    pub crate_count: usize,
+
+    /// The names of the crates added.
+    pub crates: Vec<&'a String>,
+
+    /// The number of edges added.
+    pub dependency_count: usize,
+
+    /// The edges added, by source and destination name.
+    pub dependencies: Vec<CrateEdge<'a>>,
+}
+
+/// An edge between two crates, irrespective of how many times it is declared.
+#[derive(Clone, Serialize)]
+pub struct CrateEdge<'a> {
+    /// The source crate of the edge.
+    pub from: &'a String,
+
+    /// The destination crate of the edge.
+    pub to: &'a String,
+}
+
+/// The outcome of searching for a feature selection which removes an unwanted crate from a dependency graph.
+#[derive(Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum FeatureSuggestion<'a> {
+    /// The unwanted crate can be removed by changing the feature selection.
+    Removable(FeatureChange),
+
+    /// No feature selection which keeps the required features removes the unwanted crate.
+    Blocked {
+        /// The path of dependencies from the root crate to the unwanted crate which remains with every removable
+        /// feature removed.
+        path: Vec<DependencyEdge<'a>>,
+    },
+}
+
+/// A change to the feature selection of a crate.
+#[derive(Serialize)]
+pub struct FeatureChange {
+    /// If default features are enabled after the change.
+    pub default_features: bool,
+
+    /// The features enabled after the change.
+    pub features: Vec<String>,
+
+    /// If the change disables default features, which drops any entries of `default` which are not features.
+    pub disabled_default_features: bool,
+
+    /// The explicitly enabled features removed by the change.
+    pub removed_features: Vec<String>,
+
+    /// The members of `default` no longer enabled after the change, which requires disabling default features.
+    pub removed_default_features: Vec<String>,
+
+    /// If every smaller change was tried, so the change is the smallest possible rather than only irreducible.
+    pub exhaustive: bool,
+}
@@ -0,0 +1,103 @@
+use super::{Crate, DependencyGraphOptions, Graph};
+use rayon::prelude::*;
+use serde::Serialize;
+use std::cmp::Reverse;
+
+/// The max number of crates listed on each side of a footprint leaderboard.
+const LEADERBOARD_LENGTH: usize = 10;
+
+impl Graph {
+    /// Computes and assigns the transitive footprint of each crate in its default configuration.
+    ///
+    /// Each crate's `DependencyGraph` is resolved with default features for the default target, in parallel.
+    pub(crate) fn assign_footprints(&mut self) {
+        let options = DependencyGraphOptions::default();
+
+        let footprints: Vec<(usize, usize)> = self
+            .crates()
+            .keys()
+            .collect::<Vec<&String>>()
+            .par_iter()
+            .map(|&crate_id| {
+                let dependency_graph = self
+                    .get_dependency_graph(crate_id, vec![], &None, &None, &options)
+                    .unwrap();
+
+                (
+                    dependency_graph.crates.len() - 1,
+                    dependency_graph
+                        .crates
+                        .iter()
+                        .map(|crate_distance| crate_distance.distance)
+                        .max()
+                        .unwrap_or_default(),
+                )
+            })
+            .collect();
+
+        // footprints are computed in the order of the crates
+        for (crate_val, (transitive_dependencies, max_depth)) in
+            self.compact.crates_mut().values_mut().zip(footprints)
+        {
+            crate_val.transitive_dependencies = transitive_dependencies;
+            crate_val.max_depth = max_depth;
+        }
+    }
+
+    /// Returns the crates of the specified category with the heaviest and lightest transitive footprints.
+    ///
+    /// Crates are ranked by transitive dependency count and then by max depth, with ties broken alphabetically.
+    ///
+    /// If no category matches the specified name, returns `None`.
+    ///
+    /// # Arguments
+    /// * `category_id` - the name of the category.
+    #[must_use]
+    pub fn get_footprint_leaderboard(&self, category_id: &str) -> Option<FootprintLeaderboard<'_>> {
+        let category = self.categories().get(category_id)?;
+
+        let mut category_crates: Vec<&Crate> = category
+            .crates
+            .iter()
+            .filter_map(|crate_id| self.crates().get(crate_id))
+            .collect();
+
+        category_crates.sort_by_key(|crate_val| {
+            (
+                crate_val.transitive_dependencies,
+                crate_val.max_depth,
+                &crate_val.name,
+            )
+        });
+
+        let lightest: Vec<&Crate> = category_crates
+            .iter()
+            .take(LEADERBOARD_LENGTH)
+            .copied()
+            .collect();
+
+        category_crates.sort_by_key(|crate_val| {
+            (
+                Reverse(crate_val.transitive_dependencies),
+                Reverse(crate_val.max_depth),
+                &crate_val.name,
+            )
+        });
+        category_crates.truncate(LEADERBOARD_LENGTH);
+
+        Some(FootprintLeaderboard {
+            heaviest: category_crates,
+            lightest,
+        })
+    }
+}
+
+/// The crates of a category with the heaviest and lightest transitive footprints.
+#[derive(Serialize)]
+pub struct FootprintLeaderboard<'a> {
+    /// The crates with the most transitive dependencies, heaviest first.
+    pub heaviest: Vec<&'a Crate>,
+
+    /// The crates with the fewest transitive dependencies, lightest first.
+    pub lightest: Vec<&'a Crate>,
+}
@@ -1,8 +1,9 @@
+use super::load::{read_error, LoadError, LoadRecorder};
 use std::{
     fs,
     io::{self, Write},
-    process::Command,
-    time::Instant,
+    path::Path,
+    process::{Command, Output},
 };
 use tempfile::TempDir;
 
@@ -10,113 +11,179 @@ use tempfile::TempDir;
 ///
 /// # Arguments
 /// * `temp_dir` - the `TempDir` in which to search for the directory.
-pub fn get_data_path(temp_dir: &TempDir) -> Option<String> {
-    for dir_entry in fs::read_dir(temp_dir.path()).expect("Unable to read temporary directory") {
-        let dir_entry = dir_entry.unwrap();
+///
+/// # Errors
+/// * Returns a `LoadError` if the directory cannot be read or the data path is not valid UTF-8.
+pub fn get_data_path(temp_dir: &TempDir) -> Result<Option<String>, LoadError> {
+    let temp_path = temp_dir.path().display();
+
+    for dir_entry in fs::read_dir(temp_dir.path()).map_err(read_error(&temp_path))? {
+        let dir_entry = dir_entry.map_err(read_error(&temp_path))?;
         if let Ok(file_type) = dir_entry.file_type() {
             // find the folder containing the dump results
             if file_type.is_dir() {
                 let mut data_path = dir_entry.path();
                 // append data to the path
                 data_path.push("data");
-                return Some(String::from(
-                    data_path
-                        .as_path()
-                        .to_str()
-                        .expect("Data path is not valid UTF-8"),
-                ));
+                return data_path
+                    .to_str()
+                    .map(|path| Some(String::from(path)))
+                    .ok_or_else(|| {
+                        LoadError::Read(
+                            data_path.display().to_string(),
+                            String::from("path is not valid UTF-8"),
+                        )
+                    });
             }
         }
     }
 
-    None
+    Ok(None)
 }
 
-/// Load the crates.io data dump into a temporary directory.
+/// Downloads a tarballed crates.io database dump and unpacks it into a temporary directory.
 ///
 /// Returns the temporary directory containing the dump data.
-pub fn fetch_data() -> TempDir {
-    println!("Fetching data...");
-    let fetch_start = Instant::now();
-    let temp_dir = TempDir::new().unwrap();
+///
+/// # Arguments
+/// * `url` - the URL of the tarballed database dump.
+/// * `recorder` - the `LoadRecorder` to record loading phases with.
+///
+/// # Errors
+/// * Returns a `LoadError` if the dump cannot be downloaded or unpacked.
+pub fn fetch_data(url: &str, recorder: &LoadRecorder) -> Result<TempDir, LoadError> {
+    let temp_dir = create_tempdir()?;
     let tgz_path = temp_dir.path().join("crates_data.tar.gz");
-    let tgz_path_name = tgz_path
-        .as_path()
-        .to_str()
-        .expect("Tarball path not valid UTF-8");
 
-    println!("Downloading tarballed database dump...");
-    let download_start = Instant::now();
-    let curl_output = Command::new("curl")
-        .arg("https://cloudfront-static.crates.io/db-dump.tar.gz")
-        .arg("-o")
-        .arg(tgz_path_name)
-        .output()
-        .expect("Unable to fetch Crates database dump");
-    io::stdout().write_all(&curl_output.stdout).unwrap();
-    io::stderr().write_all(&curl_output.stderr).unwrap();
-    println!(
-        "Downloaded tarballed database dump in {} seconds.",
-        download_start.elapsed().as_secs_f64()
-    );
+    recorder.time("download", || {
+        // fail on HTTP errors rather than saving the error page as the tarball, reporting only the error
+        let curl_output = Command::new("curl")
+            .arg("--fail")
+            .arg("--silent")
+            .arg("--show-error")
+            .arg(url)
+            .arg("-o")
+            .arg(&tgz_path)
+            .output();
+        check_output("curl", curl_output)
+    })?;
 
-    println!("Unpacking tarballed database dump...");
-    let unpack_start = Instant::now();
+    unpack_tarball(&tgz_path, &temp_dir, recorder)?;
 
-    // exclude files not included in loading process
-    Command::new("tar")
-        .arg("-xzf")
-        .arg(&tgz_path)
-        .arg("--exclude")
-        .arg("*/badges.csv")
-        .arg("--exclude")
-        .arg("*/crate_owners.csv")
-        .arg("--exclude")
-        .arg("*/metadata.csv")
-        .arg("--exclude")
-        .arg("*/reserved_crate_names.csv")
-        .arg("--exclude")
-        .arg("*/teams.csv")
-        .arg("--exclude")
-        .arg("*/users.csv")
-        .arg("--exclude")
-        .arg("*/version_*.csv")
-        .arg("--exclude")
-        .arg("*.sql")
-        .arg("--exclude")
-        .arg("*.json")
-        .arg("--exclude")
-        .arg("*.md")
-        .arg("-C")
-        .arg(temp_dir.path().to_str().unwrap())
-        .output()
-        .expect("Unable to fetch Crates database dump");
+    Ok(temp_dir)
+}
 
-    println!(
-        "Unpacked database dump TAR archive in {} seconds.",
-        unpack_start.elapsed().as_secs_f64()
-    );
+/// Unpacks a tarballed crates.io database dump on the local filesystem into a temporary directory.
+///
+/// Returns the temporary directory containing the dump data.
+///
+/// # Arguments
+/// * `tgz_path` - the path of the tarballed database dump.
+/// * `recorder` - the `LoadRecorder` to record loading phases with.
+///
+/// # Errors
+/// * Returns a `LoadError` if the dump cannot be unpacked.
+pub fn unpack_data(tgz_path: &Path, recorder: &LoadRecorder) -> Result<TempDir, LoadError> {
+    let temp_dir = create_tempdir()?;
+    unpack_tarball(tgz_path, &temp_dir, recorder)?;
 
-    println!(
-        "Finished fetching data in {} seconds.",
-        fetch_start.elapsed().as_secs_f64()
-    );
+    Ok(temp_dir)
+}
 
-    temp_dir
+/// Creates a temporary directory to unpack a database dump into.
+///
+/// # Errors
+/// * Returns a `LoadError` if the directory cannot be created.
+fn create_tempdir() -> Result<TempDir, LoadError> {
+    TempDir::new()
+        .map_err(|err| LoadError::Fetch(format!("unable to create temporary directory: {err}")))
+}
+
+/// Echoes the output of a command run while fetching a dump, checking that it succeeded.
+///
+/// # Arguments
+/// * `program` - the name of the program which was run.
+/// * `output` - the result of running the program.
+///
+/// # Errors
+/// * Returns a `LoadError` if the program could not be run or exited unsuccessfully.
+fn check_output(program: &str, output: io::Result<Output>) -> Result<(), LoadError> {
+    let output =
+        output.map_err(|err| LoadError::Fetch(format!("unable to run {program}: {err}")))?;
+
+    // echoing is best effort, as the outcome is reported either way
+    let _ = io::stdout().write_all(&output.stdout);
+    let _ = io::stderr().write_all(&output.stderr);
+
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(LoadError::Fetch(format!(
+            "{program} exited with {}: {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )))
+    }
+}
+
+/// Unpacks a tarballed crates.io database dump into a directory.
+///
+/// # Arguments
+/// * `tgz_path` - the path of the tarballed database dump.
+/// * `temp_dir` - the `TempDir` to unpack the dump into.
+/// * `recorder` - the `LoadRecorder` to record loading phases with.
+///
+/// # Errors
+/// * Returns a `LoadError` if `tar` cannot be run or fails to unpack the dump.
+fn unpack_tarball(
+    tgz_path: &Path,
+    temp_dir: &TempDir,
+    recorder: &LoadRecorder,
+) -> Result<(), LoadError> {
+    recorder.time("unpack", || {
+        // exclude files not included in loading process
+        let tar_output = Command::new("tar")
+            .arg("-xzf")
+            .arg(tgz_path)
+            .arg("--exclude")
+            .arg("*/badges.csv")
+            .arg("--exclude")
+            .arg("*/crate_owners.csv")
+            .arg("--exclude")
+            .arg("*/metadata.csv")
+            .arg("--exclude")
+            .arg("*/reserved_crate_names.csv")
+            .arg("--exclude")
+            .arg("*/teams.csv")
+            .arg("--exclude")
+            .arg("*/users.csv")
+            .arg("--exclude")
+            .arg("*/version_*.csv")
+            .arg("--exclude")
+            .arg("*.sql")
+            .arg("--exclude")
+            .arg("*.json")
+            .arg("--exclude")
+            .arg("*.md")
+            .arg("-C")
+            .arg(temp_dir.path())
+            .output();
+        check_output("tar", tar_output)
+    })
 }
 
 /// Cleans up a temporary directory.
 ///
 /// # Arguments
 /// * `temp_dir` - the `TempDir` to clean up.
-pub fn clean_tempdir(temp_dir: TempDir) {
-    println!("Cleaning up temporary files and directories...");
-    let clean_start = Instant::now();
-    temp_dir
-        .close()
-        .unwrap_or_else(|_| panic!("Unable to close temporary directory"));
-    println!(
-        "Temporary files and directories removed in {} seconds.",
-        clean_start.elapsed().as_secs_f64()
-    );
+/// * `recorder` - the `LoadRecorder` to record loading phases with.
+///
+/// # Errors
+/// * Returns a `LoadError` if the directory cannot be removed.
+pub fn clean_tempdir(temp_dir: TempDir, recorder: &LoadRecorder) -> Result<(), LoadError> {
+    recorder.time("clean up", || {
+        temp_dir
+            .close()
+            .map_err(|err| LoadError::Fetch(format!("unable to close temporary directory: {err}")))
+    })
 }
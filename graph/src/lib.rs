@@ -1,25 +1,74 @@
 #![warn(clippy::all, clippy::pedantic, clippy::nursery)]
 #![allow(clippy::cast_precision_loss, clippy::missing_panics_doc)]
 
+mod cache;
+mod centrality;
+mod compact;
+mod config;
+mod cycles;
+mod dominators;
+mod export;
+mod features;
+mod footprint;
 mod fs;
 mod load;
+mod lockfile;
+mod manifest;
+mod metadata;
+mod query;
+mod sbom;
 mod schema;
 mod traits;
 
 use ahash::{AHashMap, AHashSet};
-use cargo_platform::{Cfg, Platform};
+use cargo_platform::Cfg;
 use chrono::NaiveDateTime;
+use compact::{
+    CompactDependency, CompactGraph, CompactRoot, CompactView, FeatureAction, Interner,
+    DEFAULT_FEATURE,
+};
+use load::{LoadRecorder, RegistryData};
 use serde::Serialize;
 use std::{
-    collections::{BTreeMap, BTreeSet, VecDeque},
+    cell::Cell,
+    collections::{hash_map::Entry, BTreeMap, BTreeSet, VecDeque},
     fs::File,
     process::Command,
     str::{self, FromStr},
-    time::Instant,
+    time::{Duration, Instant},
+};
+use tempfile::TempDir;
+
+pub use cache::{CacheStats, DependencyGraphCache, DEFAULT_CACHE_CAPACITY};
+pub use compact::CrateMap;
+pub use config::{DataSource, GraphConfig, DEFAULT_CFG_NAME, DEFAULT_DUMP_URL, DEFAULT_TARGET};
+pub use cycles::CrateCycle;
+pub use dominators::CrateDominator;
+pub use export::{GraphFormat, UnknownGraphFormat};
+pub use features::{CrateEdge, FeatureChange, FeatureCost, FeatureCostDelta, FeatureSuggestion};
+pub use footprint::FootprintLeaderboard;
+pub use load::{LoadError, LoadPhase, LoadProgress, LoadReport, LoadStatus};
+pub use lockfile::{
+    DuplicatePackage, LockedCrate, LockedDependencyEdge, LockedPackage, LockedSource, Lockfile,
+    LockfileAnalysis, LockfileError, OutdatedPackage, RegistryMetadata,
 };
+pub use manifest::ManifestError;
+pub use metadata::{FeatureMismatch, Metadata, MetadataComparison, MetadataEdge, MetadataError};
+pub use query::{
+    DependencyGraphQuery, DependencyGraphQueryError, DependencyGraphRoot, QueryFieldError,
+};
+pub use sbom::{SbomFormat, SbomInfo, UnknownSbomFormat};
+pub use schema::{Category, Crate, Dependency, DependencyKind, Keyword};
+pub use traits::{Random, Search, SearchRanking, VertexMap};
+
+/// A function called with each computed dependency graph and the time taken to compute it.
+pub type DependencyGraphObserver = Box<dyn Fn(&DependencyGraph, Duration) + Send + Sync>;
 
-pub use schema::{Category, Crate, Dependency, Keyword};
-pub use traits::{Random, Search};
+/// The number of loading phases recorded while computing data derived from the registry.
+const GRAPH_PHASE_COUNT: usize = 3;
+
+/// The max number of potential dependencies permitted in a `DependencyGraph`.
+const MAX_POTENTIAL_DEPENDENCIES: usize = 500;
 
 /// A struct containing information about the crates.io registry.
 pub struct Graph {
@@ -29,14 +78,25 @@ pub struct Graph {
     /// A set of `Category` names for searching.
     category_names: BTreeSet<String>,
 
-    /// A set of cfg names (e.g. `unix`, `cargo_web`) present among all dependencies.
+    /// A set of cfg names (e.g. `unix`, `cargo_web`) present among all normal and build dependencies.
     cfg_names: BTreeSet<String>,
 
-    /// A mapping of `Crate` names to values.
-    crates: AHashMap<String, Crate>,
+    /// The crates and their dependencies and features by integer ids.
+    ///
+    /// This backs both name-based lookups and the traversal of dependency graphs.
+    compact: CompactGraph,
+
+    /// A cache of serialized dependency graphs by normalized query.
+    dependency_graph_cache: DependencyGraphCache,
+
+    /// A function called with each computed dependency graph, if set.
+    dependency_graph_observer: Option<DependencyGraphObserver>,
 
-    /// A set of `Crate` names for searching.
-    crate_names: BTreeSet<String>,
+    /// The additional cfg name enabled when a query does not specify one.
+    default_cfg_name: String,
+
+    /// The target triple used when a query does not specify one.
+    default_target: String,
 
     /// A set of `Keyword` names for searching.
     keywords: AHashMap<String, Keyword>,
@@ -47,6 +107,9 @@ pub struct Graph {
     /// The time at which the `Graph` was last updated.
     last_updated: Instant,
 
+    /// The time taken by each phase of loading the `Graph`.
+    load_report: LoadReport,
+
     /// A mapping of rustc-supported targets to cfg attributes.
     targets: BTreeMap<String, Vec<Cfg>>,
 }
@@ -55,31 +118,64 @@ impl Graph {
     /// Creates a new `Graph`.
     ///
     /// This pulls in the latest crates.io dump and is intended for production use.
-    #[must_use]
-    pub fn new() -> Self {
-        let temp_dir = fs::fetch_data();
-
-        let data_path = fs::get_data_path(&temp_dir).unwrap_or_else(|| {
-            panic!(
-                "Unable to find data path in {}.",
-                temp_dir.path().display().to_string()
-            )
-        });
+    ///
+    /// # Errors
+    /// * Returns a `LoadError` if the dump cannot be fetched or loaded.
+    pub fn new() -> Result<Self, LoadError> {
+        Self::from_config(&GraphConfig::default())
+    }
 
-        let (categories, crates, keywords) = load::get_data(data_path.as_str());
-        fs::clean_tempdir(temp_dir);
+    /// Creates a new `Graph` with the given `GraphConfig`.
+    ///
+    /// # Arguments
+    /// * `config` - the `GraphConfig` specifying the database dump to load and the resolution defaults.
+    ///
+    /// # Errors
+    /// * Returns a `LoadError` if the dump cannot be fetched or loaded.
+    pub fn from_config(config: &GraphConfig) -> Result<Self, LoadError> {
+        Self::from_config_with_progress(config, &LoadProgress::default())
+    }
 
-        Self {
-            category_names: get_names(&categories),
-            categories,
-            cfg_names: get_cfg_names(&crates),
-            crate_names: get_names(&crates),
-            crates,
-            keyword_names: get_names(&keywords),
-            keywords,
-            last_updated: Instant::now(),
-            targets: load::get_targets(env!("VAULT_TARGETS_PATH")),
-        }
+    /// Creates a new `Graph` with the given `GraphConfig`, reporting the progress of loading as it goes.
+    ///
+    /// # Arguments
+    /// * `config` - the `GraphConfig` specifying the database dump to load and the resolution defaults.
+    /// * `progress` - the `LoadProgress` to report to.
+    ///
+    /// # Errors
+    /// * Returns a `LoadError` if the dump cannot be fetched or loaded.
+    pub fn from_config_with_progress(
+        config: &GraphConfig,
+        progress: &LoadProgress,
+    ) -> Result<Self, LoadError> {
+        // downloading, unpacking and cleaning up run before and after the phases of `load::get_data`
+        let fetch_phase_count = match config.source {
+            DataSource::Remote(_) => 3,
+            DataSource::Dump(_) => 2,
+            DataSource::Snapshot(_) => 0,
+        };
+        let recorder = LoadRecorder::new(
+            progress.clone(),
+            fetch_phase_count + load::DATA_PHASE_COUNT + GRAPH_PHASE_COUNT,
+        );
+
+        let data = match &config.source {
+            DataSource::Remote(url) => load_unpacked(fs::fetch_data(url, &recorder)?, &recorder),
+            DataSource::Dump(tgz_path) => {
+                load_unpacked(fs::unpack_data(tgz_path, &recorder)?, &recorder)
+            }
+            DataSource::Snapshot(data_path) => load::get_data(
+                data_path.to_str().ok_or_else(|| {
+                    LoadError::Read(
+                        data_path.display().to_string(),
+                        String::from("path is not valid UTF-8"),
+                    )
+                })?,
+                &recorder,
+            ),
+        }?;
+
+        Self::from_data(data, recorder, config)
     }
 
     /// Creates a new `Graph`.
@@ -99,24 +195,73 @@ impl Graph {
                 .unwrap();
         }
 
-        let (categories, crates, keywords) = load::get_data(data_path);
+        let recorder = LoadRecorder::new(
+            LoadProgress::default(),
+            load::DATA_PHASE_COUNT + GRAPH_PHASE_COUNT,
+        );
+        let data = load::get_data(data_path, &recorder).expect("Unable to load test data");
 
-        Self {
+        Self::from_data(data, recorder, &GraphConfig::default()).expect("Unable to load test data")
+    }
+
+    /// Creates a new `Graph` from loaded categories, crates, and keywords, computing derived data.
+    ///
+    /// # Arguments
+    /// * `data` - the categories, crates, and keywords loaded from a database dump.
+    /// * `recorder` - the `LoadRecorder` with which loading has been recorded so far.
+    /// * `config` - the `GraphConfig` specifying the resolution defaults.
+    ///
+    /// # Errors
+    /// * Returns a `LoadError` if the targets cannot be loaded.
+    fn from_data(
+        (categories, crates, keywords): RegistryData,
+        recorder: LoadRecorder,
+        config: &GraphConfig,
+    ) -> Result<Self, LoadError> {
+        let targets = load::get_targets(env!("VAULT_TARGETS_PATH"))?;
+
+        let compact = recorder.time_items("index", || {
+            let compact = CompactGraph::new(crates);
+            let count = compact.crate_count();
+            (compact, count)
+        });
+        let cfg_names = recorder.time_items("cfg names", || {
+            let cfg_names = get_cfg_names(&compact);
+            let count = cfg_names.len();
+            (cfg_names, count)
+        });
+
+        let mut graph = Self {
             category_names: get_names(&categories),
             categories,
-            cfg_names: get_cfg_names(&crates),
-            crate_names: get_names(&crates),
-            crates,
+            cfg_names,
+            compact,
+            dependency_graph_cache: DependencyGraphCache::new(config.cache_capacity),
+            dependency_graph_observer: None,
+            default_cfg_name: config.default_cfg_name.clone(),
+            default_target: config.default_target.clone(),
             keyword_names: get_names(&keywords),
             keywords,
             last_updated: Instant::now(),
-            targets: load::get_targets(env!("VAULT_TARGETS_PATH")),
-        }
+            load_report: LoadReport::default(),
+            targets,
+        };
+
+        recorder.time_items("footprints", || {
+            graph.assign_footprints();
+            ((), graph.crates().len())
+        });
+
+        graph.load_report = recorder.finish();
+        Ok(graph)
     }
 
     /// Updates the `last_updated` time to the current time.
+    ///
+    /// Cached dependency graphs are discarded, as they may no longer reflect the registry.
     pub fn update_time(&mut self) {
         self.last_updated = Instant::now();
+        self.dependency_graph_cache.clear();
     }
 
     /// Returns an immutable reference to the `Category` map.
@@ -133,8 +278,8 @@ impl Graph {
 
     /// Returns an immutable reference to the `Crate` map.
     #[must_use]
-    pub const fn crates(&self) -> &AHashMap<String, Crate> {
-        &self.crates
+    pub const fn crates(&self) -> &CrateMap {
+        self.compact.crates()
     }
 
     /// Returns an immutable reference to the `Keyword` map.
@@ -150,9 +295,11 @@ impl Graph {
     }
 
     /// Returns an immutable reference to the `Crate` name set.
+    ///
+    /// This is the `Crate` map itself, as crates are stored in alphabetical order.
     #[must_use]
-    pub const fn crate_names(&self) -> &BTreeSet<String> {
-        &self.crate_names
+    pub const fn crate_names(&self) -> &CrateMap {
+        self.compact.crates()
     }
 
     /// Returns an immutable reference to the `Keyword` name set.
@@ -167,12 +314,37 @@ impl Graph {
         &self.targets
     }
 
+    /// Returns the requested features which are not exposed by the specified crate, explicitly or as optional
+    /// dependencies.
+    ///
+    /// If no crate matches the specified name, returns `None`.
+    ///
+    /// # Arguments
+    /// * `crate_id` - the name of the crate to check.
+    /// * `features` - the list of features to validate.
+    #[must_use]
+    pub fn get_unknown_features(&self, crate_id: &str, features: &[String]) -> Option<Vec<String>> {
+        self.crates().get(crate_id).map(|crate_val| {
+            features
+                .iter()
+                .filter(|feature_name| !crate_val.features.contains_key(*feature_name))
+                .cloned()
+                .collect()
+        })
+    }
+
     /// Returns the time since the `Graph` was last updated in seconds.
     #[must_use]
     pub fn time_since_last_update(&self) -> u64 {
         self.last_updated.elapsed().as_secs()
     }
 
+    /// Returns the time taken by each phase of loading the `Graph`.
+    #[must_use]
+    pub const fn load_report(&self) -> &LoadReport {
+        &self.load_report
+    }
+
     /// Returns the dependency graph of the specified crate with the specified features enabled.
     ///
     /// If no crate matches the specified name, returns `None`.
@@ -180,272 +352,587 @@ impl Graph {
     /// # Arguments
     /// * `crate_id` - the name of the crate to analyze.
     /// * `features` - the list of features to enable.
+    /// * `target` - the target triple to resolve platform-specific dependencies for.
+    /// * `cfg_name` - an additional cfg name to enable when resolving platform-specific dependencies.
+    /// * `options` - the `DependencyGraphOptions` controlling how the root crate's features are selected.
     #[must_use]
     pub fn get_dependency_graph(
         &self,
         crate_id: &str,
-        mut features: Vec<String>,
+        features: Vec<String>,
         target: &Option<String>,
         cfg_name: &Option<String>,
-    ) -> Option<DependencyGraph> {
-        self.crates().get(crate_id).map(|crate_val| {
-            // a list of crate names and distances from the root crate
-            let mut crate_distance_vec: Vec<(&String, usize)> = vec![];
-            // a map of crates seen and which features have already been enabled for them
-            let mut crates_seen: AHashMap<&String, Vec<String>> = AHashMap::new();
-
-            // a list of dependencies to return
-            let mut dependencies: Vec<&Dependency> = vec![];
-            // a set of dependencies seen so far by source and destination name
-            let mut dependencies_seen: AHashSet<(String, String)> = AHashSet::new();
-            // the queue of dependnencies to process.
-            let mut dependency_queue: VecDeque<QueueDependency> = VecDeque::new();
-
-            let target = String::from(
-                target
-                    .as_ref()
-                    .map_or("x86_64-unknown-linux-gnu", |target| target),
-            );
+        options: &DependencyGraphOptions,
+    ) -> Option<DependencyGraph<'_>> {
+        self.query_dependency_graph(&DependencyGraphQuery::new(
+            crate_id, features, target, cfg_name, options,
+        ))
+    }
 
-            let cfg_name = Cfg::from_str(
-                cfg_name
-                    .as_ref()
-                    .map_or("unix", |cfg_name| cfg_name.as_str()),
-            )
-            .unwrap();
+    /// Returns the dependency graph described by a `DependencyGraphQuery`.
+    ///
+    /// If any root crate is not found in the registry, returns `None`. Excluded crates not found in the registry are
+    /// ignored.
+    ///
+    /// # Arguments
+    /// * `query` - the `DependencyGraphQuery` describing the root crates and how to resolve their dependencies.
+    #[must_use]
+    pub fn query_dependency_graph(
+        &self,
+        query: &DependencyGraphQuery,
+    ) -> Option<DependencyGraph<'_>> {
+        let roots = query
+            .roots
+            .iter()
+            .map(|root| {
+                self.compact
+                    .crate_id(&root.name)
+                    .map(|root_id| (root_id, root))
+            })
+            .collect::<Option<Vec<(u32, &DependencyGraphRoot)>>>()?;
+
+        let context = self.traversal_context(query, &roots, None);
+
+        let started_at = Instant::now();
+        let dependency_graph = self.resolve_dependency_graph(
+            &roots,
+            None,
+            FeatureNames::new(self.compact.feature_names()),
+            &context,
+            query.potential_dependencies,
+        );
+        self.observe_dependency_graph(&dependency_graph, started_at);
+
+        Some(dependency_graph)
+    }
+
+    /// Returns the dependency graph of a crate outside the registry, such as one built from a manifest, with the
+    /// specified features enabled.
+    ///
+    /// The crate is only visible to this dependency graph, so it may share its name with a crate in the registry.
+    ///
+    /// If any dependency of the crate is not found in the registry, returns `None`.
+    ///
+    /// # Arguments
+    /// * `manifest` - the `Crate` to analyze, with dependencies sorted by destination.
+    /// * `features` - the list of features to enable.
+    /// * `target` - the target triple to resolve platform-specific dependencies for.
+    /// * `cfg_name` - an additional cfg name to enable when resolving platform-specific dependencies.
+    /// * `options` - the `DependencyGraphOptions` controlling how the root crate's features are selected.
+    #[must_use]
+    pub fn get_manifest_dependency_graph<'a>(
+        &'a self,
+        manifest: &'a Crate,
+        features: Vec<String>,
+        target: &Option<String>,
+        cfg_name: &Option<String>,
+        options: &DependencyGraphOptions,
+    ) -> Option<DependencyGraph<'a>> {
+        if !self.get_unknown_dependencies(manifest).is_empty() {
+            return None;
+        }
+
+        let query = DependencyGraphQuery::new(&manifest.name, features, target, cfg_name, options);
+        let roots = [(
+            u32::try_from(self.compact.crate_count()).unwrap(),
+            &query.roots[0],
+        )];
+
+        let mut feature_names = FeatureNames::new(self.compact.feature_names());
+        let root = CompactRoot::new(manifest, &self.compact, |feature_name| {
+            feature_names.id(feature_name)
+        });
+        let context = self.traversal_context(&query, &roots, Some(&root));
+
+        let started_at = Instant::now();
+        let dependency_graph = self.resolve_dependency_graph(
+            &roots,
+            Some(manifest),
+            feature_names,
+            &context,
+            query.potential_dependencies,
+        );
+        self.observe_dependency_graph(&dependency_graph, started_at);
+
+        Some(dependency_graph)
+    }
+
+    /// Sets a function to call with each computed dependency graph and the time taken to compute it.
+    ///
+    /// Dependency graphs served from the cache are not computed, so the function is not called for them.
+    ///
+    /// # Arguments
+    /// * `observer` - the function to call.
+    pub fn set_dependency_graph_observer(
+        &mut self,
+        observer: impl Fn(&DependencyGraph, Duration) + Send + Sync + 'static,
+    ) {
+        self.dependency_graph_observer = Some(Box::new(observer));
+    }
+
+    /// Calls the dependency graph observer, if set, with a computed dependency graph.
+    ///
+    /// # Arguments
+    /// * `dependency_graph` - the computed `DependencyGraph`.
+    /// * `started_at` - the time at which computing the dependency graph started.
+    fn observe_dependency_graph(&self, dependency_graph: &DependencyGraph, started_at: Instant) {
+        if let Some(observer) = &self.dependency_graph_observer {
+            observer(dependency_graph, started_at.elapsed());
+        }
+    }
+
+    /// Returns the dependencies of a crate outside the registry which are not found in the registry.
+    ///
+    /// # Arguments
+    /// * `manifest` - the `Crate` to check.
+    #[must_use]
+    pub fn get_unknown_dependencies(&self, manifest: &Crate) -> Vec<String> {
+        let mut unknown_dependencies: Vec<String> = vec![];
+
+        for dependency in &manifest.dependencies {
+            if !self.crates().contains_key(&dependency.to)
+                && !unknown_dependencies.contains(&dependency.to)
+            {
+                unknown_dependencies.push(dependency.to.clone());
+            }
+        }
+
+        unknown_dependencies
+    }
+
+    /// Constructs the dependency graph of one or more root crates with the specified features enabled.
+    ///
+    /// # Arguments
+    /// * `roots` - the ids of the root crates and the features selected for them.
+    /// * `manifest` - the `Crate` outside the registry, if it is the root crate.
+    /// * `feature_names` - the `FeatureNames` of the dependency graph.
+    /// * `context` - the `TraversalContext` of the dependency graph.
+    /// * `potential_dependencies` - the maximum number of inactive optional dependencies to include, if any.
+    fn resolve_dependency_graph<'a>(
+        &'a self,
+        roots: &[(u32, &DependencyGraphRoot)],
+        manifest: Option<&'a Crate>,
+        mut feature_names: FeatureNames,
+        context: &TraversalContext,
+        potential_dependencies: Option<usize>,
+    ) -> DependencyGraph<'a> {
+        // a list of crate ids and distances from the root crate
+        let mut crate_distance_vec: Vec<(u32, usize)> = vec![];
+        // a map of crates seen per compile kind and which features have already been enabled for them
+        let mut crates_seen: AHashMap<(u32, CompileKind), Vec<u32>> = AHashMap::new();
+
+        // a list of source crate ids and dependency declarations to return
+        let mut dependencies: Vec<(u32, QueueDeclaration)> = vec![];
+        // a map of dependency declarations seen so far by source id and declaration index to list indices
+        let mut dependencies_seen: AHashMap<(u32, u32), usize> = AHashMap::new();
+        // the queue of dependnencies to process.
+        let mut dependency_queue: VecDeque<QueueDependency> = VecDeque::new();
+
+        for &(root_id, root) in roots {
+            let mut features = root.features.clone();
+
+            // enable every feature of the root crate if requested
+            if root.all_features {
+                append_all_features(self.crate_val(root_id, manifest), &mut features);
+            }
+
+            let mut feature_ids: Vec<u32> = features
+                .iter()
+                .map(|feature_name| feature_names.id(feature_name))
+                .collect();
 
             // insert the root crate
-            crate_distance_vec.push((&crate_val.name, 0));
-            crates_seen.insert(&crate_val.name, features.clone());
-            features.push(String::from("default"));
+            crate_distance_vec.push((root_id, 0));
+            crates_seen.insert((root_id, CompileKind::Target), feature_ids.clone());
+
+            if root.default_features && !feature_ids.contains(&DEFAULT_FEATURE) {
+                feature_ids.push(DEFAULT_FEATURE);
+            }
 
             // add root crate dependendencies to the queue
-            self.dependency_graph_helper(
-                crate_val,
-                features,
+            context.dependency_graph_helper(
+                root_id,
+                feature_ids,
                 &mut dependency_queue,
                 0,
-                &target,
-                &cfg_name,
+                CompileKind::Target,
             );
+        }
 
-            // while the queue is not empty
-            while let Some(QueueDependency {
-                from,
-                to,
-                mut to_feature_names,
-                to_distance,
-            }) = dependency_queue.pop_front()
-            {
-                let from_crate_val = self.crates.get(&from).unwrap();
-                let to_crate_val = self.crates.get(&to).unwrap();
-                let dependency_tuple = (from_crate_val.name.clone(), to_crate_val.name.clone());
-
-                // add dependency to list and set if not seen yet
-                if !dependencies_seen.contains(&dependency_tuple) {
-                    dependencies.push(
-                        from_crate_val
-                            .dependencies
-                            .iter()
-                            .find(|dependency| dependency.to == to)
-                            .unwrap(),
-                    );
-
-                    dependencies_seen.insert(dependency_tuple);
-                }
-
-                if let Some(crate_feature_names) = crates_seen.get_mut(&to_crate_val.name) {
-                    // if crate has been seen
+        // while the queue is not empty
+        while let Some(QueueDependency {
+            from,
+            to,
+            declarations,
+            mut to_feature_ids,
+            to_distance,
+            to_compile_kind,
+        }) = dependency_queue.pop_front()
+        {
+            if !context.includes(to, to_distance) {
+                continue;
+            }
 
-                    // remove already seen features
-                    to_feature_names.retain(|dependency_feature_name| {
-                        !crate_feature_names.contains(dependency_feature_name)
-                    });
+            add_dependency_edges(
+                from,
+                declarations,
+                &mut dependencies,
+                &mut dependencies_seen,
+            );
 
-                    if !to_feature_names.is_empty() {
-                        // but has features that haven't been enabled yet
-                        // add dependencies to queue
-                        self.dependency_graph_helper(
-                            to_crate_val,
-                            to_feature_names.clone(),
-                            &mut dependency_queue,
-                            to_distance,
-                            &target,
-                            &cfg_name,
-                        );
-                    }
+            if let Some(crate_feature_ids) = crates_seen.get_mut(&(to, to_compile_kind)) {
+                // if crate has been seen for this compile kind
 
-                    crate_feature_names.append(&mut to_feature_names);
-                } else {
-                    // add crate to list and map
-                    crate_distance_vec.push((&to_crate_val.name, to_distance));
-                    crates_seen.insert(&to_crate_val.name, to_feature_names.clone());
+                // remove already seen features
+                to_feature_ids.retain(|feature_id| !crate_feature_ids.contains(feature_id));
 
-                    // add crate dependencies to queue
-                    self.dependency_graph_helper(
-                        to_crate_val,
-                        to_feature_names,
+                if !to_feature_ids.is_empty() {
+                    // but has features that haven't been enabled yet
+                    // add dependencies to queue
+                    context.dependency_graph_helper(
+                        to,
+                        to_feature_ids.clone(),
                         &mut dependency_queue,
                         to_distance,
-                        &target,
-                        &cfg_name,
+                        to_compile_kind,
                     );
                 }
-            }
 
-            DependencyGraph {
-                crates: crate_distance_vec
-                    .iter()
-                    .map(|&(crate_id, distance)| {
-                        let mut enabled_features = crates_seen.get(crate_id).unwrap().clone();
-                        enabled_features.retain(|feature_name| feature_name != "default");
+                crate_feature_ids.append(&mut to_feature_ids);
+            } else {
+                // add crate to list if it has not been seen for any compile kind
+                if !crates_seen.contains_key(&(to, to_compile_kind.other())) {
+                    crate_distance_vec.push((to, to_distance));
+                }
 
-                        CrateDistance::new(
-                            CrateDistanceInfo {
-                                crate_id,
-                                distance,
-                                enabled_features,
-                            },
-                            &self.crates,
-                        )
-                    })
-                    .collect(),
-                dependencies,
+                crates_seen.insert((to, to_compile_kind), to_feature_ids.clone());
+
+                // add crate dependencies to queue
+                context.dependency_graph_helper(
+                    to,
+                    to_feature_ids,
+                    &mut dependency_queue,
+                    to_distance,
+                    to_compile_kind,
+                );
             }
-        })
+        }
+
+        // add inactive optional dependencies if requested
+        let potential_dependencies = self.get_potential_dependencies(
+            &crate_distance_vec,
+            &crates_seen,
+            &dependencies_seen,
+            manifest,
+            &feature_names,
+            context,
+            potential_dependencies,
+        );
+
+        DependencyGraph {
+            crates: crate_distance_vec
+                .iter()
+                .map(|&(crate_id, distance)| {
+                    let crate_val = self.crate_val(crate_id, manifest);
+
+                    CrateDistance::from_crate(
+                        get_crate_distance_info(
+                            &crate_val.name,
+                            get_crate_features(crate_id, &crates_seen, &feature_names),
+                            distance,
+                            context,
+                        ),
+                        crate_val,
+                    )
+                })
+                .collect(),
+            dependencies: self.get_dependency_edges(dependencies, manifest, &feature_names),
+            potential_dependencies,
+        }
     }
 
-    /// A helper function to construct the dependency graph.
-    ///
-    /// Adds all relevant dependencies of a crate into the dependency queue for processing.
+    /// Returns the `Crate` with the specified id.
     ///
     /// # Arguments
-    /// * `crate_val` - the `Crate` being examined.
-    /// * `feature_names` - the list of enabled features.
-    /// * `dependency_queue` - the queue of dependencies to process.
-    /// * `distance` - the distance from the root crate.
-    fn dependency_graph_helper(
-        &self,
-        crate_val: &Crate,
-        mut feature_names: Vec<String>,
-        dependency_queue: &mut VecDeque<QueueDependency>,
-        distance: usize,
-        target: &str,
-        cfg_name: &Cfg,
-    ) {
-        // dependencies included in traversal
-        let mut dependencies_to_check: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    /// * `crate_id` - the id of the crate.
+    /// * `manifest` - the `Crate` outside the registry assigned the id past those of registry crates, if any.
+    fn crate_val<'a>(&'a self, crate_id: u32, manifest: Option<&'a Crate>) -> &'a Crate {
+        match manifest {
+            Some(manifest) if crate_id as usize == self.compact.crate_count() => manifest,
+            _ => self.crates().crate_val(crate_id),
+        }
+    }
 
-        // add mandatory dependencies
-        for dependency in &crate_val.dependencies {
-            if !dependency.optional {
-                dependencies_to_check.insert(dependency.to.clone(), dependency.features.clone());
+    /// Returns a dependency declaration of a crate as an edge of a dependency graph.
+    ///
+    /// # Arguments
+    /// * `crate_id` - the id of the crate declaring the dependency.
+    /// * `index` - the index of the declaration among the crate's dependencies.
+    /// * `manifest` - the `Crate` outside the registry assigned the id past those of registry crates, if any.
+    /// * `features` - the features requested of the destination crate through the declaration.
+    /// * `activated_by` - the features of the source crate which activated the declaration.
+    fn declaration_edge<'a>(
+        &'a self,
+        crate_id: u32,
+        index: u32,
+        manifest: Option<&'a Crate>,
+        features: Vec<String>,
+        activated_by: Vec<String>,
+    ) -> DependencyEdge<'a> {
+        match manifest {
+            Some(manifest) if crate_id as usize == self.compact.crate_count() => {
+                DependencyEdge::new(
+                    &manifest.dependencies[index as usize],
+                    features,
+                    activated_by,
+                )
             }
+            _ => DependencyEdge::from_compact(
+                &self.compact,
+                crate_id,
+                &self.compact.dependencies(crate_id)[index as usize],
+                features,
+                activated_by,
+            ),
         }
+    }
 
-        let default_string = String::from("default");
-        let default_features_enabled = feature_names.contains(&default_string);
-        let default_features = crate_val
-            .features
-            .get(&default_string)
-            .map_or_else(Vec::new, Clone::clone);
-
-        // add dependencies enabled by features
-        for (feature_name, feature_dependencies) in &crate_val.features {
-            if feature_name != "default"
-                && (feature_names.contains(feature_name)
-                    || (default_features_enabled && default_features.contains(feature_name)))
-            {
-                for feature_dependency in feature_dependencies {
-                    if crate_val.features.contains_key(feature_dependency) {
-                        // if feature enables another feature
-                        if !feature_names.contains(feature_dependency) {
-                            // if the enabled feature is not already included
-                            feature_names.push(feature_dependency.clone());
-                        }
-                    } else if let Some(slash_index) = feature_dependency.find('/') {
-                        // if features enabled
-                        let feature_dependency_name =
-                            String::from(&feature_dependency[..slash_index]);
+    /// Returns the dependency declarations of the specified crate as edges, each requesting the features listed in
+    /// the declaration.
+    ///
+    /// If no crate matches the specified name, returns `None`.
+    ///
+    /// # Arguments
+    /// * `crate_id` - the name of the crate.
+    #[must_use]
+    pub fn get_dependencies(&self, crate_id: &str) -> Option<Vec<DependencyEdge<'_>>> {
+        self.crates()
+            .get(crate_id)
+            .map(|crate_val| self.declarations(crate_val))
+    }
 
-                        if crate_val
-                            .dependencies
+    /// Returns the dependency declarations of a crate in the registry or outside it as edges, each requesting the
+    /// features listed in the declaration.
+    ///
+    /// # Arguments
+    /// * `crate_val` - the `Crate` declaring the dependencies.
+    fn declarations<'a>(&'a self, crate_val: &'a Crate) -> Vec<DependencyEdge<'a>> {
+        self.crates().id_of(crate_val).map_or_else(
+            || {
+                crate_val
+                    .dependencies
+                    .iter()
+                    .map(|dependency| {
+                        DependencyEdge::new(dependency, dependency.features.clone(), vec![])
+                    })
+                    .collect()
+            },
+            |crate_id| {
+                self.compact
+                    .dependencies(crate_id)
+                    .iter()
+                    .map(|dependency| {
+                        let features = self
+                            .compact
+                            .requested_features(dependency)
                             .iter()
-                            .any(|dependency| dependency.to == feature_dependency_name)
-                        {
-                            let feature_dependency_transitive_feature =
-                                String::from(&feature_dependency[slash_index + 1..]);
-
-                            // if dependency already added, add feature if feature was not added
-                            // otherwise add dependency and feature
-                            dependencies_to_check
-                                .entry(feature_dependency_name)
-                                .and_modify(|dependency_feature_list| {
-                                    if !dependency_feature_list
-                                        .contains(&feature_dependency_transitive_feature)
-                                    {
-                                        dependency_feature_list
-                                            .push(feature_dependency_transitive_feature);
-                                    }
-                                })
-                                .or_insert_with(|| {
-                                    vec![String::from(&feature_dependency[slash_index + 1..])]
-                                });
-                        }
-                    } else if crate_val
-                        .dependencies
-                        .iter()
-                        .any(|dependency| dependency.to == *feature_dependency)
-                    {
-                        // if features not enabled, insert dependency if not already present
-                        dependencies_to_check
-                            .entry(feature_dependency.clone())
-                            .or_insert_with(Vec::new);
-                    }
-                }
-            }
+                            .map(|&feature_id| {
+                                self.compact.feature_names().name(feature_id).clone()
+                            })
+                            .collect();
+
+                        DependencyEdge::from_compact(
+                            &self.compact,
+                            crate_id,
+                            dependency,
+                            features,
+                            vec![],
+                        )
+                    })
+                    .collect()
+            },
+        )
+    }
+
+    /// Returns the edges of a dependency graph from the enabled dependency declarations.
+    ///
+    /// # Arguments
+    /// * `dependencies` - the list of source crate ids and declarations.
+    /// * `manifest` - the `Crate` outside the registry, if it is the root crate.
+    /// * `feature_names` - the `FeatureNames` of the dependency graph.
+    fn get_dependency_edges<'a>(
+        &'a self,
+        dependencies: Vec<(u32, QueueDeclaration)>,
+        manifest: Option<&'a Crate>,
+        feature_names: &FeatureNames,
+    ) -> Vec<DependencyEdge<'a>> {
+        dependencies
+            .into_iter()
+            .map(|(from, declaration)| {
+                self.declaration_edge(
+                    from,
+                    declaration.index,
+                    manifest,
+                    feature_names.names(&declaration.features),
+                    feature_names.names(&declaration.activated_by),
+                )
+            })
+            .collect()
+    }
+
+    /// Returns the optional dependencies of crates in a dependency graph which were not activated.
+    ///
+    /// Crates closer to the root are examined first, and at most `limit` dependencies (capped at
+    /// `MAX_POTENTIAL_DEPENDENCIES`) are returned. If no limit is provided, none are returned.
+    ///
+    /// # Arguments
+    /// * `crate_distance_vec` - the crates in the dependency graph, ordered by distance from the root crate.
+    /// * `crates_seen` - the features enabled for each crate per compile kind.
+    /// * `dependencies_seen` - the dependency declarations included in the dependency graph.
+    /// * `manifest` - the `Crate` outside the registry, if it is the root crate.
+    /// * `feature_names` - the `FeatureNames` of the dependency graph.
+    /// * `context` - the `TraversalContext` of the dependency graph.
+    /// * `limit` - the maximum number of dependencies to return, if any should be returned.
+    #[allow(clippy::too_many_arguments)]
+    fn get_potential_dependencies<'a>(
+        &'a self,
+        crate_distance_vec: &[(u32, usize)],
+        crates_seen: &AHashMap<(u32, CompileKind), Vec<u32>>,
+        dependencies_seen: &AHashMap<(u32, u32), usize>,
+        manifest: Option<&'a Crate>,
+        feature_names: &FeatureNames,
+        context: &TraversalContext,
+        limit: Option<usize>,
+    ) -> Vec<DependencyEdge<'a>> {
+        let limit = limit.map_or(0, |limit| limit.min(MAX_POTENTIAL_DEPENDENCIES));
+        let mut potential_dependencies: Vec<DependencyEdge> = vec![];
+        let mut potential_dependencies_seen: AHashSet<(u32, u32)> = AHashSet::new();
+
+        if limit == 0 {
+            return potential_dependencies;
         }
 
-        for (dependency_name, mut dependency_features) in dependencies_to_check {
-            let mut target_supported = true;
+        for &(crate_id, _) in crate_distance_vec {
+            let crate_val = self.crate_val(crate_id, manifest);
 
-            if let Some(dependency) = crate_val
-                .dependencies
-                .iter()
-                .find(|dependency| dependency.to == dependency_name)
-            {
-                if dependency.default_features {
-                    dependency_features.push(default_string.clone());
+            for compile_kind in [CompileKind::Target, CompileKind::Host] {
+                if !crates_seen.contains_key(&(crate_id, compile_kind)) {
+                    continue;
                 }
 
-                if let Some(dependency_target) = &dependency.target {
-                    if let Ok(dependency_platform) = Platform::from_str(dependency_target) {
-                        let mut cfg_attributes = self.targets.get(target).unwrap().clone();
+                for (index, dependency) in context.compact.dependencies(crate_id).iter().enumerate()
+                {
+                    if potential_dependencies.len() == limit {
+                        return potential_dependencies;
+                    }
 
-                        cfg_attributes.push(cfg_name.clone());
+                    let index = u32::try_from(index).unwrap();
 
-                        target_supported =
-                            dependency_platform.matches(target, cfg_attributes.as_slice());
+                    if context.follows(crate_id, dependency.kind)
+                        && dependency.optional
+                        && !dependencies_seen.contains_key(&(crate_id, index))
+                        && dependency.target.is_none_or(|dependency_target| {
+                            context.matches(dependency_target, compile_kind)
+                        })
+                        && potential_dependencies_seen.insert((crate_id, index))
+                    {
+                        potential_dependencies.push(DependencyEdge {
+                            active: false,
+                            ..self.declaration_edge(
+                                crate_id,
+                                index,
+                                manifest,
+                                feature_names.names(
+                                    context.compact.requested_features(crate_id, dependency),
+                                ),
+                                get_activating_features(
+                                    crate_val,
+                                    self.compact.crate_name(dependency.to),
+                                ),
+                            )
+                        });
                     }
                 }
             }
+        }
 
-            if target_supported {
-                dependency_queue.push_back(QueueDependency {
-                    from: crate_val.name.clone(),
-                    to: dependency_name,
-                    to_feature_names: dependency_features,
-                    to_distance: distance + 1,
-                });
-            }
+        potential_dependencies
+    }
+
+    /// Creates the `TraversalContext` used to construct a dependency graph.
+    ///
+    /// # Arguments
+    /// * `query` - the `DependencyGraphQuery` of the dependency graph.
+    /// * `roots` - the ids of the root crates and the features selected for them.
+    /// * `root` - the `CompactRoot` of a root crate outside the registry, if any.
+    fn traversal_context<'a>(
+        &'a self,
+        query: &'a DependencyGraphQuery,
+        roots: &[(u32, &DependencyGraphRoot)],
+        root: Option<&'a CompactRoot>,
+    ) -> TraversalContext<'a> {
+        let target = query.target.as_deref().unwrap_or(&self.default_target);
+        let host = query.host.as_deref().unwrap_or(&self.default_target);
+        let cfg_names: Vec<Cfg> = if query.cfgs.is_empty() {
+            vec![Cfg::from_str(&self.default_cfg_name).unwrap()]
+        } else {
+            query
+                .cfgs
+                .iter()
+                .map(|cfg_name| Cfg::from_str(cfg_name).unwrap())
+                .collect()
+        };
+
+        let get_cfg_attributes = |triple: &str| {
+            let mut cfg_attributes = self.targets.get(triple).unwrap().clone();
+            cfg_attributes.extend(cfg_names.iter().cloned());
+            cfg_attributes
+        };
+
+        let compact = CompactView::new(&self.compact, root);
+
+        TraversalContext {
+            target,
+            target_cfg_attributes: get_cfg_attributes(target),
+            host,
+            host_cfg_attributes: get_cfg_attributes(host),
+            resolver: query.resolver,
+            infer_proc_macros: query.infer_proc_macros,
+            kinds: query.kinds.as_deref(),
+            roots: roots.iter().map(|&(root_id, _)| root_id).collect(),
+            excluded: query
+                .exclude
+                .iter()
+                .filter_map(|crate_id| self.compact.crate_id(crate_id))
+                .collect(),
+            max_depth: query.max_depth,
+            compact,
+            platform_matches: vec![Cell::new(None); compact.platform_count() * 2],
         }
     }
 }
 
-impl Default for Graph {
-    fn default() -> Self {
-        Self::new()
-    }
+/// Loads the categories, crates, and keywords of a database dump unpacked into a temporary directory, cleaning up
+/// the directory afterwards.
+///
+/// # Arguments
+/// * `temp_dir` - the `TempDir` containing the unpacked database dump.
+/// * `recorder` - the `LoadRecorder` to record loading phases with.
+///
+/// # Errors
+/// * Returns a `LoadError` if the dump has no data directory or cannot be loaded.
+fn load_unpacked(temp_dir: TempDir, recorder: &LoadRecorder) -> Result<RegistryData, LoadError> {
+    let data_path = fs::get_data_path(&temp_dir)?.ok_or_else(|| {
+        LoadError::Fetch(format!(
+            "unable to find data path in {}",
+            temp_dir.path().display()
+        ))
+    })?;
+
+    let data = load::get_data(data_path.as_str(), recorder)?;
+    fs::clean_tempdir(temp_dir, recorder)?;
+
+    Ok(data)
 }
 
 /// Creates a set of names from a `AHashMap`.
@@ -456,16 +943,196 @@ fn get_names<T>(collection: &AHashMap<String, T>) -> BTreeSet<String> {
     collection.keys().cloned().collect()
 }
 
-/// Returns a set of cfg names (e.g. `unix`, `cargo_web`) present among all dependencies.
-fn get_cfg_names(crates: &AHashMap<String, Crate>) -> BTreeSet<String> {
-    println!("Collecting cfg names...");
-    let start = Instant::now();
+/// Appends every feature exposed by a crate, including the implicit features of optional dependencies, to a list of
+/// features, skipping those already present.
+///
+/// # Arguments
+/// * `crate_val` - the `Crate` whose features are enabled.
+/// * `features` - the list of features to extend.
+fn append_all_features(crate_val: &Crate, features: &mut Vec<String>) {
+    for feature_name in crate_val.features.keys() {
+        if !features.contains(feature_name) {
+            features.push(feature_name.clone());
+        }
+    }
+}
+
+/// Returns the names of the features enabled for a crate in a dependency graph when built for the target and the
+/// host, excluding `default`.
+///
+/// # Arguments
+/// * `crate_id` - the id of the crate.
+/// * `crates_seen` - the features enabled for each crate per compile kind.
+/// * `feature_names` - the `FeatureNames` of the dependency graph.
+fn get_crate_features(
+    crate_id: u32,
+    crates_seen: &AHashMap<(u32, CompileKind), Vec<u32>>,
+    feature_names: &FeatureNames,
+) -> (Option<Vec<String>>, Option<Vec<String>>) {
+    let get_features = |compile_kind: CompileKind| {
+        crates_seen
+            .get(&(crate_id, compile_kind))
+            .map(|feature_ids| {
+                feature_ids
+                    .iter()
+                    .filter(|&&feature_id| feature_id != DEFAULT_FEATURE)
+                    .map(|&feature_id| feature_names.name(feature_id).clone())
+                    .collect()
+            })
+    };
+
+    (
+        get_features(CompileKind::Target),
+        get_features(CompileKind::Host),
+    )
+}
+
+/// Returns the `CrateDistanceInfo` of a crate in a dependency graph.
+///
+/// # Arguments
+/// * `crate_id` - the name of the crate.
+/// * `(target_features, host_features)` - the features enabled for the crate when built for the target and the host.
+/// * `distance` - the distance of the crate from the root crate.
+/// * `context` - the `TraversalContext` of the dependency graph.
+fn get_crate_distance_info<'a>(
+    crate_id: &'a String,
+    (target_features, host_features): (Option<Vec<String>>, Option<Vec<String>>),
+    distance: usize,
+    context: &TraversalContext,
+) -> CrateDistanceInfo<'a> {
+    // features enabled for any compile kind
+    let mut enabled_features = target_features.clone().unwrap_or_default();
+    for feature_name in host_features.iter().flatten() {
+        if !enabled_features.contains(feature_name) {
+            enabled_features.push(feature_name.clone());
+        }
+    }
+
+    let compile_kinds = (context.resolver == Resolver::V2).then(|| {
+        // a crate is built twice if it is built for a distinct host or with different features
+        let builds_twice = match (&target_features, &host_features) {
+            (Some(target_features), Some(host_features)) => {
+                context.host != context.target
+                    || target_features.iter().collect::<BTreeSet<_>>()
+                        != host_features.iter().collect::<BTreeSet<_>>()
+            }
+            _ => false,
+        };
+
+        CompileKindFeatures {
+            builds_twice,
+            host: host_features,
+            target: target_features,
+        }
+    });
+
+    CrateDistanceInfo {
+        crate_id,
+        distance,
+        enabled_features,
+        compile_kinds,
+    }
+}
+
+/// Adds the declarations of a dependency to the edges of a dependency graph.
+///
+/// Declarations not seen yet are added, otherwise newly requested features and activations are merged.
+///
+/// # Arguments
+/// * `from` - the id of the crate declaring the dependency.
+/// * `declarations` - the enabled declarations of the dependency.
+/// * `dependencies` - the list of source crate ids and declarations.
+/// * `dependencies_seen` - a map of declarations seen so far by source id and declaration index to list indices.
+fn add_dependency_edges(
+    from: u32,
+    declarations: Vec<QueueDeclaration>,
+    dependencies: &mut Vec<(u32, QueueDeclaration)>,
+    dependencies_seen: &mut AHashMap<(u32, u32), usize>,
+) {
+    for declaration in declarations {
+        match dependencies_seen.entry((from, declaration.index)) {
+            Entry::Occupied(entry) => dependencies[*entry.get()].1.merge(declaration),
+            Entry::Vacant(entry) => {
+                entry.insert(dependencies.len());
+                dependencies.push((from, declaration));
+            }
+        }
+    }
+}
+
+/// Returns the features of a crate which would activate an optional dependency, directly or through other features.
+///
+/// # Arguments
+/// * `crate_val` - the `Crate` declaring the dependency.
+/// * `dependency_name` - the name of the optional dependency.
+fn get_activating_features(crate_val: &Crate, dependency_name: &str) -> Vec<String> {
+    // features listing the dependency itself or features of the dependency
+    let mut activating_features: BTreeSet<&String> = crate_val
+        .features
+        .iter()
+        .filter(|(_, feature_dependencies)| {
+            feature_dependencies.iter().any(|feature_dependency| {
+                let feature_dependency = feature_dependency
+                    .strip_prefix("dep:")
+                    .unwrap_or(feature_dependency);
+
+                feature_dependency
+                    .split_once('/')
+                    .map_or(feature_dependency, |(name, _)| name)
+                    == dependency_name
+            })
+        })
+        .map(|(feature_name, _)| feature_name)
+        .collect();
+
+    // features enabling activating features, until no more are found
+    loop {
+        let enabling_features: Vec<&String> = crate_val
+            .features
+            .iter()
+            .filter(|(feature_name, feature_dependencies)| {
+                !activating_features.contains(feature_name)
+                    && feature_dependencies
+                        .iter()
+                        .any(|feature_dependency| activating_features.contains(feature_dependency))
+            })
+            .map(|(feature_name, _)| feature_name)
+            .collect();
+
+        if enabling_features.is_empty() {
+            break;
+        }
+
+        activating_features.extend(enabling_features);
+    }
+
+    activating_features.into_iter().cloned().collect()
+}
 
+/// Returns a set of cfg names (e.g. `unix`, `cargo_web`) present among all normal and build dependencies.
+///
+/// Dev dependencies are left out, as they are only followed for root crates.
+///
+/// # Arguments
+/// * `compact` - the `CompactGraph` of the registry.
+fn get_cfg_names(compact: &CompactGraph) -> BTreeSet<String> {
     let mut cfg_names: BTreeSet<String> = BTreeSet::new();
+    let mut targets_seen: Vec<bool> = vec![false; compact.target_count()];
+
+    for crate_id in 0..u32::try_from(compact.crate_count()).unwrap() {
+        for dependency in compact
+            .dependencies(crate_id)
+            .iter()
+            .filter(|dependency| dependency.kind != DependencyKind::Dev)
+        {
+            if let Some(target_id) = dependency.target {
+                if targets_seen[target_id as usize] {
+                    continue;
+                }
+
+                targets_seen[target_id as usize] = true;
+                let target = compact.target(target_id);
 
-    for crate_val in crates.values() {
-        for dependency in &crate_val.dependencies {
-            if let Some(target) = &dependency.target {
                 if target.matches('(').count() == 1
                     && target.matches(')').count() == 1
                     && !target.contains('=')
@@ -481,11 +1148,6 @@ fn get_cfg_names(crates: &AHashMap<String, Crate>) -> BTreeSet<String> {
         }
     }
 
-    println!(
-        "Finished collecting cfg names in {} seconds.",
-        start.elapsed().as_secs_f64()
-    );
-
     cfg_names
 }
 
@@ -495,6 +1157,10 @@ pub struct CrateDistance<'a> {
     /// A list of categories the crate belongs to.
     pub categories: &'a Vec<String>,
 
+    /// The features enabled for this crate per `CompileKind`, if resolved with `Resolver::V2`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub compile_kinds: Option<CompileKindFeatures>,
+
     /// The time at which the most recent stable version (if available) of the crate was created.
     pub created_at: &'a NaiveDateTime,
 
@@ -527,6 +1193,7 @@ pub struct CrateDistanceInfo<'a> {
     pub crate_id: &'a String,
     pub distance: usize,
     pub enabled_features: Vec<String>,
+    pub compile_kinds: Option<CompileKindFeatures>,
 }
 
 impl<'a> CrateDistance<'a> {
@@ -539,11 +1206,23 @@ impl<'a> CrateDistance<'a> {
     pub fn new(
         crate_distance_info: CrateDistanceInfo<'a>,
         crates: &'a AHashMap<String, Crate>,
-    ) -> CrateDistance<'a> {
+    ) -> Self {
+        let crate_val = crates.get(crate_distance_info.crate_id).unwrap();
+        Self::from_crate(crate_distance_info, crate_val)
+    }
+
+    /// Creates a new `CrateDistance` from a `Crate` value, which need not be in the registry.
+    ///
+    /// # Arguments
+    /// * `crate_distance_info` - a the `CrateDistanceInfo` containing the relevant information.
+    /// * `crate_val` - the `Crate` value.
+    #[must_use]
+    pub fn from_crate(crate_distance_info: CrateDistanceInfo<'a>, crate_val: &'a Crate) -> Self {
         let CrateDistanceInfo {
-            crate_id,
             distance,
             enabled_features,
+            compile_kinds,
+            ..
         } = crate_distance_info;
 
         let Crate {
@@ -556,10 +1235,11 @@ impl<'a> CrateDistance<'a> {
             name,
             version,
             ..
-        } = &crates.get(crate_id).unwrap();
+        } = crate_val;
 
         CrateDistance {
             categories,
+            compile_kinds,
             created_at,
             description,
             distance,
@@ -580,20 +1260,698 @@ pub struct DependencyGraph<'a> {
     pub crates: Vec<CrateDistance<'a>>,
 
     /// The list of dependencies included in the dependency graph.
-    pub dependencies: Vec<&'a Dependency>,
+    ///
+    /// Each declaration which applied is included, so a crate may have several dependencies on the same crate.
+    pub dependencies: Vec<DependencyEdge<'a>>,
+
+    /// The optional dependencies of included crates which were not activated, if requested.
+    ///
+    /// The `activated_by` field of each lists the features of the source crate which would activate it.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub potential_dependencies: Vec<DependencyEdge<'a>>,
+}
+
+#[derive(Clone, Serialize)]
+/// A `Dependency` intended for serialization as an edge of a `DependencyGraph`.
+pub struct DependencyEdge<'a> {
+    /// The features of the source crate which activated the dependency or requested features through it.
+    pub activated_by: Vec<String>,
+
+    /// If the dependency is active, as opposed to a potential dependency which was not activated.
+    pub active: bool,
+
+    /// If the dependency relies on default features.
+    pub default_features: bool,
+
+    /// The features requested of the destination crate through the dependency.
+    pub features: Vec<String>,
+
+    /// The source crate of the dependency.
+    pub from: &'a String,
+
+    /// The kind of the dependency.
+    pub kind: DependencyKind,
+
+    /// If the dependency is optional.
+    pub optional: bool,
+
+    /// The specific target of the dependency, if one is present.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target: Option<&'a String>,
+
+    /// The destination crate of the dependency.
+    pub to: &'a String,
+}
+
+impl<'a> DependencyEdge<'a> {
+    /// Creates a new `DependencyEdge` from an enabled declaration.
+    ///
+    /// # Arguments
+    /// * `dependency` - the declared `Dependency`.
+    /// * `features` - the features requested of the destination crate through the declaration.
+    /// * `activated_by` - the features of the source crate which activated the declaration.
+    const fn new(
+        dependency: &'a Dependency,
+        features: Vec<String>,
+        activated_by: Vec<String>,
+    ) -> Self {
+        let Dependency {
+            default_features,
+            from,
+            kind,
+            optional,
+            target,
+            to,
+            ..
+        } = dependency;
+
+        Self {
+            activated_by,
+            active: true,
+            default_features: *default_features,
+            features,
+            from,
+            kind: *kind,
+            optional: *optional,
+            target: target.as_ref(),
+            to,
+        }
+    }
+
+    /// Creates a new `DependencyEdge` from an enabled declaration of a crate in the registry.
+    ///
+    /// # Arguments
+    /// * `compact` - the `CompactGraph` of the registry.
+    /// * `from` - the id of the crate declaring the dependency.
+    /// * `dependency` - the declared `CompactDependency`.
+    /// * `features` - the features requested of the destination crate through the declaration.
+    /// * `activated_by` - the features of the source crate which activated the declaration.
+    fn from_compact(
+        compact: &'a CompactGraph,
+        from: u32,
+        dependency: &CompactDependency,
+        features: Vec<String>,
+        activated_by: Vec<String>,
+    ) -> Self {
+        Self {
+            activated_by,
+            active: true,
+            default_features: dependency.default_features,
+            features,
+            from: compact.crate_name(from),
+            kind: dependency.kind,
+            optional: dependency.optional,
+            target: dependency.target.map(|target_id| compact.target(target_id)),
+            to: compact.crate_name(dependency.to),
+        }
+    }
+}
+
+impl DependencyGraph<'_> {
+    /// Returns the names of crates built separately for the host and the target.
+    ///
+    /// This is only populated for dependency graphs resolved with `Resolver::V2`.
+    #[must_use]
+    pub fn crates_built_twice(&self) -> Vec<&String> {
+        self.crates
+            .iter()
+            .filter(|crate_distance| {
+                crate_distance
+                    .compile_kinds
+                    .as_ref()
+                    .is_some_and(|compile_kinds| compile_kinds.builds_twice)
+            })
+            .map(|crate_distance| crate_distance.name)
+            .collect()
+    }
+}
+
+/// The features enabled for a crate in a `DependencyGraph`, split by the `CompileKind` it is built for.
+#[derive(Serialize)]
+pub struct CompileKindFeatures {
+    /// If the crate is built separately for the host and the target.
+    pub builds_twice: bool,
+
+    /// The features enabled when building for the host, if built for the host.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub host: Option<Vec<String>>,
+
+    /// The features enabled when building for the target, if built for the target.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target: Option<Vec<String>>,
+}
+
+/// The platform a crate is compiled for.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum CompileKind {
+    /// The platform the build runs on, used by build dependencies and procedural macros.
+    Host,
+
+    /// The platform being built for.
+    Target,
+}
+
+impl CompileKind {
+    /// Returns the opposite `CompileKind`.
+    #[must_use]
+    pub const fn other(self) -> Self {
+        match self {
+            Self::Host => Self::Target,
+            Self::Target => Self::Host,
+        }
+    }
+}
+
+/// The feature resolver used to construct a `DependencyGraph`.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum Resolver {
+    /// Features are unified across every edge and only normal dependencies are followed.
+    #[default]
+    Unified,
+
+    /// Features are resolved as with Cargo's resolver 2.
+    ///
+    /// Build dependencies and procedural macros are built for the host with their own features,
+    /// separately from dependencies built for the target. Dev dependencies are never followed.
+    V2,
+}
+
+impl Resolver {
+    /// Returns if dependencies of the specified kind are followed by the resolver.
+    ///
+    /// # Arguments
+    /// * `kind` - the `DependencyKind` to check.
+    #[must_use]
+    pub const fn follows(self, kind: DependencyKind) -> bool {
+        match kind {
+            DependencyKind::Normal => true,
+            DependencyKind::Build => matches!(self, Self::V2),
+            DependencyKind::Dev => false,
+        }
+    }
+
+    /// Returns the `CompileKind` a dependency is built for.
+    ///
+    /// # Arguments
+    /// * `compile_kind` - the `CompileKind` of the dependent crate.
+    /// * `kind` - the `DependencyKind` of the dependency.
+    /// * `proc_macro` - if the crate being depended on is a procedural macro.
+    #[must_use]
+    pub const fn dependency_compile_kind(
+        self,
+        compile_kind: CompileKind,
+        kind: DependencyKind,
+        proc_macro: bool,
+    ) -> CompileKind {
+        match self {
+            Self::Unified => CompileKind::Target,
+            Self::V2 if matches!(kind, DependencyKind::Build) || proc_macro => CompileKind::Host,
+            Self::V2 => compile_kind,
+        }
+    }
+}
+
+/// Options controlling how the root crate of a `DependencyGraph` is configured.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct DependencyGraphOptions {
+    /// If the default features of the root crate are enabled (disabled by `--no-default-features`).
+    pub default_features: bool,
+
+    /// If every feature of the root crate is enabled (as with `--all-features`).
+    pub all_features: bool,
+
+    /// The feature resolver to use.
+    pub resolver: Resolver,
+
+    /// The host triple build dependencies and procedural macros are built for under `Resolver::V2`.
+    ///
+    /// Defaults to the `Graph`'s default target if not provided.
+    pub host: Option<String>,
+
+    /// If procedural macros are inferred with `Crate::is_proc_macro` and built for the host under `Resolver::V2`.
+    ///
+    /// The registry does not record which crates are procedural macros, so this is a heuristic with both false
+    /// positives and false negatives. When disabled, procedural macros are built for the target like any other
+    /// normal dependency.
+    pub infer_proc_macros: bool,
+
+    /// The maximum number of inactive optional dependencies to include, if they should be included.
+    ///
+    /// This is capped at 500.
+    pub potential_dependencies: Option<usize>,
+}
+
+impl Default for DependencyGraphOptions {
+    fn default() -> Self {
+        Self {
+            default_features: true,
+            all_features: false,
+            resolver: Resolver::default(),
+            host: None,
+            infer_proc_macros: false,
+            potential_dependencies: None,
+        }
+    }
 }
 
 /// A struct containing information about a `Dependency` for processing in a queue to create a dependency graph.
+///
+/// This merges every declaration of the destination crate that applies to the current platform.
 struct QueueDependency {
-    /// The source fof the `Dependency`.
-    pub from: String,
+    /// The id of the source of the `Dependency`.
+    pub from: u32,
+
+    /// The id of the destination of the `Dependency`.
+    pub to: u32,
 
-    /// The destination of the `Dependency`.
-    pub to: String,
+    /// The source crate's dependency declarations which apply to the destination.
+    pub declarations: Vec<QueueDeclaration>,
 
-    /// The list of features to included with the destination crate.
-    pub to_feature_names: Vec<String>,
+    /// The ids of the features to included with the destination crate.
+    pub to_feature_ids: Vec<u32>,
 
     /// The distance of the destination crate from the root.
     pub to_distance: usize,
+
+    /// The `CompileKind` the destination crate is built for.
+    pub to_compile_kind: CompileKind,
+}
+
+/// A dependency declaration enabled while constructing a dependency graph.
+struct QueueDeclaration {
+    /// The index of the declaration among the source crate's dependencies.
+    pub index: u32,
+
+    /// The ids of the features requested of the destination crate through the declaration.
+    pub features: Vec<u32>,
+
+    /// The ids of the features of the source crate which activated the declaration or requested features through it.
+    pub activated_by: Vec<u32>,
+}
+
+impl QueueDeclaration {
+    /// Creates a new `QueueDeclaration` requesting the features listed in the declaration.
+    ///
+    /// # Arguments
+    /// * `index` - the index of the declaration among the source crate's dependencies.
+    /// * `requested_features` - the ids of the features listed in the declaration.
+    fn new(index: u32, requested_features: &[u32]) -> Self {
+        Self {
+            index,
+            features: requested_features.to_vec(),
+            activated_by: vec![],
+        }
+    }
+
+    /// Adds a feature requested of the destination crate, if not already present.
+    ///
+    /// # Arguments
+    /// * `feature_id` - the id of the feature.
+    fn add_feature(&mut self, feature_id: u32) {
+        if !self.features.contains(&feature_id) {
+            self.features.push(feature_id);
+        }
+    }
+
+    /// Adds a feature of the source crate which activated the declaration, if not already present.
+    ///
+    /// # Arguments
+    /// * `feature_id` - the id of the feature.
+    fn add_activation(&mut self, feature_id: u32) {
+        if !self.activated_by.contains(&feature_id) {
+            self.activated_by.push(feature_id);
+        }
+    }
+
+    /// Merges the features and activations of a later visit of the same declaration.
+    ///
+    /// # Arguments
+    /// * `declaration` - the `QueueDeclaration` of the later visit.
+    fn merge(&mut self, declaration: Self) {
+        for feature_id in declaration.features {
+            self.add_feature(feature_id);
+        }
+
+        for feature_id in declaration.activated_by {
+            self.add_activation(feature_id);
+        }
+    }
+}
+
+/// The names of the features enabled while constructing a dependency graph.
+///
+/// Features unknown to the registry (which may only be requested of the root crate) are assigned ids past those of
+/// interned feature names.
+struct FeatureNames<'a> {
+    /// The interned feature names of the registry.
+    interner: &'a Interner,
+
+    /// The names of unknown features, by id past those of interned feature names.
+    unknown: Vec<String>,
+}
+
+impl<'a> FeatureNames<'a> {
+    /// Creates a new `FeatureNames`.
+    ///
+    /// # Arguments
+    /// * `interner` - the interned feature names of the registry.
+    const fn new(interner: &'a Interner) -> Self {
+        Self {
+            interner,
+            unknown: vec![],
+        }
+    }
+
+    /// Returns the id of a feature, assigning a new id if it is unknown.
+    ///
+    /// # Arguments
+    /// * `feature_name` - the name of the feature.
+    fn id(&mut self, feature_name: &str) -> u32 {
+        if let Some(feature_id) = self.interner.get(feature_name) {
+            return feature_id;
+        }
+
+        let index = self
+            .unknown
+            .iter()
+            .position(|unknown_name| unknown_name == feature_name)
+            .unwrap_or_else(|| {
+                self.unknown.push(String::from(feature_name));
+                self.unknown.len() - 1
+            });
+
+        u32::try_from(self.interner.len() + index).unwrap()
+    }
+
+    /// Returns the name of a feature.
+    ///
+    /// # Arguments
+    /// * `feature_id` - the id of the feature.
+    fn name(&self, feature_id: u32) -> &String {
+        let feature_index = feature_id as usize;
+
+        if feature_index < self.interner.len() {
+            self.interner.name(feature_id)
+        } else {
+            &self.unknown[feature_index - self.interner.len()]
+        }
+    }
+
+    /// Returns the names of a list of features.
+    ///
+    /// # Arguments
+    /// * `feature_ids` - the ids of the features.
+    fn names(&self, feature_ids: &[u32]) -> Vec<String> {
+        feature_ids
+            .iter()
+            .map(|&feature_id| self.name(feature_id).clone())
+            .collect()
+    }
+}
+
+/// Settings shared across the construction of a single dependency graph.
+struct TraversalContext<'a> {
+    /// The target triple being built for.
+    target: &'a str,
+
+    /// The cfg attributes of the target, including the requested cfg name.
+    target_cfg_attributes: Vec<Cfg>,
+
+    /// The host triple build dependencies and procedural macros are built for.
+    host: &'a str,
+
+    /// The cfg attributes of the host, including the requested cfg name.
+    host_cfg_attributes: Vec<Cfg>,
+
+    /// The feature resolver in use.
+    resolver: Resolver,
+
+    /// If crates inferred to be procedural macros are built for the host.
+    infer_proc_macros: bool,
+
+    /// The kinds of dependencies to follow, if restricted.
+    kinds: Option<&'a [DependencyKind]>,
+
+    /// The ids of the root crates.
+    roots: Vec<u32>,
+
+    /// The ids of the crates left out of the dependency graph.
+    excluded: AHashSet<u32>,
+
+    /// The greatest distance from a root crate at which crates are included, if limited.
+    max_depth: Option<usize>,
+
+    /// The crates and their dependencies and features, including the root crate if outside the registry.
+    compact: CompactView<'a>,
+
+    /// If each dependency target applies when building for the host and the target, once checked.
+    platform_matches: Vec<Cell<Option<bool>>>,
+}
+
+impl TraversalContext<'_> {
+    /// Returns if a crate is included in the dependency graph, unless excluded or past the depth limit.
+    ///
+    /// # Arguments
+    /// * `crate_id` - the id of the crate.
+    /// * `distance` - the distance of the crate from the root crates.
+    fn includes(&self, crate_id: u32, distance: usize) -> bool {
+        !self.excluded.contains(&crate_id)
+            && self.max_depth.is_none_or(|max_depth| distance <= max_depth)
+    }
+
+    /// Returns if a dependency of the specified kind is followed.
+    ///
+    /// If the kinds of dependencies are restricted, dev dependencies of the root crates are followed if included.
+    ///
+    /// # Arguments
+    /// * `crate_id` - the id of the dependent crate.
+    /// * `kind` - the `DependencyKind` of the dependency.
+    fn follows(&self, crate_id: u32, kind: DependencyKind) -> bool {
+        self.kinds.map_or_else(
+            || self.resolver.follows(kind),
+            |kinds| {
+                kinds.contains(&kind)
+                    && (self.resolver.follows(kind)
+                        || (kind == DependencyKind::Dev && self.roots.contains(&crate_id)))
+            },
+        )
+    }
+
+    /// Returns if a platform-specific dependency applies when building for the specified `CompileKind`.
+    ///
+    /// Dependency targets which cannot be parsed are treated as applying to every platform.
+    ///
+    /// # Arguments
+    /// * `dependency_target` - the id of the target (triple or cfg expression) of the dependency.
+    /// * `compile_kind` - the `CompileKind` of the dependent crate.
+    fn matches(&self, dependency_target: u32, compile_kind: CompileKind) -> bool {
+        let platform_match =
+            &self.platform_matches[dependency_target as usize * 2 + compile_kind as usize];
+
+        if let Some(matches) = platform_match.get() {
+            return matches;
+        }
+
+        let matches =
+            self.compact
+                .platform(dependency_target)
+                .is_none_or(|dependency_platform| match compile_kind {
+                    CompileKind::Host => {
+                        dependency_platform.matches(self.host, self.host_cfg_attributes.as_slice())
+                    }
+                    CompileKind::Target => dependency_platform
+                        .matches(self.target, self.target_cfg_attributes.as_slice()),
+                });
+
+        platform_match.set(Some(matches));
+        matches
+    }
+
+    /// A helper function to construct the dependency graph.
+    ///
+    /// Adds all relevant dependencies of a crate into the dependency queue for processing.
+    ///
+    /// Each dependency declaration is considered separately, as a crate may declare the same dependency several
+    /// times (e.g. under different targets). Declarations applying to the current platform are then merged.
+    ///
+    /// # Arguments
+    /// * `crate_id` - the id of the crate being examined.
+    /// * `feature_ids` - the list of enabled features.
+    /// * `dependency_queue` - the queue of dependencies to process.
+    /// * `distance` - the distance from the root crate.
+    /// * `compile_kind` - the `CompileKind` the crate is being built for.
+    fn dependency_graph_helper(
+        &self,
+        crate_id: u32,
+        mut feature_ids: Vec<u32>,
+        dependency_queue: &mut VecDeque<QueueDependency>,
+        distance: usize,
+        compile_kind: CompileKind,
+    ) {
+        let crate_dependencies = self.compact.dependencies(crate_id);
+        let crate_features = self.compact.features(crate_id);
+
+        // dependency declarations included in traversal, keyed by index
+        let mut declarations_to_check: BTreeMap<u32, QueueDeclaration> = BTreeMap::new();
+
+        // add mandatory dependencies
+        for (index, dependency) in crate_dependencies.iter().enumerate() {
+            if self.follows(crate_id, dependency.kind) && !dependency.optional {
+                let index = u32::try_from(index).unwrap();
+                declarations_to_check.insert(
+                    index,
+                    QueueDeclaration::new(
+                        index,
+                        self.compact.requested_features(crate_id, dependency),
+                    ),
+                );
+            }
+        }
+
+        let default_features_enabled = feature_ids.contains(&DEFAULT_FEATURE);
+        let default_actions = crate_features
+            .iter()
+            .find(|feature| feature.name == DEFAULT_FEATURE)
+            .map_or(&[][..], |feature| {
+                self.compact.feature_actions(crate_id, feature)
+            });
+
+        // add dependencies enabled by features
+        for feature in crate_features {
+            if feature.name != DEFAULT_FEATURE
+                && (feature_ids.contains(&feature.name)
+                    || (default_features_enabled
+                        && default_actions.contains(&FeatureAction::Feature(feature.name))))
+            {
+                for action in self.compact.feature_actions(crate_id, feature) {
+                    match *action {
+                        FeatureAction::Feature(feature_id) => {
+                            // if feature enables another feature that is not already included
+                            if !feature_ids.contains(&feature_id) {
+                                feature_ids.push(feature_id);
+                            }
+                        }
+                        FeatureAction::DependencyFeature {
+                            declarations,
+                            feature: dependency_feature_id,
+                        } => {
+                            // add declaration if not already added, then add feature if feature was not added
+                            for index in declarations.range() {
+                                let dependency = &crate_dependencies[index];
+                                if self.follows(crate_id, dependency.kind) {
+                                    let index = u32::try_from(index).unwrap();
+                                    let declaration =
+                                        declarations_to_check.entry(index).or_insert_with(|| {
+                                            QueueDeclaration::new(
+                                                index,
+                                                self.compact
+                                                    .requested_features(crate_id, dependency),
+                                            )
+                                        });
+
+                                    declaration.add_feature(dependency_feature_id);
+                                    declaration.add_activation(feature.name);
+                                }
+                            }
+                        }
+                        FeatureAction::Dependency { declarations } => {
+                            // if features not enabled, insert declaration if not already present
+                            for index in declarations.range() {
+                                let dependency = &crate_dependencies[index];
+                                if self.follows(crate_id, dependency.kind) {
+                                    let index = u32::try_from(index).unwrap();
+                                    declarations_to_check
+                                        .entry(index)
+                                        .or_insert_with(|| {
+                                            QueueDeclaration::new(
+                                                index,
+                                                self.compact
+                                                    .requested_features(crate_id, dependency),
+                                            )
+                                        })
+                                        .add_activation(feature.name);
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        self.queue_declarations(
+            crate_id,
+            declarations_to_check,
+            dependency_queue,
+            distance,
+            compile_kind,
+        );
+    }
+
+    /// A helper function to construct the dependency graph.
+    ///
+    /// Merges the dependency declarations of a crate which apply to the current platform by destination and
+    /// `CompileKind`, adding them into the dependency queue for processing.
+    ///
+    /// # Arguments
+    /// * `crate_id` - the id of the crate being examined.
+    /// * `declarations_to_check` - the enabled declarations, keyed by index.
+    /// * `dependency_queue` - the queue of dependencies to process.
+    /// * `distance` - the distance from the root crate.
+    /// * `compile_kind` - the `CompileKind` the crate is being built for.
+    fn queue_declarations(
+        &self,
+        crate_id: u32,
+        declarations_to_check: BTreeMap<u32, QueueDeclaration>,
+        dependency_queue: &mut VecDeque<QueueDependency>,
+        distance: usize,
+        compile_kind: CompileKind,
+    ) {
+        let crate_dependencies = self.compact.dependencies(crate_id);
+
+        // declarations supported on the current platform, merged by destination and compile kind
+        let mut dependencies_to_check: BTreeMap<(u32, CompileKind), QueueDependency> =
+            BTreeMap::new();
+
+        for declaration in declarations_to_check.into_values() {
+            let dependency = &crate_dependencies[declaration.index as usize];
+
+            if dependency
+                .target
+                .is_none_or(|dependency_target| self.matches(dependency_target, compile_kind))
+            {
+                let to_compile_kind = self.resolver.dependency_compile_kind(
+                    compile_kind,
+                    dependency.kind,
+                    self.infer_proc_macros && self.compact.is_proc_macro(dependency.to),
+                );
+
+                let queue_dependency = dependencies_to_check
+                    .entry((dependency.to, to_compile_kind))
+                    .or_insert_with(|| QueueDependency {
+                        from: crate_id,
+                        to: dependency.to,
+                        declarations: vec![],
+                        to_feature_ids: vec![],
+                        to_distance: distance + 1,
+                        to_compile_kind,
+                    });
+
+                for feature_id in &declaration.features {
+                    if !queue_dependency.to_feature_ids.contains(feature_id) {
+                        queue_dependency.to_feature_ids.push(*feature_id);
+                    }
+                }
+
+                queue_dependency.declarations.push(declaration);
+
+                if dependency.default_features
+                    && !queue_dependency.to_feature_ids.contains(&DEFAULT_FEATURE)
+                {
+                    queue_dependency.to_feature_ids.push(DEFAULT_FEATURE);
+                }
+            }
+        }
+
+        dependency_queue.extend(dependencies_to_check.into_values());
+    }
 }
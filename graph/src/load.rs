@@ -1,19 +1,309 @@
 use super::{
+    centrality,
+    manifest::get_manifest_features,
     schema::{
-        Category, Crate, CrateCategory, CrateKeyword, Dependency, Keyword, SqlDependency, Version,
+        Category, Crate, CrateCategory, CrateKeyword, Dependency, DependencyKind, Keyword,
+        SqlDependency, Version,
     },
     traits::Vertex,
 };
 use ahash::AHashMap;
 use cargo_platform::Cfg;
 use csv::{Reader, ReaderBuilder};
+use rayon::prelude::*;
 use semver_parser::version as semver_version;
-use serde::{de::DeserializeOwned, Deserialize};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use std::{
-    any, cmp::Ordering, collections::BTreeMap, fmt::Debug, fs::File, io::BufReader, path::Path,
+    any,
+    cmp::Ordering,
+    collections::{hash_map::Entry, BTreeMap},
+    error::Error,
+    fmt::{self, Display, Formatter},
+    fs::File,
+    io::{self, BufRead, BufReader, Read, Seek, SeekFrom},
+    mem,
+    path::Path,
+    sync::{Arc, Mutex},
     time::Instant,
 };
 
+/// The number of chunks per thread CSV files are split into when parsing.
+///
+/// Using several chunks per thread evens out the work when some chunks take longer to parse than others.
+const CHUNKS_PER_THREAD: usize = 4;
+
+/// The size of the buffer used to scan CSV files for the boundaries of their chunks.
+const SCAN_BUFFER_SIZE: usize = 1 << 16;
+
+/// The time taken by each phase of loading the registry.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct LoadReport {
+    /// The phases of loading, in the order they started.
+    pub phases: Vec<LoadPhase>,
+
+    /// The total time taken to load the registry, in seconds.
+    pub seconds: f64,
+}
+
+/// A phase of loading the registry.
+#[derive(Clone, Debug, Serialize)]
+pub struct LoadPhase {
+    /// The name of the phase.
+    pub name: &'static str,
+
+    /// The number of items (e.g. rows or crates) processed by the phase, if applicable.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub items: Option<usize>,
+
+    /// The time at which the phase started, in seconds since loading started.
+    pub started_at: f64,
+
+    /// The time taken by the phase, in seconds.
+    pub seconds: f64,
+}
+
+/// An error encountered while loading the registry.
+#[derive(Debug, Eq, PartialEq)]
+pub enum LoadError {
+    /// The database dump could not be downloaded or unpacked, with a description of the problem.
+    Fetch(String),
+
+    /// A file could not be read, with its path and a description of the problem.
+    Read(String, String),
+
+    /// The database dump has rows which cannot be parsed or reference missing rows, with a description of the problem.
+    Invalid(String),
+}
+
+impl Display for LoadError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::Fetch(message) => write!(f, "Unable to fetch database dump: {message}"),
+            Self::Read(path, message) => write!(f, "Unable to read {path}: {message}"),
+            Self::Invalid(message) => write!(f, "Invalid database dump: {message}"),
+        }
+    }
+}
+
+impl Error for LoadError {}
+
+/// Returns a function converting an I/O error encountered while reading a file into a `LoadError`.
+///
+/// # Arguments
+/// * `path` - the path of the file.
+pub fn read_error(path: impl Display) -> impl FnOnce(io::Error) -> LoadError {
+    move |err| LoadError::Read(path.to_string(), err.to_string())
+}
+
+/// The number of phases recorded by `get_data`.
+pub const DATA_PHASE_COUNT: usize = 14;
+
+/// The progress of loading the registry at some point in time.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct LoadStatus {
+    /// The phases currently running.
+    pub running: Vec<&'static str>,
+
+    /// The number of phases completed so far.
+    pub completed: usize,
+
+    /// The number of phases expected to run in total.
+    pub total: usize,
+
+    /// The fraction of phases completed so far, between 0 and 1.
+    pub progress: f64,
+
+    /// The time since loading started, in seconds.
+    pub seconds: f64,
+}
+
+/// A handle to the progress of loading the registry, which may be observed from other threads.
+#[derive(Clone, Default)]
+pub struct LoadProgress {
+    /// The phases running and completed so far.
+    phases: Arc<Mutex<LoadPhases>>,
+}
+
+/// The phases running and completed while loading the registry.
+#[derive(Default)]
+struct LoadPhases {
+    /// The time at which loading started, once it has.
+    start: Option<Instant>,
+
+    /// The names of the phases currently running.
+    running: Vec<&'static str>,
+
+    /// The phases completed so far, in the order they finished.
+    completed: Vec<LoadPhase>,
+
+    /// The number of phases expected to run in total.
+    total: usize,
+}
+
+impl LoadProgress {
+    /// Returns the current `LoadStatus`.
+    #[must_use]
+    pub fn status(&self) -> LoadStatus {
+        let phases = self.phases.lock().unwrap();
+
+        LoadStatus {
+            running: phases.running.clone(),
+            completed: phases.completed.len(),
+            total: phases.total,
+            progress: if phases.total == 0 {
+                0.0
+            } else {
+                (phases.completed.len() as f64 / phases.total as f64).min(1.0)
+            },
+            seconds: phases
+                .start
+                .map_or(0.0, |start| start.elapsed().as_secs_f64()),
+        }
+    }
+
+    /// Marks loading as started.
+    ///
+    /// # Arguments
+    /// * `total` - the number of phases expected to run in total.
+    fn start(&self, total: usize) {
+        *self.phases.lock().unwrap() = LoadPhases {
+            start: Some(Instant::now()),
+            total,
+            ..LoadPhases::default()
+        };
+    }
+}
+
+/// Records the phases of loading the registry, which may run concurrently.
+pub struct LoadRecorder {
+    /// The time at which loading started.
+    start: Instant,
+
+    /// The progress of loading, including the phases completed so far.
+    progress: LoadProgress,
+}
+
+impl LoadRecorder {
+    /// Creates a new `LoadRecorder`, reporting its progress through a `LoadProgress`.
+    ///
+    /// # Arguments
+    /// * `progress` - the `LoadProgress` to report to.
+    /// * `total` - the number of phases expected to run in total.
+    pub fn new(progress: LoadProgress, total: usize) -> Self {
+        progress.start(total);
+
+        Self {
+            start: Instant::now(),
+            progress,
+        }
+    }
+
+    /// Runs a phase of loading, recording the time it took.
+    ///
+    /// # Arguments
+    /// * `name` - the name of the phase.
+    /// * `run` - the work of the phase.
+    pub fn time<T>(&self, name: &'static str, run: impl FnOnce() -> T) -> T {
+        self.record(name, || (run(), None))
+    }
+
+    /// Runs a phase of loading, recording the time it took and the number of items it processed.
+    ///
+    /// # Arguments
+    /// * `name` - the name of the phase.
+    /// * `run` - the work of the phase, returning its result and the number of items processed.
+    pub fn time_items<T>(&self, name: &'static str, run: impl FnOnce() -> (T, usize)) -> T {
+        self.record(name, || {
+            let (result, items) = run();
+            (result, Some(items))
+        })
+    }
+
+    /// Runs a phase of loading which may fail, recording the time it took and, if it succeeds, the number of items it
+    /// processed.
+    ///
+    /// # Arguments
+    /// * `name` - the name of the phase.
+    /// * `run` - the work of the phase, returning its result and the number of items processed.
+    pub fn try_time_items<T, E>(
+        &self,
+        name: &'static str,
+        run: impl FnOnce() -> Result<(T, usize), E>,
+    ) -> Result<T, E> {
+        self.record(name, || match run() {
+            Ok((result, items)) => (Ok(result), Some(items)),
+            Err(err) => (Err(err), None),
+        })
+    }
+
+    /// Runs a phase of loading and records it.
+    ///
+    /// # Arguments
+    /// * `name` - the name of the phase.
+    /// * `run` - the work of the phase, returning its result and the number of items processed, if applicable.
+    fn record<T>(&self, name: &'static str, run: impl FnOnce() -> (T, Option<usize>)) -> T {
+        let started_at = self.start.elapsed().as_secs_f64();
+        let phase_start = Instant::now();
+        self.progress.phases.lock().unwrap().running.push(name);
+
+        let (result, items) = run();
+
+        let mut phases = self.progress.phases.lock().unwrap();
+        phases.running.retain(|running| *running != name);
+        phases.completed.push(LoadPhase {
+            name,
+            items,
+            started_at,
+            seconds: phase_start.elapsed().as_secs_f64(),
+        });
+
+        result
+    }
+
+    /// Returns the `LoadReport` of the phases recorded.
+    pub fn finish(self) -> LoadReport {
+        let mut phases = self.progress.phases.lock().unwrap().completed.clone();
+        phases.sort_by(|first, second| first.started_at.total_cmp(&second.started_at));
+
+        LoadReport {
+            phases,
+            seconds: self.start.elapsed().as_secs_f64(),
+        }
+    }
+}
+
+/// The names of the collections loaded from a database dump.
+const COLLECTION_NAMES: [&str; 7] = [
+    "categories",
+    "crates",
+    "crates_categories",
+    "crates_keywords",
+    "dependencies",
+    "keywords",
+    "versions",
+];
+
+/// The categories, crates, and keywords loaded from a database dump, each keyed by name.
+pub type RegistryData = (
+    AHashMap<String, Category>,
+    AHashMap<String, Crate>,
+    AHashMap<String, Keyword>,
+);
+
+/// Vertices loaded from a database dump keyed by name, and a map from their SQL ids to names.
+type Vertices<T> = (AHashMap<String, T>, AHashMap<usize, String>);
+
+/// Returns the names of the collections whose files are missing from a `data` directory.
+///
+/// # Arguments
+/// * `data_path` - the path to the `data` directory inside the database dump.
+pub fn get_missing_collections(data_path: &Path) -> Vec<&'static str> {
+    COLLECTION_NAMES
+        .into_iter()
+        .filter(|collection_name| !data_path.join(format!("{collection_name}.csv")).is_file())
+        .collect()
+}
+
 /// Returns the path of the file containing rows for the specified collection.
 ///
 /// # Arguments
@@ -25,488 +315,724 @@ fn get_collection_path(data_path: &str, collection_name: &str) -> String {
 
 /// Returns a tuple containing the categories, crates, and keywords loaded from a crates.io database dump.
 ///
+/// Every CSV file is parsed concurrently, with each file split across threads. Rows are then joined once the
+/// collections they reference are loaded.
+///
 /// # Arguments
 /// * `data_path` - the path to the `data` directory inside the database dump.
-pub fn get_data(
-    data_path: &str,
-) -> (
-    AHashMap<String, Category>,
-    AHashMap<String, Crate>,
-    AHashMap<String, Keyword>,
-) {
-    let start = Instant::now();
-    println!("Loading registry graph...");
-
-    let (
-        (mut categories, category_id_lookup),
-        (mut crates, crate_id_lookup),
-        (mut keywords, keyword_id_lookup),
-    ) = (
-        load_vertices::<Category>(data_path, "categories"),
-        load_vertices::<Crate>(data_path, "crates"),
-        load_vertices::<Keyword>(data_path, "keywords"),
-    );
-
-    let versions_to_crates = create_versioned_crates(data_path, &mut crates, &crate_id_lookup);
+/// * `recorder` - the `LoadRecorder` to record loading phases with.
+///
+/// # Errors
+/// * Returns a `LoadError` if any file cannot be read or the rows of the dump are invalid.
+pub fn get_data(data_path: &str, recorder: &LoadRecorder) -> Result<RegistryData, LoadError> {
+    let mut categories = None;
+    let mut crates = None;
+    let mut keywords = None;
+    let mut versions = None;
+    let mut sql_dependencies = None;
+    let mut crate_categories = None;
+    let mut crate_keywords = None;
+
+    rayon::scope(|scope| {
+        scope.spawn(|_| {
+            categories = Some(load_vertices::<Category>(data_path, "categories", recorder));
+        });
+        scope.spawn(|_| crates = Some(load_vertices::<Crate>(data_path, "crates", recorder)));
+        scope.spawn(|_| keywords = Some(load_vertices::<Keyword>(data_path, "keywords", recorder)));
+        scope.spawn(|_| versions = Some(get_versions(data_path, recorder)));
+        scope.spawn(|_| {
+            sql_dependencies = Some(load_rows::<SqlDependency>(
+                data_path,
+                "dependencies",
+                recorder,
+            ));
+        });
+        scope.spawn(|_| {
+            crate_categories = Some(load_rows::<CrateCategory>(
+                data_path,
+                "crates_categories",
+                recorder,
+            ));
+        });
+        scope.spawn(|_| {
+            crate_keywords = Some(load_rows::<CrateKeyword>(
+                data_path,
+                "crates_keywords",
+                recorder,
+            ));
+        });
+    });
+
+    // every task has run once the scope ends
+    let (mut categories, category_id_lookup) = categories.unwrap()?;
+    let (mut crates, crate_id_lookup) = crates.unwrap()?;
+    let (mut keywords, keyword_id_lookup) = keywords.unwrap()?;
+
+    let versions_to_crates =
+        create_versioned_crates(versions.unwrap()?, &mut crates, &crate_id_lookup, recorder)?;
 
     load_dependencies(
-        data_path,
+        sql_dependencies.unwrap()?,
         &mut crates,
         &versions_to_crates,
         &crate_id_lookup,
-    );
+        recorder,
+    )?;
 
     load_crate_categories(
-        data_path,
+        crate_categories.unwrap()?,
         &mut crates,
         &mut categories,
         &crate_id_lookup,
         &category_id_lookup,
-    );
+        recorder,
+    )?;
 
     load_crate_keywords(
-        data_path,
+        crate_keywords.unwrap()?,
         &mut crates,
         &mut keywords,
         &crate_id_lookup,
         &keyword_id_lookup,
-    );
+        recorder,
+    )?;
 
-    alphabetize_crate_contents(&mut crates);
+    alphabetize_crate_contents(&mut crates, recorder);
+    add_implicit_features(&mut crates, recorder);
 
-    println!(
-        "Finished loading registry graph in {} seconds.",
-        start.elapsed().as_secs_f64()
-    );
+    recorder.time_items("centrality", || {
+        centrality::assign_centrality(&mut crates);
+        ((), crates.len())
+    });
 
-    (categories, crates, keywords)
+    Ok((categories, crates, keywords))
 }
 
-/// Loads vertices (categories, crates, keywords) from a CSV file in the database dump.
+/// Returns the rows of a CSV file in the database dump, split into chunks which are parsed in parallel.
 ///
-/// Returns a tuple containing a map from names to vertices and a map from SQL ids to names.
+/// The chunks are returned in the order they appear in the file.
 ///
 /// # Arguments
 /// * `data_path` - the path to the `data` directory inside the database dump.
-/// * `collection_name` - the name of the vertex collection.
-fn load_vertices<T: DeserializeOwned + Vertex + Debug>(
+/// * `collection_name` - the name of the collection.
+///
+/// # Errors
+/// * Returns a `LoadError` if the file cannot be read or a row cannot be deserialized.
+fn read_rows<T: DeserializeOwned + Send>(
     data_path: &str,
     collection_name: &str,
-) -> (AHashMap<String, T>, AHashMap<usize, String>) {
-    println!("Loading {collection_name}...");
-    let start = Instant::now();
-    let mut count = 0_usize;
+) -> Result<Vec<Vec<T>>, LoadError> {
+    Ok(fold_rows(data_path, collection_name, Vec::new, Vec::push)?
+        .into_iter()
+        .map(|(rows, _)| rows)
+        .collect())
+}
 
+/// Folds the rows of a CSV file in the database dump, split into byte ranges which are read and folded in parallel.
+///
+/// Each range is streamed from the file, so rows are folded as they are parsed rather than being held in memory.
+/// The result of folding each range is returned with its number of rows, in the order the ranges appear in the file.
+///
+/// # Arguments
+/// * `data_path` - the path to the `data` directory inside the database dump.
+/// * `collection_name` - the name of the collection.
+/// * `init` - a function returning the initial value folded over each range.
+/// * `fold` - a function folding a row into the value of its range.
+///
+/// # Errors
+/// * Returns a `LoadError` if the file cannot be read or a row cannot be deserialized.
+fn fold_rows<T: DeserializeOwned, A: Send>(
+    data_path: &str,
+    collection_name: &str,
+    init: impl Fn() -> A + Sync,
+    fold: impl Fn(&mut A, T) + Sync,
+) -> Result<Vec<(A, usize)>, LoadError> {
     let file_path = get_collection_path(data_path, collection_name);
+    let (header, boundaries) =
+        get_chunk_boundaries(&file_path, rayon::current_num_threads() * CHUNKS_PER_THREAD)?;
+
+    boundaries
+        .par_windows(2)
+        .map(|bounds| {
+            let mut file = File::open(&file_path).map_err(read_error(&file_path))?;
+            file.seek(SeekFrom::Start(bounds[0]))
+                .map_err(read_error(&file_path))?;
+
+            // each range is parsed with a copy of the header, so that rows are deserialized by column name
+            let mut value = init();
+            let mut count = 0_usize;
+            for result in
+                Reader::from_reader(header.chain(file.take(bounds[1] - bounds[0]))).deserialize()
+            {
+                let row = result.map_err(|err| {
+                    LoadError::Invalid(format!(
+                        "unable to deserialize entry of {} as {}: {}",
+                        file_path,
+                        any::type_name::<T>(),
+                        err
+                    ))
+                })?;
+
+                fold(&mut value, row);
+                count += 1;
+            }
 
-    // map names to objects
-    let mut collection = AHashMap::<String, T>::new();
-
-    // map SQL ids to names
-    let mut id_lookup = AHashMap::<usize, String>::new();
-
-    for result in Reader::from_reader(BufReader::new(
-        File::open(Path::new(&file_path)).unwrap_or_else(|_| panic!("Unable to open {file_path}")),
-    ))
-    .deserialize()
-    {
-        count += 1;
-
-        let record: T = result.unwrap_or_else(|_| {
-            panic!(
-                "Unable to deserialize entry {} as {}",
-                count,
-                any::type_name::<T>()
-            )
-        });
+            Ok((value, count))
+        })
+        .collect()
+}
+
+/// Returns the header of a CSV file and the byte offsets splitting its records into roughly equal ranges of whole
+/// records.
+///
+/// The file is scanned through a fixed-size buffer. Newlines inside quoted fields (e.g. multi-line crate
+/// descriptions) are not treated as record boundaries.
+///
+/// # Arguments
+/// * `file_path` - the path of the CSV file.
+/// * `chunk_count` - the number of ranges to aim for.
+///
+/// # Errors
+/// * Returns a `LoadError` if the file cannot be read.
+fn get_chunk_boundaries(
+    file_path: &str,
+    chunk_count: usize,
+) -> Result<(Vec<u8>, Vec<u64>), LoadError> {
+    let file = File::open(file_path).map_err(read_error(file_path))?;
+    let file_length = file.metadata().map_err(read_error(file_path))?.len();
+    let mut reader = BufReader::with_capacity(SCAN_BUFFER_SIZE, file);
+
+    let mut header = vec![];
+    reader
+        .read_until(b'\n', &mut header)
+        .map_err(read_error(file_path))?;
+
+    let mut offset = header.len() as u64;
+    let chunk_length = (file_length - offset) / chunk_count.max(1) as u64 + 1;
+    let mut boundaries = vec![offset];
+    let mut in_quotes = false;
+
+    loop {
+        let buffer = reader.fill_buf().map_err(read_error(file_path))?;
+        if buffer.is_empty() {
+            break;
+        }
+
+        for &byte in buffer {
+            offset += 1;
+
+            match byte {
+                // escaped quotes toggle twice, leaving the state unchanged
+                b'"' => in_quotes = !in_quotes,
+                b'\n'
+                    if !in_quotes && offset - boundaries[boundaries.len() - 1] >= chunk_length =>
+                {
+                    boundaries.push(offset);
+                }
+                _ => {}
+            }
+        }
 
-        id_lookup.insert(record.sql_id(), String::from(record.id()));
-        collection.insert(String::from(record.id()), record);
+        let length = buffer.len();
+        reader.consume(length);
     }
 
-    println!(
-        "Loaded {} {} in {} seconds.",
-        count,
-        collection_name,
-        start.elapsed().as_secs_f64()
-    );
+    if boundaries[boundaries.len() - 1] != offset {
+        boundaries.push(offset);
+    }
 
-    (collection, id_lookup)
+    Ok((header, boundaries))
 }
 
-/// Inserts the contents of one `Version` into another.
+/// Loads the rows of a CSV file in the database dump which are joined once other collections are loaded.
 ///
 /// # Arguments
-/// * `version` - the `Version` with the contents to insert.
-/// * `other` - the `Version` to update.
-fn replace_version(version: Version, other: &mut Version) {
-    let Version {
-        num,
-        id,
-        created_at,
-        features,
-        ..
-    } = version;
+/// * `data_path` - the path to the `data` directory inside the database dump.
+/// * `collection_name` - the name of the collection.
+/// * `recorder` - the `LoadRecorder` to record loading phases with.
+///
+/// # Errors
+/// * Returns a `LoadError` if the file cannot be read or a row cannot be deserialized.
+fn load_rows<T: DeserializeOwned + Send>(
+    data_path: &str,
+    collection_name: &'static str,
+    recorder: &LoadRecorder,
+) -> Result<Vec<Vec<T>>, LoadError> {
+    recorder.try_time_items(collection_name, || {
+        let rows = read_rows::<T>(data_path, collection_name)?;
+        let count = rows.iter().map(Vec::len).sum();
+        Ok((rows, count))
+    })
+}
 
-    other.num = num;
-    other.id = id;
-    other.created_at = created_at;
-    other.features = features;
+/// Loads vertices (categories, crates, keywords) from a CSV file in the database dump.
+///
+/// Returns a tuple containing a map from names to vertices and a map from SQL ids to names.
+///
+/// # Arguments
+/// * `data_path` - the path to the `data` directory inside the database dump.
+/// * `collection_name` - the name of the vertex collection.
+/// * `recorder` - the `LoadRecorder` to record loading phases with.
+///
+/// # Errors
+/// * Returns a `LoadError` if the file cannot be read or a row cannot be deserialized.
+fn load_vertices<T: DeserializeOwned + Vertex + Send>(
+    data_path: &str,
+    collection_name: &'static str,
+    recorder: &LoadRecorder,
+) -> Result<Vertices<T>, LoadError> {
+    recorder.try_time_items(collection_name, || {
+        let mut count = 0_usize;
+
+        // map names to objects
+        let mut collection = AHashMap::<String, T>::new();
+
+        // map SQL ids to names
+        let mut id_lookup = AHashMap::<usize, String>::new();
+
+        // later chunks overwrite earlier ones, as if the file had been read in order
+        for ((chunk_collection, chunk_id_lookup), chunk_count) in fold_rows(
+            data_path,
+            collection_name,
+            || {
+                (
+                    AHashMap::<String, T>::new(),
+                    AHashMap::<usize, String>::new(),
+                )
+            },
+            |(collection, id_lookup), record: T| {
+                id_lookup.insert(record.sql_id(), String::from(record.id()));
+                collection.insert(String::from(record.id()), record);
+            },
+        )? {
+            count += chunk_count;
+            collection.extend(chunk_collection);
+            id_lookup.extend(chunk_id_lookup);
+        }
+
+        Ok(((collection, id_lookup), count))
+    })
 }
 
-/// Returns a map of SQL ids to versions.
+/// Returns if a `Version` should be preferred over another version of the same crate.
+///
+/// Stable versions are preferred to preview versions, and then newer releases to older ones. Versions which are
+/// not SemVer-compliant are only preferred to other non-compliant versions created earlier.
+///
+/// # Arguments
+/// * `version` - the candidate `Version`.
+/// * `existing_version` - the `Version` currently kept.
+fn is_preferred(version: &Version, existing_version: &Version) -> bool {
+    if let Ok(version_num) = semver_version::parse(version.num.as_str()) {
+        if let Ok(existing_version_num) = semver_version::parse(existing_version.num.as_str()) {
+            // if both versions are SemVer adherent
+
+            let version_is_pre = version.is_pre();
+            let existing_version_is_pre = existing_version.is_pre();
+
+            !version_is_pre && existing_version_is_pre // if is stable and existing one isn't
+                || (version_is_pre == existing_version_is_pre // otherwise if the two are the same and the current one is a newer release
+                    && (version_num.major > existing_version_num.major
+                        || (version_num.major == existing_version_num.major
+                            && version_num.minor > existing_version_num.minor)
+                        || (version_num.major == existing_version_num.major
+                            && version_num.minor == existing_version_num.minor
+                            && version_num.patch > existing_version_num.patch)))
+        } else {
+            // if existing version is not SemVer adherent but current one is
+            true
+        }
+    } else {
+        // if both are not SemVer adherent and current was created more recent
+        semver_version::parse(existing_version.num.as_str()).is_err()
+            && version.created_at.cmp(&existing_version.created_at) == Ordering::Greater
+    }
+}
+
+/// Inserts a `Version` into a map of crate SQL ids to versions, keeping the preferred version of each crate.
+///
+/// # Arguments
+/// * `versions` - the map of crate SQL ids to versions.
+/// * `version` - the `Version` to insert.
+fn insert_version(versions: &mut AHashMap<usize, Version>, version: Version) {
+    match versions.entry(version.crate_id) {
+        Entry::Occupied(mut entry) => {
+            if is_preferred(&version, entry.get()) {
+                entry.insert(version);
+            }
+        }
+        Entry::Vacant(entry) => {
+            entry.insert(version);
+        }
+    }
+}
+
+/// Returns a map of crate SQL ids to versions.
 ///
 /// Only the most recent stable version of each crate is kept, if a crate has a stable version.
 /// If the crate does not have a stable version, then the most recent version is used.
 ///
+/// Each chunk of the file is folded in parallel as it is read, after which the chunks are merged in order, so that
+/// ties are broken in favor of the version appearing first.
+///
 /// # Arguments
 /// * `data_path` - the path to the `data` directory inside the database dump.
-fn get_versions(data_path: &str) -> AHashMap<usize, Version> {
-    println!("Loading versions...");
-    let start = Instant::now();
-    let mut versions = AHashMap::<usize, Version>::new();
-    let mut count = 0_usize;
-    let filename = get_collection_path(data_path, "versions");
-
-    for result in Reader::from_reader(BufReader::new(
-        File::open(Path::new(&filename)).unwrap_or_else(|_| panic!("Unable to open {filename}")),
-    ))
-    .deserialize()
-    {
-        count += 1;
-        let version: Version =
-            result.unwrap_or_else(|_| panic!("Unable to deserialize entry {count} as Version"));
-
-        let Version {
-            num,
-            created_at,
-            crate_id,
-            ..
-        } = &version;
-
-        let version_clone = version.clone();
-
-        versions
-            .entry(*crate_id)
-            .and_modify(|existing_version| {
-                // if the crate has a version already
-
-                if let Ok(version_num) = semver_version::parse(num.as_str()) {
-                    if let Ok(existing_version_num) =
-                        semver_version::parse(existing_version.num.as_str())
-                    {
-                        // if both versions are SemVer adherent
-
-                        let version_is_pre = version.is_pre();
-                        let existing_version_is_pre = existing_version.is_pre();
-
-                        if !version_is_pre && existing_version_is_pre // if is stable and existing one isn't
-                            || (version_is_pre == existing_version_is_pre // otherwise if the two are the same and the current one is a newer release
-                                && (version_num.major > existing_version_num.major
-                                    || (version_num.major == existing_version_num.major
-                                        && version_num.minor > existing_version_num.minor)
-                                    || (version_num.major == existing_version_num.major
-                                        && version_num.minor == existing_version_num.minor
-                                        && version_num.patch > existing_version_num.patch)))
-                        {
-                            replace_version(version_clone, existing_version);
-                        }
-                    } else {
-                        // if existing version is not SemVer adherent but current one is
-                        replace_version(version_clone, existing_version);
-                    }
-                } else if semver_version::parse(existing_version.num.as_str()).is_err()
-                    && created_at.cmp(&existing_version.created_at) == Ordering::Greater
-                {
-                    // if both are not SemVer adherent and current was created more recent
-                    replace_version(version_clone, existing_version);
-                }
-            })
-            .or_insert(version);
-    }
-
-    println!(
-        "Processed {} versions in {} seconds.",
-        count,
-        start.elapsed().as_secs_f64()
-    );
+/// * `recorder` - the `LoadRecorder` to record loading phases with.
+///
+/// # Errors
+/// * Returns a `LoadError` if the file cannot be read or a row cannot be deserialized.
+fn get_versions(
+    data_path: &str,
+    recorder: &LoadRecorder,
+) -> Result<AHashMap<usize, Version>, LoadError> {
+    recorder.try_time_items("versions", || {
+        let chunk_versions = fold_rows(
+            data_path,
+            "versions",
+            AHashMap::<usize, Version>::new,
+            insert_version,
+        )?;
+
+        let mut versions = AHashMap::<usize, Version>::new();
+        let mut count = 0_usize;
+
+        for (chunk_versions, chunk_count) in chunk_versions {
+            count += chunk_count;
+
+            for version in chunk_versions.into_values() {
+                insert_version(&mut versions, version);
+            }
+        }
 
-    versions
+        Ok((versions, count))
+    })
 }
 
 /// Assign versions to crates, returning a map of version ids to crate names.
 ///
 /// # Arguments
-/// * `data_path` - the path to the `data` directory inside the database dump.
+/// * `versions` - a map of crate SQL ids to versions.
 /// * `crates` - a map of crate names to values.
 /// * `crate_id_lookup` - a map of crate SQL ids to names.
+/// * `recorder` - the `LoadRecorder` to record loading phases with.
+///
+/// # Errors
+/// * Returns a `LoadError` if a version belongs to a nonexistent crate or has features which cannot be parsed.
 fn create_versioned_crates(
-    data_path: &str,
+    versions: AHashMap<usize, Version>,
     crates: &mut AHashMap<String, Crate>,
     crate_id_lookup: &AHashMap<usize, String>,
-) -> AHashMap<usize, String> {
-    let versions = get_versions(data_path);
-    let mut version_to_crates = AHashMap::<usize, String>::new();
-    println!("Creating versioned crates...");
+    recorder: &LoadRecorder,
+) -> Result<AHashMap<usize, String>, LoadError> {
+    recorder.try_time_items("versioned crates", || {
+        let mut version_to_crates = AHashMap::<usize, String>::new();
 
-    let start = Instant::now();
-
-    for Version {
-        crate_id,
-        created_at,
-        features,
-        id,
-        num,
-    } in versions.values()
-    {
-        let crate_id = crate_id_lookup
-            .get(crate_id)
-            .unwrap_or_else(|| panic!("Crate with SQL id {crate_id} does not exist"));
-
-        let version_crate = crates
-            .get_mut(crate_id)
-            .unwrap_or_else(|| panic!("Crate with id {crate_id} does not exist"));
-
-        version_crate.created_at = *created_at;
-        version_crate.features = serde_json::from_str(features)
-            .unwrap_or_else(|_| panic!("Unable to deserialize {features} as AHashMap"));
-        version_crate.version = num.clone();
-
-        version_to_crates.insert(id.to_owned(), crate_id.clone());
-    }
-
-    println!(
-        "Created versioned crates in {} seconds.",
-        start.elapsed().as_secs_f64()
-    );
+        for Version {
+            crate_id,
+            created_at,
+            features,
+            id,
+            license,
+            num,
+        } in versions.into_values()
+        {
+            let crate_id = crate_id_lookup.get(&crate_id).ok_or_else(|| {
+                LoadError::Invalid(format!("crate with SQL id {crate_id} does not exist"))
+            })?;
+
+            let version_crate = crates.get_mut(crate_id).ok_or_else(|| {
+                LoadError::Invalid(format!("crate with id {crate_id} does not exist"))
+            })?;
+
+            version_crate.created_at = created_at;
+            version_crate.features = serde_json::from_str(&features).map_err(|_| {
+                LoadError::Invalid(format!("unable to deserialize {features} as AHashMap"))
+            })?;
+            version_crate.license = license;
+            version_crate.version = num;
+
+            version_to_crates.insert(id, crate_id.clone());
+        }
 
-    version_to_crates
+        let count = version_to_crates.len();
+        Ok((version_to_crates, count))
+    })
 }
 
 /// Loads dependencies from a crates.io database dump.
 ///
+/// Each chunk of rows is converted in parallel, after which dependencies are assigned to crates in order.
+///
 /// # Arguments
-/// * `data_path` - the path to the `data` directory inside the database dump.
+/// * `sql_dependencies` - the chunks of dependency rows of the database dump.
 /// * `crates` - a map of crate names to values.
 /// * `versions_to_crates` - a map of version ids to crate names.
 /// * `crate_id_lookup` - a map of crate SQL ids to names.
+/// * `recorder` - the `LoadRecorder` to record loading phases with.
+///
+/// # Errors
+/// * Returns a `LoadError` if a dependency references a nonexistent crate.
 fn load_dependencies(
-    data_path: &str,
+    sql_dependencies: Vec<Vec<SqlDependency>>,
     crates: &mut AHashMap<String, Crate>,
     versions_to_crates: &AHashMap<usize, String>,
     crate_id_lookup: &AHashMap<usize, String>,
-) {
-    println!("Loading dependencies...");
-    let start = Instant::now();
-    let mut count = 0_usize;
-    let dependencies_path = get_collection_path(data_path, "dependencies");
-
-    for result in Reader::from_reader(BufReader::new(
-        File::open(Path::new(&dependencies_path))
-            .unwrap_or_else(|_| panic!("Unable to open {dependencies_path}")),
-    ))
-    .deserialize()
-    {
-        let sql_dependency: SqlDependency =
-            result.unwrap_or_else(|_| panic!("Unable to deserialize entry {count} as Dependency"));
-        let SqlDependency {
-            default_features,
-            features,
-            kind,
-            optional,
-            target,
-            version_id,
-            ..
-        } = sql_dependency;
+    recorder: &LoadRecorder,
+) -> Result<(), LoadError> {
+    recorder.try_time_items("dependency edges", || {
+        let dependencies: Vec<Vec<Dependency>> = sql_dependencies
+            .into_par_iter()
+            .map(|rows| {
+                rows.into_iter()
+                    .filter_map(|sql_dependency| {
+                        get_dependency(sql_dependency, versions_to_crates, crate_id_lookup)
+                            .transpose()
+                    })
+                    .collect()
+            })
+            .collect::<Result<_, LoadError>>()?;
 
-        let sql_dependency_crate_id = sql_dependency.crate_id;
+        let mut count = 0_usize;
 
-        if let Some(from) = versions_to_crates.get(&version_id) {
-            if kind == 0 {
-                count += 1;
+        for dependency in dependencies.into_iter().flatten() {
+            count += 1;
 
-                crates
-                    .get_mut(from)
-                    .unwrap_or_else(|| panic!("Crate with id {from} not found"))
-                    .dependencies
-                    .push(Dependency {
-                        default_features: default_features == "t",
-                        features: String::from(&features[1..features.len() - 1]) // convert brace array to array ({a, b, c} => [a, b, c])
-                            .split(',')
-                            .filter_map(|split| {
-                                if split.is_empty() {
-                                    None
-                                } else {
-                                    Some(String::from(split))
-                                }
-                            })
-                            .collect(),
-                        from: from.clone(),
-                        optional: optional == "t",
-                        target: if target.is_empty() {
-                            None
-                        } else {
-                            Some(target)
-                        },
-                        to: crate_id_lookup
-                            .get(&sql_dependency_crate_id)
-                            .unwrap_or_else(|| {
-                                panic!("Crate with id {sql_dependency_crate_id} not found")
-                            })
-                            .clone(),
-                    });
-            }
+            crates
+                .get_mut(&dependency.from)
+                .ok_or_else(|| {
+                    LoadError::Invalid(format!("crate with id {} not found", dependency.from))
+                })?
+                .dependencies
+                .push(dependency);
         }
-    }
-    println!(
-        "Loaded {} dependencies into database in {} seconds.",
-        count,
-        start.elapsed().as_secs_f64()
-    );
+
+        Ok(((), count))
+    })
+}
+
+/// Converts a dependency row of the database dump into a `Dependency`.
+///
+/// Returns `None` if the dependency belongs to a version which is not tracked.
+///
+/// # Arguments
+/// * `sql_dependency` - the dependency row.
+/// * `versions_to_crates` - a map of version ids to crate names.
+/// * `crate_id_lookup` - a map of crate SQL ids to names.
+///
+/// # Errors
+/// * Returns a `LoadError` if the dependency references a nonexistent crate.
+fn get_dependency(
+    sql_dependency: SqlDependency,
+    versions_to_crates: &AHashMap<usize, String>,
+    crate_id_lookup: &AHashMap<usize, String>,
+) -> Result<Option<Dependency>, LoadError> {
+    let SqlDependency {
+        crate_id,
+        default_features,
+        features,
+        kind,
+        optional,
+        target,
+        version_id,
+    } = sql_dependency;
+
+    let Some(from) = versions_to_crates.get(&version_id) else {
+        return Ok(None);
+    };
+
+    let to = crate_id_lookup
+        .get(&crate_id)
+        .ok_or_else(|| LoadError::Invalid(format!("crate with id {crate_id} not found")))?;
+
+    Ok(Some(Dependency {
+        default_features: default_features == "t",
+        features: String::from(&features[1..features.len() - 1]) // convert brace array to array ({a, b, c} => [a, b, c])
+            .split(',')
+            .filter_map(|split| {
+                if split.is_empty() {
+                    None
+                } else {
+                    Some(String::from(split))
+                }
+            })
+            .collect(),
+        from: from.clone(),
+        optional: optional == "t",
+        target: if target.is_empty() {
+            None
+        } else {
+            Some(target)
+        },
+        to: to.clone(),
+        kind: DependencyKind::from(kind),
+    }))
 }
 
 /// Loads crate-category relationships from a crates.io database dump.
 ///
 /// # Arguments
-/// * `data_path` - the path to the `data` directory inside the database dump.
+/// * `crate_categories` - the chunks of crate-category rows of the database dump.
 /// * `crates` - a map of crate names to values.
 /// * `categories` - a map of category names to values.
 /// * `crate_id_lookup` - a map of crate SQL ids to names.
 /// * `category_id_lookup` - a map of category SQL ids to names.
+/// * `recorder` - the `LoadRecorder` to record loading phases with.
+///
+/// # Errors
+/// * Returns a `LoadError` if a row references a nonexistent crate or category.
 fn load_crate_categories(
-    data_path: &str,
+    crate_categories: Vec<Vec<CrateCategory>>,
     crates: &mut AHashMap<String, Crate>,
     categories: &mut AHashMap<String, Category>,
     crate_id_lookup: &AHashMap<usize, String>,
     category_id_lookup: &AHashMap<usize, String>,
-) {
-    println!("Loading crate categories...");
-    let start = Instant::now();
-    let mut count = 0_usize;
-
-    let file_path = get_collection_path(data_path, "crates_categories");
-
-    for result in Reader::from_reader(BufReader::new(
-        File::open(Path::new(&file_path)).unwrap_or_else(|_| panic!("Unable to open {file_path}")),
-    ))
-    .deserialize()
-    {
-        count += 1;
-        let CrateCategory {
+    recorder: &LoadRecorder,
+) -> Result<(), LoadError> {
+    recorder.try_time_items("crate category edges", || {
+        let mut count = 0_usize;
+
+        for CrateCategory {
             category_id,
             crate_id,
-        } = result
-            .unwrap_or_else(|_| panic!("Unable to deserialize entry {count} as crate category"));
-
-        let category_id = category_id_lookup
-            .get(&category_id)
-            .unwrap_or_else(|| panic!("Category with id {category_id} not found"));
-
-        let crate_id = crate_id_lookup
-            .get(&crate_id)
-            .unwrap_or_else(|| panic!("Crate with id {crate_id} not found"));
-
-        crates
-            .get_mut(crate_id)
-            .unwrap_or_else(|| panic!("Crate with id {crate_id} not found"))
-            .categories
-            .push(category_id.clone());
-
-        categories
-            .get_mut(category_id)
-            .unwrap_or_else(|| panic!("Category with id {category_id} not found"))
-            .crates
-            .push(crate_id.clone());
-    }
+        } in crate_categories.into_iter().flatten()
+        {
+            count += 1;
+
+            let category_id = category_id_lookup.get(&category_id).ok_or_else(|| {
+                LoadError::Invalid(format!("category with id {category_id} not found"))
+            })?;
+
+            let crate_id = crate_id_lookup
+                .get(&crate_id)
+                .ok_or_else(|| LoadError::Invalid(format!("crate with id {crate_id} not found")))?;
+
+            crates
+                .get_mut(crate_id)
+                .ok_or_else(|| LoadError::Invalid(format!("crate with id {crate_id} not found")))?
+                .categories
+                .push(category_id.clone());
+
+            categories
+                .get_mut(category_id)
+                .ok_or_else(|| {
+                    LoadError::Invalid(format!("category with id {category_id} not found"))
+                })?
+                .crates
+                .push(crate_id.clone());
+        }
 
-    println!(
-        "Loaded {} crate categories in {} seconds.",
-        count,
-        start.elapsed().as_secs_f64()
-    );
+        Ok(((), count))
+    })
 }
 
 /// Loads crate-keywords relationships from a crates.io database dump.
 ///
 /// # Arguments
-/// * `data_path` - the path to the `data` directory inside the database dump.
+/// * `crate_keywords` - the chunks of crate-keyword rows of the database dump.
 /// * `crates` - a map of crate names to values.
 /// * `keywords` - a map of keyword names to values.
 /// * `crate_id_lookup` - a map of crate SQL ids to names.
 /// * `keyword_id_lookup` - a map of keyword SQL ids to names.
+/// * `recorder` - the `LoadRecorder` to record loading phases with.
+///
+/// # Errors
+/// * Returns a `LoadError` if a row references a nonexistent crate or keyword.
 fn load_crate_keywords(
-    data_path: &str,
+    crate_keywords: Vec<Vec<CrateKeyword>>,
     crates: &mut AHashMap<String, Crate>,
     keywords: &mut AHashMap<String, Keyword>,
     crate_id_lookup: &AHashMap<usize, String>,
     keyword_id_lookup: &AHashMap<usize, String>,
-) {
-    println!("Loading crate keywords...");
-    let start = Instant::now();
-    let mut count = 0_usize;
-
-    let file_path = get_collection_path(data_path, "crates_keywords");
-
-    for result in Reader::from_reader(BufReader::new(
-        File::open(Path::new(&file_path)).unwrap_or_else(|_| panic!("Unable to open {file_path}")),
-    ))
-    .deserialize()
-    {
-        count += 1;
-        let CrateKeyword {
+    recorder: &LoadRecorder,
+) -> Result<(), LoadError> {
+    recorder.try_time_items("crate keyword edges", || {
+        let mut count = 0_usize;
+
+        for CrateKeyword {
             crate_id,
             keyword_id,
-        } = result
-            .unwrap_or_else(|_| panic!("Unable to deserialize entry {count} as crate keyword"));
-
-        let crate_id = crate_id_lookup
-            .get(&crate_id)
-            .unwrap_or_else(|| panic!("Unable to find crate with id {crate_id}"));
-
-        let keyword_id = keyword_id_lookup
-            .get(&keyword_id)
-            .unwrap_or_else(|| panic!("Unable to find keyword with id {keyword_id}"));
-
-        crates
-            .get_mut(crate_id)
-            .unwrap_or_else(|| panic!("Crate with id {crate_id} not found"))
-            .keywords
-            .push(keyword_id.clone());
-
-        keywords
-            .get_mut(keyword_id)
-            .unwrap_or_else(|| panic!("Keyword with id {keyword_id} not found"))
-            .crates
-            .push(crate_id.clone());
-    }
+        } in crate_keywords.into_iter().flatten()
+        {
+            count += 1;
+
+            let crate_id = crate_id_lookup
+                .get(&crate_id)
+                .ok_or_else(|| LoadError::Invalid(format!("crate with id {crate_id} not found")))?;
+
+            let keyword_id = keyword_id_lookup.get(&keyword_id).ok_or_else(|| {
+                LoadError::Invalid(format!("keyword with id {keyword_id} not found"))
+            })?;
+
+            crates
+                .get_mut(crate_id)
+                .ok_or_else(|| LoadError::Invalid(format!("crate with id {crate_id} not found")))?
+                .keywords
+                .push(keyword_id.clone());
+
+            keywords
+                .get_mut(keyword_id)
+                .ok_or_else(|| {
+                    LoadError::Invalid(format!("keyword with id {keyword_id} not found"))
+                })?
+                .crates
+                .push(crate_id.clone());
+        }
 
-    println!(
-        "Loaded {} crate keywords in {} seconds.",
-        count,
-        start.elapsed().as_secs_f64()
-    );
+        Ok(((), count))
+    })
 }
 
 /// Alphabetize crate category, dependency, and keyword lists.
 ///
 /// # Arguments
 /// * `crates` - a map of crate names to values.
-fn alphabetize_crate_contents(crates: &mut AHashMap<String, Crate>) {
-    println!("Alphabetizing crate contents...");
-    let start = Instant::now();
-
-    for crate_val in crates.values_mut() {
-        crate_val.categories.sort_unstable();
-        crate_val.keywords.sort_unstable();
-        crate_val
-            .dependencies
-            .sort_unstable_by_key(|dependency| dependency.to.clone());
-    }
+/// * `recorder` - the `LoadRecorder` to record loading phases with.
+fn alphabetize_crate_contents(crates: &mut AHashMap<String, Crate>, recorder: &LoadRecorder) {
+    recorder.time_items("alphabetize", || {
+        crates.par_iter_mut().for_each(|(_, crate_val)| {
+            crate_val.categories.sort_unstable();
+            crate_val.keywords.sort_unstable();
+            crate_val
+                .dependencies
+                .sort_unstable_by_key(|dependency| dependency.to.clone());
+        });
+
+        ((), crates.len())
+    });
+}
 
-    println!(
-        "Alphabetized crate contents in {} seconds.",
-        start.elapsed().as_secs_f64()
-    );
+/// Exposes the optional dependencies of each crate which are not referenced through `dep:` by any feature as features,
+/// as Cargo does.
+///
+/// # Arguments
+/// * `crates` - a map of crate names to values, with dependencies loaded.
+/// * `recorder` - the `LoadRecorder` to record loading phases with.
+fn add_implicit_features(crates: &mut AHashMap<String, Crate>, recorder: &LoadRecorder) {
+    recorder.time_items("implicit features", || {
+        let count = crates
+            .par_iter_mut()
+            .map(|(_, crate_val)| {
+                // the dump records the crate a dependency refers to rather than the name it is declared under
+                let optional_names: BTreeMap<String, String> = crate_val
+                    .dependencies
+                    .iter()
+                    .filter(|dependency| dependency.optional)
+                    .map(|dependency| (dependency.to.clone(), dependency.to.clone()))
+                    .collect();
+
+                let explicit_count = crate_val.features.len();
+                crate_val.features = get_manifest_features(
+                    mem::take(&mut crate_val.features),
+                    &BTreeMap::new(),
+                    optional_names,
+                );
+
+                crate_val.features.len() - explicit_count
+            })
+            .sum();
+
+        ((), count)
+    });
 }
 
 /// A struct for deserialization based on the structure in targets.txt.
@@ -523,26 +1049,33 @@ struct Target {
 ///
 /// # Arguments
 /// * `filename` - the file to load from.
-pub fn get_targets(filename: &str) -> BTreeMap<String, Vec<Cfg>> {
+///
+/// # Errors
+/// * Returns a `LoadError` if the file cannot be read or has invalid entries.
+pub fn get_targets(filename: &str) -> Result<BTreeMap<String, Vec<Cfg>>, LoadError> {
     ReaderBuilder::new()
         .delimiter(b';')
         .from_reader(BufReader::new(
-            File::open(filename).unwrap_or_else(|_| panic!("Error opening {filename}.")),
+            File::open(filename).map_err(read_error(filename))?,
         ))
         .deserialize()
         .map(|record| {
-            let target: Target = record.unwrap();
+            let target: Target =
+                record.map_err(|err| LoadError::Read(String::from(filename), err.to_string()))?;
             let cfgs = target
                 .cfgs
                 .iter()
                 .map(|cfg| match cfg.len() {
-                    1 => Cfg::Name(cfg[0].clone()),
-                    2 => Cfg::KeyPair(cfg[0].clone(), cfg[1].clone()),
-                    _ => panic!("Invalid cfg entry: {cfg:?}."),
+                    1 => Ok(Cfg::Name(cfg[0].clone())),
+                    2 => Ok(Cfg::KeyPair(cfg[0].clone(), cfg[1].clone())),
+                    _ => Err(LoadError::Read(
+                        String::from(filename),
+                        format!("invalid cfg entry {cfg:?}"),
+                    )),
                 })
-                .collect();
+                .collect::<Result<Vec<Cfg>, LoadError>>()?;
 
-            (target.triple, cfgs)
+            Ok((target.triple, cfgs))
         })
         .collect()
 }
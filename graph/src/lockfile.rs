@@ -0,0 +1,497 @@
+use super::{DependencyEdge, DependencyKind, Graph};
+use ahash::AHashMap;
+use semver_parser::version::{self as semver_version, Version as SemverVersion};
+use serde::{Deserialize, Serialize};
+use std::{
+    cmp::Ordering,
+    collections::{BTreeMap, VecDeque},
+    error::Error,
+    fmt::{self, Display, Formatter},
+};
+
+/// The sources under which Cargo records packages from crates.io.
+pub const CRATES_IO_SOURCES: [&str; 2] = [
+    "registry+https://github.com/rust-lang/crates.io-index",
+    "sparse+https://index.crates.io/",
+];
+
+/// An error encountered while parsing a `Cargo.lock` lockfile.
+#[derive(Debug, Eq, PartialEq)]
+pub enum LockfileError {
+    /// The lockfile is not valid TOML or does not follow the lockfile format, with a description of the problem.
+    Invalid(String),
+
+    /// A package depends on a package which is not in the lockfile, with the names of both.
+    UnknownDependency(String, String),
+}
+
+impl Display for LockfileError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::Invalid(message) => write!(f, "Invalid lockfile: {message}"),
+            Self::UnknownDependency(package, dependency) => write!(
+                f,
+                "Invalid lockfile: package {package} depends on {dependency}, which is not locked."
+            ),
+        }
+    }
+}
+
+impl Error for LockfileError {}
+
+/// A parsed `Cargo.lock` lockfile.
+#[derive(Debug)]
+pub struct Lockfile {
+    /// The locked packages, in lockfile order.
+    pub packages: Vec<LockedPackage>,
+}
+
+/// A package pinned by a lockfile.
+#[derive(Debug, Serialize)]
+pub struct LockedPackage {
+    /// The name of the package.
+    pub name: String,
+
+    /// The locked version of the package.
+    pub version: String,
+
+    /// The source of the package, or `None` for packages of the local workspace.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+
+    /// The indices of the packages this package depends on.
+    #[serde(skip)]
+    pub dependencies: Vec<usize>,
+}
+
+/// Where a locked package comes from.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LockedSource {
+    /// A member of the local workspace or a path dependency.
+    Local,
+
+    /// The crates.io registry.
+    CratesIo,
+
+    /// Another registry or a git repository.
+    Other,
+}
+
+impl LockedPackage {
+    /// Returns where the package comes from.
+    #[must_use]
+    pub fn source_kind(&self) -> LockedSource {
+        match &self.source {
+            None => LockedSource::Local,
+            Some(source) if CRATES_IO_SOURCES.contains(&source.as_str()) => LockedSource::CratesIo,
+            Some(_) => LockedSource::Other,
+        }
+    }
+}
+
+impl Lockfile {
+    /// Parses the contents of a `Cargo.lock` lockfile.
+    ///
+    /// Every lockfile format is supported, with dependencies written as `name`, `name version` or
+    /// `name version (source)`.
+    ///
+    /// # Arguments
+    /// * `lockfile` - the contents of the lockfile.
+    ///
+    /// # Errors
+    /// * Returns `LockfileError::Invalid` if the lockfile cannot be parsed.
+    /// * Returns `LockfileError::UnknownDependency` if a dependency does not match exactly one locked package.
+    pub fn parse(lockfile: &str) -> Result<Self, LockfileError> {
+        let TomlLockfile { package } =
+            toml::from_str(lockfile).map_err(|err| LockfileError::Invalid(err.to_string()))?;
+
+        // the indices of the packages sharing each name
+        let mut package_indices: AHashMap<&str, Vec<usize>> = AHashMap::new();
+        for (index, locked_package) in package.iter().enumerate() {
+            package_indices
+                .entry(locked_package.name.as_str())
+                .or_default()
+                .push(index);
+        }
+
+        let mut dependencies: Vec<Vec<usize>> = Vec::with_capacity(package.len());
+        for locked_package in &package {
+            let mut package_dependencies: Vec<usize> = vec![];
+
+            for dependency in &locked_package.dependencies {
+                let mut parts = dependency.splitn(3, ' ');
+                let name = parts.next().unwrap_or_default();
+                let version = parts.next();
+                let source = parts
+                    .next()
+                    .map(|source| source.trim_start_matches('(').trim_end_matches(')'));
+
+                let matches: Vec<usize> = package_indices
+                    .get(name)
+                    .map(|indices| {
+                        indices
+                            .iter()
+                            .copied()
+                            .filter(|&index| {
+                                version.is_none_or(|version| package[index].version == version)
+                                    && source.is_none_or(|source| {
+                                        package[index].source.as_deref() == Some(source)
+                                    })
+                            })
+                            .collect()
+                    })
+                    .unwrap_or_default();
+
+                let [index] = matches[..] else {
+                    return Err(LockfileError::UnknownDependency(
+                        locked_package.name.clone(),
+                        dependency.clone(),
+                    ));
+                };
+
+                package_dependencies.push(index);
+            }
+
+            dependencies.push(package_dependencies);
+        }
+
+        Ok(Self {
+            packages: package
+                .into_iter()
+                .zip(dependencies)
+                .map(|(locked_package, dependencies)| LockedPackage {
+                    name: locked_package.name,
+                    version: locked_package.version,
+                    source: locked_package.source,
+                    dependencies,
+                })
+                .collect(),
+        })
+    }
+}
+
+impl Graph {
+    /// Matches the packages of a lockfile against the registry.
+    ///
+    /// Packages from crates.io are looked up by name, and reported as missing if absent from the registry or as
+    /// outdated if locked to an older version than the registry's most recent stable one. Each dependency edge takes
+    /// its kind, features and target from the registry's declaration where one exists, which reflects the most recent
+    /// version of the crate rather than the locked one.
+    ///
+    /// # Arguments
+    /// * `lockfile` - the `Lockfile` to analyze.
+    #[must_use]
+    pub fn analyze_lockfile<'a>(&'a self, lockfile: &'a Lockfile) -> LockfileAnalysis<'a> {
+        let packages = &lockfile.packages;
+        let distances = get_distances(packages);
+
+        let mut crates: Vec<LockedCrate> = Vec::with_capacity(packages.len());
+        let mut missing: Vec<&LockedPackage> = vec![];
+        let mut outdated: Vec<OutdatedPackage> = vec![];
+
+        for (locked_package, distance) in packages.iter().zip(distances) {
+            let source = locked_package.source_kind();
+            let crate_val = (source == LockedSource::CratesIo)
+                .then(|| self.crates().get(&locked_package.name))
+                .flatten();
+
+            if let Some(crate_val) = crate_val {
+                if is_newer(&crate_val.version, &locked_package.version) {
+                    outdated.push(OutdatedPackage {
+                        name: &locked_package.name,
+                        version: &locked_package.version,
+                        latest_version: &crate_val.version,
+                    });
+                }
+            } else if source == LockedSource::CratesIo {
+                missing.push(locked_package);
+            }
+
+            crates.push(LockedCrate {
+                distance,
+                name: &locked_package.name,
+                registry: crate_val.map(|crate_val| RegistryMetadata {
+                    categories: &crate_val.categories,
+                    description: &crate_val.description,
+                    downloads: crate_val.downloads,
+                    latest_version: &crate_val.version,
+                }),
+                source,
+                version: &locked_package.version,
+            });
+        }
+
+        LockfileAnalysis {
+            dependencies: self.get_locked_dependencies(packages),
+            duplicates: get_duplicates(packages),
+            crates,
+            missing,
+            outdated,
+        }
+    }
+
+    /// Returns the dependency edges of a lockfile.
+    ///
+    /// # Arguments
+    /// * `packages` - the locked packages.
+    fn get_locked_dependencies<'a>(
+        &'a self,
+        packages: &'a [LockedPackage],
+    ) -> Vec<LockedDependencyEdge<'a>> {
+        let mut dependencies: Vec<LockedDependencyEdge> = vec![];
+
+        for locked_package in packages {
+            let declarations = (locked_package.source_kind() == LockedSource::CratesIo)
+                .then(|| self.get_dependencies(&locked_package.name))
+                .flatten()
+                .unwrap_or_default();
+
+            for &index in &locked_package.dependencies {
+                let dependency = &packages[index];
+
+                // prefer normal dependencies, as the lockfile does not record kinds
+                let edge = declarations
+                    .iter()
+                    .filter(|declaration| *declaration.to == dependency.name)
+                    .min_by_key(|declaration| declaration.kind)
+                    .cloned()
+                    .unwrap_or_else(|| DependencyEdge {
+                        activated_by: vec![],
+                        active: true,
+                        default_features: true,
+                        features: vec![],
+                        from: &locked_package.name,
+                        kind: DependencyKind::Normal,
+                        optional: false,
+                        target: None,
+                        to: &dependency.name,
+                    });
+
+                dependencies.push(LockedDependencyEdge {
+                    edge,
+                    from_version: &locked_package.version,
+                    to_version: &dependency.version,
+                });
+            }
+        }
+
+        dependencies
+    }
+}
+
+/// Returns the distance of each locked package from the local workspace.
+///
+/// If the lockfile has no local packages, distances are measured from packages no other package depends on.
+///
+/// # Arguments
+/// * `packages` - the locked packages.
+fn get_distances(packages: &[LockedPackage]) -> Vec<Option<usize>> {
+    let mut distances: Vec<Option<usize>> = vec![None; packages.len()];
+    let mut queue: VecDeque<usize> = VecDeque::new();
+
+    let mut roots: Vec<usize> = (0..packages.len())
+        .filter(|&index| packages[index].source.is_none())
+        .collect();
+    if roots.is_empty() {
+        let mut is_dependency = vec![false; packages.len()];
+        for locked_package in packages {
+            for &index in &locked_package.dependencies {
+                is_dependency[index] = true;
+            }
+        }
+
+        roots = (0..packages.len())
+            .filter(|&index| !is_dependency[index])
+            .collect();
+    }
+
+    for index in roots {
+        distances[index] = Some(0);
+        queue.push_back(index);
+    }
+
+    while let Some(index) = queue.pop_front() {
+        let distance = distances[index].unwrap() + 1;
+
+        for &dependency_index in &packages[index].dependencies {
+            if distances[dependency_index].is_none() {
+                distances[dependency_index] = Some(distance);
+                queue.push_back(dependency_index);
+            }
+        }
+    }
+
+    distances
+}
+
+/// Returns the packages locked at several versions, by name.
+///
+/// # Arguments
+/// * `packages` - the locked packages.
+fn get_duplicates(packages: &[LockedPackage]) -> Vec<DuplicatePackage<'_>> {
+    let mut versions: BTreeMap<&String, Vec<&String>> = BTreeMap::new();
+
+    for locked_package in packages {
+        let package_versions = versions.entry(&locked_package.name).or_default();
+
+        if !package_versions.contains(&&locked_package.version) {
+            package_versions.push(&locked_package.version);
+        }
+    }
+
+    versions
+        .into_iter()
+        .filter(|(_, versions)| versions.len() > 1)
+        .map(|(name, versions)| DuplicatePackage { name, versions })
+        .collect()
+}
+
+/// Returns if a version is newer than another by `SemVer` precedence, ignoring build metadata.
+///
+/// Versions which are not SemVer-compliant are never considered newer.
+///
+/// # Arguments
+/// * `version` - the candidate version.
+/// * `other_version` - the version to compare against.
+fn is_newer(version: &str, other_version: &str) -> bool {
+    let (Ok(version), Ok(other_version)) = (
+        semver_version::parse(version),
+        semver_version::parse(other_version),
+    ) else {
+        return false;
+    };
+
+    let release = |version: &SemverVersion| (version.major, version.minor, version.patch);
+
+    // pre-releases precede the release they lead up to
+    release(&version)
+        .cmp(&release(&other_version))
+        .then_with(
+            || match (version.pre.is_empty(), other_version.pre.is_empty()) {
+                (true, true) => Ordering::Equal,
+                (true, false) => Ordering::Greater,
+                (false, true) => Ordering::Less,
+                (false, false) => version.pre.cmp(&other_version.pre),
+            },
+        )
+        .is_gt()
+}
+
+#[derive(Serialize)]
+/// The result of matching a lockfile against the registry, with crates and dependencies shaped as in a
+/// `DependencyGraph`.
+pub struct LockfileAnalysis<'a> {
+    /// The locked packages, in lockfile order.
+    pub crates: Vec<LockedCrate<'a>>,
+
+    /// The dependencies between locked packages.
+    pub dependencies: Vec<LockedDependencyEdge<'a>>,
+
+    /// The crates locked at several versions.
+    pub duplicates: Vec<DuplicatePackage<'a>>,
+
+    /// The packages from crates.io which are not in the registry.
+    pub missing: Vec<&'a LockedPackage>,
+
+    /// The packages from crates.io locked to an older version than the registry's most recent stable one.
+    pub outdated: Vec<OutdatedPackage<'a>>,
+}
+
+#[derive(Serialize)]
+/// A locked package intended for serialization, including its distance from the local workspace.
+pub struct LockedCrate<'a> {
+    /// The distance from the local workspace, or `None` if unreachable from it.
+    pub distance: Option<usize>,
+
+    /// The name of the package.
+    pub name: &'a String,
+
+    /// The registry's metadata on the crate, if it is from crates.io and found in the registry.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub registry: Option<RegistryMetadata<'a>>,
+
+    /// Where the package comes from.
+    pub source: LockedSource,
+
+    /// The locked version of the package.
+    pub version: &'a String,
+}
+
+#[derive(Serialize)]
+/// The registry's metadata on a locked crate.
+pub struct RegistryMetadata<'a> {
+    /// A list of categories the crate belongs to.
+    pub categories: &'a Vec<String>,
+
+    /// The description of the crate.
+    pub description: &'a String,
+
+    /// The number of downloads of the crate.
+    pub downloads: usize,
+
+    /// The most recent stable version (if available) of the crate.
+    pub latest_version: &'a String,
+}
+
+#[derive(Serialize)]
+/// A `DependencyEdge` between locked packages, including the versions on either end.
+pub struct LockedDependencyEdge<'a> {
+    /// The dependency edge.
+    #[serde(flatten)]
+    pub edge: DependencyEdge<'a>,
+
+    /// The locked version of the source package.
+    pub from_version: &'a String,
+
+    /// The locked version of the destination package.
+    pub to_version: &'a String,
+}
+
+#[derive(Serialize)]
+/// A crate locked at several versions.
+pub struct DuplicatePackage<'a> {
+    /// The name of the crate.
+    pub name: &'a String,
+
+    /// The locked versions, in lockfile order.
+    pub versions: Vec<&'a String>,
+}
+
+#[derive(Serialize)]
+/// A package locked to an older version than the registry's most recent stable one.
+pub struct OutdatedPackage<'a> {
+    /// The name of the package.
+    pub name: &'a String,
+
+    /// The locked version of the package.
+    pub version: &'a String,
+
+    /// The most recent stable version of the crate in the registry.
+    pub latest_version: &'a String,
+}
+
+/// The sections of a `Cargo.lock` lockfile relevant to analysis.
+#[derive(Deserialize)]
+struct TomlLockfile {
+    /// The `[[package]]` entries.
+    #[serde(default)]
+    package: Vec<TomlLockedPackage>,
+}
+
+/// A `[[package]]` entry of a lockfile.
+#[derive(Deserialize)]
+struct TomlLockedPackage {
+    /// The name of the package.
+    name: String,
+
+    /// The locked version of the package.
+    version: String,
+
+    /// The source of the package, absent for packages of the local workspace.
+    source: Option<String>,
+
+    /// The dependencies of the package.
+    #[serde(default)]
+    dependencies: Vec<String>,
+}
@@ -0,0 +1,397 @@
+use super::schema::{default_naive_date_time, Crate, Dependency, DependencyKind};
+use serde::Deserialize;
+use std::{
+    collections::BTreeMap,
+    error::Error,
+    fmt::{self, Display, Formatter},
+};
+
+/// An error encountered while parsing a `Cargo.toml` manifest.
+#[derive(Debug, Eq, PartialEq)]
+pub enum ManifestError {
+    /// The manifest is not valid TOML or does not follow the manifest format, with a description of the problem.
+    Invalid(String),
+
+    /// The manifest has no `[package]` section, as with virtual workspace manifests.
+    MissingPackage,
+}
+
+impl Display for ManifestError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::Invalid(message) => write!(f, "Invalid manifest: {message}"),
+            Self::MissingPackage => write!(f, "Manifest has no [package] section."),
+        }
+    }
+}
+
+impl Error for ManifestError {}
+
+impl Crate {
+    /// Creates a new `Crate` from the contents of a `Cargo.toml` manifest.
+    ///
+    /// Dependencies are resolved against the registry by package name, so renamed dependencies (`package = "..."`)
+    /// point to the crate they rename, and features referencing them are rewritten accordingly. Optional dependencies
+    /// not referenced through `dep:` are exposed as features, as Cargo does.
+    ///
+    /// Dependencies which cannot come from the registry (`path` or `git` dependencies without a `version`, or those
+    /// from another `registry`) are left out, as Cargo does when publishing. Inherited `workspace = true` dependencies
+    /// are kept, as the registry only tracks the latest version of each crate.
+    ///
+    /// # Arguments
+    /// * `manifest` - the contents of the manifest.
+    ///
+    /// # Errors
+    /// * Returns `ManifestError::Invalid` if the manifest cannot be parsed.
+    /// * Returns `ManifestError::MissingPackage` if the manifest has no `[package]` section.
+    pub fn from_manifest(manifest: &str) -> Result<Self, ManifestError> {
+        let TomlManifest {
+            package,
+            dependencies,
+            dev_dependencies,
+            build_dependencies,
+            features,
+            target,
+        } = toml::from_str(manifest).map_err(|err| ManifestError::Invalid(err.to_string()))?;
+
+        let package = package.ok_or(ManifestError::MissingPackage)?;
+
+        let mut declarations: Vec<(DependencyKind, Option<String>, TomlDependencies)> = vec![
+            (DependencyKind::Normal, None, dependencies),
+            (DependencyKind::Dev, None, dev_dependencies),
+            (DependencyKind::Build, None, build_dependencies),
+        ];
+
+        for (platform, platform_dependencies) in target {
+            declarations.push((
+                DependencyKind::Normal,
+                Some(platform.clone()),
+                platform_dependencies.dependencies,
+            ));
+            declarations.push((
+                DependencyKind::Dev,
+                Some(platform.clone()),
+                platform_dependencies.dev_dependencies,
+            ));
+            declarations.push((
+                DependencyKind::Build,
+                Some(platform),
+                platform_dependencies.build_dependencies,
+            ));
+        }
+
+        // the crate each dependency name refers to
+        let mut renames: BTreeMap<String, String> = BTreeMap::new();
+        // the names of optional dependencies, which may be exposed as features
+        let mut optional_names: BTreeMap<String, String> = BTreeMap::new();
+        let mut crate_dependencies: Vec<Dependency> = vec![];
+
+        for (kind, platform, platform_dependencies) in declarations {
+            for (dependency_name, dependency) in platform_dependencies {
+                let Some(dependency) = dependency.into_registry_dependency(
+                    &package.name,
+                    &dependency_name,
+                    kind,
+                    platform.clone(),
+                ) else {
+                    continue;
+                };
+
+                renames
+                    .entry(dependency_name.clone())
+                    .or_insert_with(|| dependency.to.clone());
+
+                if dependency.optional {
+                    optional_names
+                        .entry(dependency_name)
+                        .or_insert_with(|| dependency.to.clone());
+                }
+
+                crate_dependencies.push(dependency);
+            }
+        }
+
+        // keep the order of declarations on the same crate, as several may apply
+        crate_dependencies.sort_by(|first, second| first.to.cmp(&second.to));
+
+        Ok(Self {
+            categories: vec![],
+            created_at: default_naive_date_time(),
+            dependencies: crate_dependencies,
+            description: package
+                .description
+                .as_ref()
+                .and_then(toml::Value::as_str)
+                .map(String::from)
+                .unwrap_or_default(),
+            downloads: 0,
+            features: get_manifest_features(features, &renames, optional_names),
+            keywords: package
+                .keywords
+                .as_ref()
+                .and_then(toml::Value::as_array)
+                .map(|keywords| {
+                    keywords
+                        .iter()
+                        .filter_map(toml::Value::as_str)
+                        .map(String::from)
+                        .collect()
+                })
+                .unwrap_or_default(),
+            license: package
+                .license
+                .as_ref()
+                .and_then(toml::Value::as_str)
+                .map(String::from),
+            max_depth: 0,
+            id: 0,
+            name: package.name,
+            pagerank: 0.0,
+            transitive_dependencies: 0,
+            transitive_dependents: 0,
+            version: package
+                .version
+                .as_ref()
+                .and_then(toml::Value::as_str)
+                .map(String::from)
+                .unwrap_or_default(),
+        })
+    }
+}
+
+/// Returns the features of a manifest, with references to renamed dependencies pointing to the crates they rename.
+///
+/// Optional dependencies which are not referenced through `dep:` by any feature are exposed as features.
+///
+/// # Arguments
+/// * `features` - the features declared in the manifest.
+/// * `renames` - a map of dependency names to the crates they refer to.
+/// * `optional_names` - a map of optional dependency names to the crates they refer to.
+pub fn get_manifest_features(
+    features: BTreeMap<String, Vec<String>>,
+    renames: &BTreeMap<String, String>,
+    optional_names: BTreeMap<String, String>,
+) -> BTreeMap<String, Vec<String>> {
+    let mut implicit_features: Vec<(String, Vec<String>)> = vec![];
+
+    for (dependency_name, crate_name) in optional_names {
+        let dep_feature = format!("dep:{dependency_name}");
+
+        if !features.contains_key(&dependency_name)
+            && !features
+                .values()
+                .any(|feature_dependencies| feature_dependencies.contains(&dep_feature))
+        {
+            implicit_features.push((dependency_name, vec![format!("dep:{crate_name}")]));
+        }
+    }
+
+    features
+        .into_iter()
+        .map(|(feature_name, feature_dependencies)| {
+            let feature_dependencies = feature_dependencies
+                .into_iter()
+                .map(|feature_dependency| rename_feature_dependency(feature_dependency, renames))
+                .collect();
+
+            (feature_name, feature_dependencies)
+        })
+        .chain(implicit_features)
+        .collect()
+}
+
+/// Rewrites an entry of a feature's list to refer to the crates renamed dependencies point to.
+///
+/// # Arguments
+/// * `feature_dependency` - the entry of the feature's list.
+/// * `renames` - a map of dependency names to the crates they refer to.
+fn rename_feature_dependency(
+    feature_dependency: String,
+    renames: &BTreeMap<String, String>,
+) -> String {
+    let rename = |dependency_name: &str| {
+        renames
+            .get(dependency_name)
+            .map_or_else(|| String::from(dependency_name), String::clone)
+    };
+
+    if let Some(dependency_name) = feature_dependency.strip_prefix("dep:") {
+        return format!("dep:{}", rename(dependency_name));
+    }
+
+    if let Some((dependency_name, dependency_feature)) = feature_dependency.split_once('/') {
+        // weak dependency features (`name?/feature`) keep their marker
+        let (dependency_name, weak_marker) = dependency_name
+            .strip_suffix('?')
+            .map_or((dependency_name, ""), |dependency_name| {
+                (dependency_name, "?")
+            });
+
+        return format!(
+            "{}{weak_marker}/{dependency_feature}",
+            rename(dependency_name)
+        );
+    }
+
+    feature_dependency
+}
+
+/// The dependencies declared in a manifest table, keyed by dependency name.
+type TomlDependencies = BTreeMap<String, TomlDependency>;
+
+/// The sections of a `Cargo.toml` manifest relevant to dependency resolution.
+#[derive(Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct TomlManifest {
+    /// The `[package]` section.
+    package: Option<TomlPackage>,
+
+    /// The `[dependencies]` section.
+    #[serde(default)]
+    dependencies: TomlDependencies,
+
+    /// The `[dev-dependencies]` section.
+    #[serde(default, alias = "dev_dependencies")]
+    dev_dependencies: TomlDependencies,
+
+    /// The `[build-dependencies]` section.
+    #[serde(default, alias = "build_dependencies")]
+    build_dependencies: TomlDependencies,
+
+    /// The `[features]` section.
+    #[serde(default)]
+    features: BTreeMap<String, Vec<String>>,
+
+    /// The `[target.<triple or cfg>]` sections, keyed by platform.
+    #[serde(default)]
+    target: BTreeMap<String, TomlPlatform>,
+}
+
+/// The `[package]` section of a manifest.
+///
+/// Fields besides the name may be inherited from a workspace, in which case they are left empty.
+#[derive(Deserialize)]
+struct TomlPackage {
+    /// The name of the package.
+    name: String,
+
+    /// The version of the package.
+    version: Option<toml::Value>,
+
+    /// The description of the package.
+    description: Option<toml::Value>,
+
+    /// The keywords of the package.
+    keywords: Option<toml::Value>,
+
+    /// The license expression of the package.
+    license: Option<toml::Value>,
+}
+
+/// The dependencies of a `[target.<triple or cfg>]` section.
+#[derive(Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct TomlPlatform {
+    /// The `[target.<triple or cfg>.dependencies]` section.
+    #[serde(default)]
+    dependencies: TomlDependencies,
+
+    /// The `[target.<triple or cfg>.dev-dependencies]` section.
+    #[serde(default, alias = "dev_dependencies")]
+    dev_dependencies: TomlDependencies,
+
+    /// The `[target.<triple or cfg>.build-dependencies]` section.
+    #[serde(default, alias = "build_dependencies")]
+    build_dependencies: TomlDependencies,
+}
+
+/// A dependency declared in a manifest.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum TomlDependency {
+    /// A dependency declared by version requirement alone (`name = "1.0"`).
+    Simple(String),
+
+    /// A dependency declared with a table.
+    Detailed(DetailedTomlDependency),
+}
+
+/// A dependency declared with a table in a manifest.
+#[derive(Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct DetailedTomlDependency {
+    /// The version requirement of the dependency.
+    version: Option<String>,
+
+    /// The local path of the dependency.
+    path: Option<String>,
+
+    /// The git repository of the dependency.
+    git: Option<String>,
+
+    /// The registry of the dependency, if not crates.io.
+    registry: Option<String>,
+
+    /// The name of the crate, if the dependency is renamed.
+    package: Option<String>,
+
+    /// If the dependency relies on default features.
+    #[serde(alias = "default_features")]
+    default_features: Option<bool>,
+
+    /// A list of features the dependency uses.
+    #[serde(default)]
+    features: Vec<String>,
+
+    /// If the dependency is optional.
+    #[serde(default)]
+    optional: bool,
+
+    /// If the dependency is inherited from the workspace.
+    #[serde(default)]
+    workspace: bool,
+}
+
+impl TomlDependency {
+    /// Converts the declaration into a `Dependency` on a crate in the registry.
+    ///
+    /// Returns `None` if the dependency cannot come from the registry.
+    ///
+    /// # Arguments
+    /// * `from` - the name of the package declaring the dependency.
+    /// * `dependency_name` - the name the dependency is declared under.
+    /// * `kind` - the kind of the dependency.
+    /// * `target` - the platform the dependency is specific to, if any.
+    fn into_registry_dependency(
+        self,
+        from: &str,
+        dependency_name: &str,
+        kind: DependencyKind,
+        target: Option<String>,
+    ) -> Option<Dependency> {
+        let dependency = match self {
+            Self::Simple(version) => DetailedTomlDependency {
+                version: Some(version),
+                ..DetailedTomlDependency::default()
+            },
+            Self::Detailed(dependency) => dependency,
+        };
+
+        let from_registry = dependency.registry.is_none()
+            && (dependency.version.is_some()
+                || dependency.workspace
+                || (dependency.path.is_none() && dependency.git.is_none()));
+
+        from_registry.then(|| Dependency {
+            default_features: dependency.default_features.unwrap_or(true),
+            features: dependency.features,
+            from: String::from(from),
+            optional: dependency.optional,
+            target,
+            to: dependency
+                .package
+                .unwrap_or_else(|| String::from(dependency_name)),
+            kind,
+        })
+    }
+}
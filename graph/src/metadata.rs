@@ -0,0 +1,678 @@
+use super::{
+    lockfile::CRATES_IO_SOURCES,
+    manifest::get_manifest_features,
+    schema::{default_naive_date_time, Crate, Dependency, DependencyKind},
+    DependencyGraph, DependencyGraphOptions, Graph,
+};
+use ahash::AHashMap;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, BTreeSet, VecDeque},
+    error::Error,
+    fmt::{self, Display, Formatter},
+};
+
+/// An error encountered while parsing the output of `cargo metadata`.
+#[derive(Debug, Eq, PartialEq)]
+pub enum MetadataError {
+    /// The document is not valid JSON or does not follow the metadata format, with a description of the problem.
+    Invalid(String),
+
+    /// The document has no `resolve` section, as with `cargo metadata --no-deps`.
+    MissingResolve,
+
+    /// The resolve section has no root package, as with virtual workspaces.
+    MissingRoot,
+}
+
+impl Display for MetadataError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::Invalid(message) => write!(f, "Invalid metadata: {message}"),
+            Self::MissingResolve => write!(
+                f,
+                "Metadata has no resolve section, as produced by `cargo metadata --no-deps`."
+            ),
+            Self::MissingRoot => write!(
+                f,
+                "Metadata has no root package, as produced for virtual workspaces."
+            ),
+        }
+    }
+}
+
+impl Error for MetadataError {}
+
+/// The dependency resolution of a single package, as reported by `cargo metadata --format-version 1`.
+#[derive(Debug)]
+pub struct Metadata {
+    /// The root package, as a `Crate` built from its dependency declarations.
+    pub root: Crate,
+
+    /// The features Cargo enabled for the root package.
+    pub root_features: Vec<String>,
+
+    /// The resolved packages, with the root first.
+    packages: Vec<ResolvedPackage>,
+}
+
+/// A package resolved by Cargo.
+#[derive(Debug)]
+struct ResolvedPackage {
+    /// The name of the package.
+    name: String,
+
+    /// If the package comes from crates.io.
+    crates_io: bool,
+
+    /// The features Cargo enabled for the package.
+    features: Vec<String>,
+
+    /// The indices of the packages this package depends on, with the kinds of each dependency.
+    dependencies: Vec<(usize, Vec<DependencyKind>)>,
+}
+
+impl Metadata {
+    /// Parses the output of `cargo metadata --format-version 1`.
+    ///
+    /// Dependencies on other platforms are kept, so the document should be produced with `--filter-platform` set to
+    /// the target being compared against.
+    ///
+    /// # Arguments
+    /// * `metadata` - the JSON document.
+    ///
+    /// # Errors
+    /// * Returns `MetadataError::Invalid` if the document cannot be parsed or references unknown packages.
+    /// * Returns `MetadataError::MissingResolve` if the document has no `resolve` section.
+    /// * Returns `MetadataError::MissingRoot` if the resolve section has no root package.
+    pub fn parse(metadata: &str) -> Result<Self, MetadataError> {
+        let CargoMetadata { packages, resolve } = serde_json::from_str(metadata)
+            .map_err(|err| MetadataError::Invalid(err.to_string()))?;
+
+        let MetadataResolve { nodes, root } = resolve.ok_or(MetadataError::MissingResolve)?;
+        let root = root.ok_or(MetadataError::MissingRoot)?;
+
+        let unknown_id = |id: &str| MetadataError::Invalid(format!("unknown package id {id}"));
+
+        let mut packages: AHashMap<String, MetadataPackage> = packages
+            .into_iter()
+            .map(|package| (package.id.clone(), package))
+            .collect();
+
+        // resolved package indices by id, with the root first
+        let mut node_indices: AHashMap<&str, usize> = AHashMap::new();
+        node_indices.insert(root.as_str(), 0);
+        for node in &nodes {
+            if node.id != root {
+                let index = node_indices.len();
+                node_indices.entry(node.id.as_str()).or_insert(index);
+            }
+        }
+
+        let mut resolved_packages: Vec<Option<ResolvedPackage>> =
+            (0..node_indices.len()).map(|_| None).collect();
+        let mut root_package: Option<MetadataPackage> = None;
+
+        for node in &nodes {
+            let package = packages.get(&node.id).ok_or_else(|| unknown_id(&node.id))?;
+
+            let mut dependencies: Vec<(usize, Vec<DependencyKind>)> = vec![];
+            for dependency in &node.deps {
+                let &index = node_indices
+                    .get(dependency.pkg.as_str())
+                    .ok_or_else(|| unknown_id(&dependency.pkg))?;
+
+                dependencies.push((
+                    index,
+                    dependency
+                        .dep_kinds
+                        .iter()
+                        .map(|dep_kind| dep_kind.kind.unwrap_or(DependencyKind::Normal))
+                        .collect(),
+                ));
+            }
+
+            resolved_packages[node_indices[node.id.as_str()]] = Some(ResolvedPackage {
+                name: package.name.clone(),
+                crates_io: package
+                    .source
+                    .as_deref()
+                    .is_some_and(|source| CRATES_IO_SOURCES.contains(&source)),
+                features: node.features.clone(),
+                dependencies,
+            });
+
+            if node.id == root {
+                root_package = packages.remove(&node.id);
+            }
+        }
+
+        let root_package = root_package.ok_or_else(|| unknown_id(&root))?;
+        let packages: Vec<ResolvedPackage> = resolved_packages.into_iter().flatten().collect();
+
+        Ok(Self {
+            root: root_package.into_crate(),
+            root_features: packages[0].features.clone(),
+            packages,
+        })
+    }
+
+    /// Returns the crates.io packages reachable from the root through dependencies the resolver follows, and the
+    /// edges between them.
+    ///
+    /// # Arguments
+    /// * `options` - the `DependencyGraphOptions` whose resolver decides which dependencies are followed.
+    fn resolution(
+        &self,
+        options: &DependencyGraphOptions,
+    ) -> (Vec<usize>, BTreeSet<MetadataEdge<'_>>) {
+        let mut seen = vec![false; self.packages.len()];
+        let mut reachable: Vec<usize> = vec![0];
+        let mut edges: BTreeSet<MetadataEdge> = BTreeSet::new();
+        let mut queue: VecDeque<usize> = VecDeque::from([0]);
+        seen[0] = true;
+
+        while let Some(index) = queue.pop_front() {
+            let package = &self.packages[index];
+
+            for (dependency_index, kinds) in &package.dependencies {
+                let dependency = &self.packages[*dependency_index];
+                if !dependency.crates_io {
+                    continue;
+                }
+
+                let mut followed = false;
+                for &kind in kinds {
+                    if options.resolver.follows(kind) {
+                        followed = true;
+                        edges.insert(MetadataEdge {
+                            from: &package.name,
+                            to: &dependency.name,
+                            kind,
+                        });
+                    }
+                }
+
+                if followed && !seen[*dependency_index] {
+                    seen[*dependency_index] = true;
+                    reachable.push(*dependency_index);
+                    queue.push_back(*dependency_index);
+                }
+            }
+        }
+
+        (reachable, edges)
+    }
+}
+
+impl Graph {
+    /// Compares the resolution reported by `cargo metadata` with the dependency graph of the same root package and
+    /// features.
+    ///
+    /// If the root package matches a crate in the registry by name and version, the registry crate is resolved;
+    /// otherwise, a crate is built from the root package's declarations. Only crates.io packages reachable through
+    /// dependencies the resolver follows are compared, by name, so several versions of a crate are merged. Enabled
+    /// features are compared once expanded through each crate's feature table.
+    ///
+    /// If any crates.io dependency of the root package is not found in the registry, returns `None`.
+    ///
+    /// # Arguments
+    /// * `metadata` - the `Metadata` to compare against.
+    /// * `target` - the target triple to resolve platform-specific dependencies for.
+    /// * `cfg_name` - an additional cfg name to enable when resolving platform-specific dependencies.
+    /// * `options` - the `DependencyGraphOptions` selecting the resolver and host; root features are taken from the
+    ///   metadata.
+    #[must_use]
+    pub fn compare_metadata<'a>(
+        &'a self,
+        metadata: &'a Metadata,
+        target: &Option<String>,
+        cfg_name: &Option<String>,
+        options: &DependencyGraphOptions,
+    ) -> Option<MetadataComparison<'a>> {
+        let options = DependencyGraphOptions {
+            default_features: metadata
+                .root_features
+                .iter()
+                .any(|feature| feature == "default"),
+            all_features: false,
+            potential_dependencies: None,
+            ..options.clone()
+        };
+        let features: Vec<String> = metadata
+            .root_features
+            .iter()
+            .filter(|feature| *feature != "default")
+            .cloned()
+            .collect();
+
+        let registry_root = self
+            .crates()
+            .get(&metadata.root.name)
+            .filter(|crate_val| crate_val.version == metadata.root.version);
+
+        let (root, dependency_graph) = match registry_root {
+            Some(root) => (
+                root,
+                self.get_dependency_graph(&root.name, features, target, cfg_name, &options)?,
+            ),
+            None => (
+                &metadata.root,
+                self.get_manifest_dependency_graph(
+                    &metadata.root,
+                    features,
+                    target,
+                    cfg_name,
+                    &options,
+                )?,
+            ),
+        };
+
+        let (cargo_packages, cargo_edges) = metadata.resolution(&options);
+        let vault_edges: BTreeSet<MetadataEdge> = dependency_graph
+            .dependencies
+            .iter()
+            .map(|dependency| MetadataEdge {
+                from: dependency.from,
+                to: dependency.to,
+                kind: dependency.kind,
+            })
+            .collect();
+
+        // crates and features enabled by Cargo, merged across versions
+        let mut cargo_features: BTreeMap<&String, BTreeSet<String>> = BTreeMap::new();
+        for &index in &cargo_packages {
+            let package = &metadata.packages[index];
+            let crate_val = if index == 0 {
+                Some(root)
+            } else {
+                self.crates().get(&package.name)
+            };
+
+            cargo_features
+                .entry(&package.name)
+                .or_default()
+                .extend(crate_val.map_or_else(BTreeSet::new, |crate_val| {
+                    expand_features(self, crate_val, package.features.iter().map(String::as_str))
+                }));
+        }
+
+        let vault_features = get_vault_features(self, root, &dependency_graph, &options);
+
+        let mut feature_mismatches: Vec<FeatureMismatch> = vec![];
+        for (name, features) in &cargo_features {
+            if let Some(crate_vault_features) = vault_features.get(name) {
+                if features != crate_vault_features {
+                    feature_mismatches.push(FeatureMismatch {
+                        name,
+                        cargo: features.iter().cloned().collect(),
+                        vault: crate_vault_features.iter().cloned().collect(),
+                    });
+                }
+            }
+        }
+
+        let comparison = MetadataComparison {
+            root: &root.name,
+            agrees: false,
+            cargo_only_crates: cargo_features
+                .keys()
+                .filter(|name| !vault_features.contains_key(*name))
+                .copied()
+                .collect(),
+            vault_only_crates: vault_features
+                .keys()
+                .filter(|name| !cargo_features.contains_key(*name))
+                .copied()
+                .collect(),
+            cargo_only_dependencies: cargo_edges.difference(&vault_edges).copied().collect(),
+            vault_only_dependencies: vault_edges.difference(&cargo_edges).copied().collect(),
+            feature_mismatches,
+        };
+
+        Some(MetadataComparison {
+            agrees: comparison.cargo_only_crates.is_empty()
+                && comparison.vault_only_crates.is_empty()
+                && comparison.cargo_only_dependencies.is_empty()
+                && comparison.vault_only_dependencies.is_empty()
+                && comparison.feature_mismatches.is_empty(),
+            ..comparison
+        })
+    }
+}
+
+/// Returns the features enabled for each crate of a dependency graph, expanded through each crate's feature table.
+///
+/// The default feature of a crate is considered enabled if any active dependency on it relies on default features.
+///
+/// # Arguments
+/// * `graph` - the `Graph` of the registry.
+/// * `root` - the root `Crate` of the dependency graph.
+/// * `dependency_graph` - the `DependencyGraph`.
+/// * `options` - the `DependencyGraphOptions` the dependency graph was resolved with.
+fn get_vault_features<'a>(
+    graph: &Graph,
+    root: &Crate,
+    dependency_graph: &DependencyGraph<'a>,
+    options: &DependencyGraphOptions,
+) -> BTreeMap<&'a String, BTreeSet<String>> {
+    let mut default_enabled: BTreeSet<&String> = BTreeSet::new();
+    for dependency in &dependency_graph.dependencies {
+        if dependency.active && dependency.default_features {
+            default_enabled.insert(dependency.to);
+        }
+    }
+
+    dependency_graph
+        .crates
+        .iter()
+        .map(|crate_distance| {
+            let is_root = crate_distance.distance == 0;
+            let crate_val = if is_root {
+                root
+            } else {
+                &graph.crates()[crate_distance.name]
+            };
+
+            let default_feature = (if is_root {
+                options.default_features
+            } else {
+                default_enabled.contains(crate_distance.name)
+            })
+            .then_some("default");
+
+            (
+                crate_distance.name,
+                expand_features(
+                    graph,
+                    crate_val,
+                    crate_distance
+                        .enabled_features
+                        .iter()
+                        .map(String::as_str)
+                        .chain(default_feature),
+                ),
+            )
+        })
+        .collect()
+}
+
+/// Returns the features transitively enabled by a set of features of a crate.
+///
+/// Features which enable an optional dependency's feature also enable the dependency's implicit feature, unless the
+/// crate refers to the dependency through `dep:`. Features the crate does not define are kept, except `default`.
+///
+/// # Arguments
+/// * `graph` - the `Graph` of the registry.
+/// * `crate_val` - the `Crate` whose features are enabled.
+/// * `features` - the enabled features.
+fn expand_features<'b>(
+    graph: &Graph,
+    crate_val: &Crate,
+    features: impl IntoIterator<Item = &'b str>,
+) -> BTreeSet<String> {
+    let mut expanded: BTreeSet<String> = BTreeSet::new();
+    let mut stack: Vec<&str> = features.into_iter().collect();
+    let declarations = graph.declarations(crate_val);
+
+    let has_implicit_feature = |dependency_name: &str| {
+        let dep_feature = format!("dep:{dependency_name}");
+
+        declarations
+            .iter()
+            .any(|dependency| dependency.optional && dependency.to == dependency_name)
+            && !crate_val
+                .features
+                .values()
+                .any(|feature_dependencies| feature_dependencies.contains(&dep_feature))
+    };
+
+    while let Some(feature) = stack.pop() {
+        if feature == "default" && !crate_val.features.contains_key(feature) {
+            continue;
+        }
+
+        if !expanded.insert(String::from(feature)) {
+            continue;
+        }
+
+        for feature_dependency in crate_val.features.get(feature).into_iter().flatten() {
+            if feature_dependency.starts_with("dep:") {
+                continue;
+            }
+
+            match feature_dependency.split_once('/') {
+                Some((dependency_name, _)) => {
+                    if !dependency_name.ends_with('?') && has_implicit_feature(dependency_name) {
+                        stack.push(dependency_name);
+                    }
+                }
+                None => stack.push(feature_dependency),
+            }
+        }
+    }
+
+    expanded
+}
+
+#[derive(Serialize)]
+/// The differences between the resolution reported by `cargo metadata` and a `DependencyGraph`.
+pub struct MetadataComparison<'a> {
+    /// The name of the root crate.
+    pub root: &'a String,
+
+    /// If no differences were found.
+    pub agrees: bool,
+
+    /// The crates resolved by Cargo but missing from the dependency graph.
+    pub cargo_only_crates: Vec<&'a String>,
+
+    /// The crates in the dependency graph but not resolved by Cargo.
+    pub vault_only_crates: Vec<&'a String>,
+
+    /// The dependencies resolved by Cargo but missing from the dependency graph.
+    pub cargo_only_dependencies: Vec<MetadataEdge<'a>>,
+
+    /// The dependencies in the dependency graph but not resolved by Cargo.
+    pub vault_only_dependencies: Vec<MetadataEdge<'a>>,
+
+    /// The crates resolved by both with different features enabled.
+    pub feature_mismatches: Vec<FeatureMismatch<'a>>,
+}
+
+/// A dependency between two crates of a specific kind, irrespective of how many times it is declared.
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd, Serialize)]
+pub struct MetadataEdge<'a> {
+    /// The source crate of the dependency.
+    pub from: &'a String,
+
+    /// The destination crate of the dependency.
+    pub to: &'a String,
+
+    /// The kind of the dependency.
+    pub kind: DependencyKind,
+}
+
+#[derive(Serialize)]
+/// A crate with different features enabled by Cargo and in a dependency graph.
+pub struct FeatureMismatch<'a> {
+    /// The name of the crate.
+    pub name: &'a String,
+
+    /// The features enabled by Cargo.
+    pub cargo: Vec<String>,
+
+    /// The features enabled in the dependency graph.
+    pub vault: Vec<String>,
+}
+
+/// The sections of `cargo metadata` output relevant to comparison.
+#[derive(Deserialize)]
+struct CargoMetadata {
+    /// The packages of the workspace and their dependencies.
+    packages: Vec<MetadataPackage>,
+
+    /// The resolved dependency graph, absent with `--no-deps`.
+    resolve: Option<MetadataResolve>,
+}
+
+/// A package listed by `cargo metadata`.
+#[derive(Deserialize)]
+struct MetadataPackage {
+    /// The opaque id of the package.
+    id: String,
+
+    /// The name of the package.
+    name: String,
+
+    /// The version of the package.
+    version: String,
+
+    /// The source of the package, absent for path packages.
+    source: Option<String>,
+
+    /// The license expression of the package, if declared.
+    license: Option<String>,
+
+    /// The dependencies declared by the package.
+    #[serde(default)]
+    dependencies: Vec<MetadataDependency>,
+
+    /// The features declared by the package, including implicit features of optional dependencies.
+    #[serde(default)]
+    features: BTreeMap<String, Vec<String>>,
+}
+
+/// A dependency declared by a package listed by `cargo metadata`.
+#[derive(Deserialize)]
+struct MetadataDependency {
+    /// The name of the crate depended on.
+    name: String,
+
+    /// The source of the dependency, absent for path dependencies.
+    source: Option<String>,
+
+    /// The kind of the dependency, absent for normal dependencies.
+    kind: Option<DependencyKind>,
+
+    /// The name the dependency is declared under, if renamed.
+    rename: Option<String>,
+
+    /// If the dependency is optional.
+    #[serde(default)]
+    optional: bool,
+
+    /// If the dependency relies on default features.
+    #[serde(default = "default_true")]
+    uses_default_features: bool,
+
+    /// A list of features the dependency uses.
+    #[serde(default)]
+    features: Vec<String>,
+
+    /// The platform the dependency is specific to, if any.
+    target: Option<String>,
+}
+
+/// The `resolve` section of `cargo metadata` output.
+#[derive(Deserialize)]
+struct MetadataResolve {
+    /// The resolved packages.
+    nodes: Vec<MetadataNode>,
+
+    /// The id of the root package, absent for virtual workspaces.
+    root: Option<String>,
+}
+
+/// A package resolved by Cargo.
+#[derive(Deserialize)]
+struct MetadataNode {
+    /// The opaque id of the package.
+    id: String,
+
+    /// The resolved dependencies of the package.
+    #[serde(default)]
+    deps: Vec<MetadataNodeDependency>,
+
+    /// The features enabled for the package.
+    #[serde(default)]
+    features: Vec<String>,
+}
+
+/// A resolved dependency of a package.
+#[derive(Deserialize)]
+struct MetadataNodeDependency {
+    /// The opaque id of the package depended on.
+    pkg: String,
+
+    /// The kinds of the dependency.
+    #[serde(default)]
+    dep_kinds: Vec<MetadataDependencyKind>,
+}
+
+/// A kind of a resolved dependency.
+#[derive(Deserialize)]
+struct MetadataDependencyKind {
+    /// The kind of the dependency, absent for normal dependencies.
+    kind: Option<DependencyKind>,
+}
+
+/// Returns `true`, as serde defaults must be functions.
+const fn default_true() -> bool {
+    true
+}
+
+impl MetadataPackage {
+    /// Converts the package into a `Crate` depending on crates.io packages.
+    ///
+    /// Renamed dependencies point to the crates they rename, and features referencing them are rewritten accordingly.
+    fn into_crate(self) -> Crate {
+        let mut renames: BTreeMap<String, String> = BTreeMap::new();
+        let mut dependencies: Vec<Dependency> = vec![];
+
+        for dependency in self.dependencies {
+            if !dependency
+                .source
+                .as_deref()
+                .is_some_and(|source| CRATES_IO_SOURCES.contains(&source))
+            {
+                continue;
+            }
+
+            if let Some(rename) = dependency.rename {
+                renames.insert(rename, dependency.name.clone());
+            }
+
+            dependencies.push(Dependency {
+                default_features: dependency.uses_default_features,
+                features: dependency.features,
+                from: self.name.clone(),
+                optional: dependency.optional,
+                target: dependency.target,
+                to: dependency.name,
+                kind: dependency.kind.unwrap_or(DependencyKind::Normal),
+            });
+        }
+
+        // keep the order of declarations on the same crate, as several may apply
+        dependencies.sort_by(|first, second| first.to.cmp(&second.to));
+
+        Crate {
+            categories: vec![],
+            created_at: default_naive_date_time(),
+            dependencies,
+            description: String::new(),
+            downloads: 0,
+            features: get_manifest_features(self.features, &renames, BTreeMap::new()),
+            keywords: vec![],
+            license: self.license,
+            max_depth: 0,
+            id: 0,
+            name: self.name,
+            pagerank: 0.0,
+            transitive_dependencies: 0,
+            transitive_dependents: 0,
+            version: self.version,
+        }
+    }
+}
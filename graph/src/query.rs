@@ -0,0 +1,433 @@
+use super::{DependencyGraphOptions, DependencyKind, GraphFormat, Resolver};
+use cargo_platform::Cfg;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{
+    error::Error,
+    fmt::{self, Display, Formatter},
+    str::FromStr,
+};
+
+/// A root crate of a `DependencyGraphQuery` and the features selected for it.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct DependencyGraphRoot {
+    /// The name of the crate.
+    pub name: String,
+
+    /// The features enabled for the crate.
+    pub features: Vec<String>,
+
+    /// If the default features of the crate are enabled.
+    pub default_features: bool,
+
+    /// If every feature of the crate is enabled.
+    pub all_features: bool,
+}
+
+impl DependencyGraphRoot {
+    /// Creates a new `DependencyGraphRoot` with only its default features enabled.
+    ///
+    /// # Arguments
+    /// * `name` - the name of the crate.
+    #[must_use]
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            features: vec![],
+            default_features: true,
+            all_features: false,
+        }
+    }
+}
+
+/// A query describing a `DependencyGraph` with one or more root crates.
+///
+/// Features are unified across every root crate, as Cargo does for the members of a workspace.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct DependencyGraphQuery {
+    /// The root crates, each at a distance of 0.
+    pub roots: Vec<DependencyGraphRoot>,
+
+    /// The target triple to resolve platform-specific dependencies for, defaulting to the `Graph`'s default target.
+    pub target: Option<String>,
+
+    /// The additional cfg names to enable, defaulting to the `Graph`'s default cfg name if empty.
+    pub cfgs: Vec<String>,
+
+    /// The host triple build dependencies and procedural macros are built for under `Resolver::V2`.
+    pub host: Option<String>,
+
+    /// If procedural macros are inferred and built for the host under `Resolver::V2`.
+    pub infer_proc_macros: bool,
+
+    /// The feature resolver to use.
+    pub resolver: Resolver,
+
+    /// The greatest distance from a root crate at which crates are included, if limited.
+    pub max_depth: Option<usize>,
+
+    /// The crates left out of the dependency graph along with any dependencies only they lead to.
+    pub exclude: Vec<String>,
+
+    /// The kinds of dependencies to follow, if restricted.
+    ///
+    /// Only kinds followed by the resolver are followed, except for the dev dependencies of root crates.
+    pub kinds: Option<Vec<DependencyKind>>,
+
+    /// The maximum number of inactive optional dependencies to include, if they should be included.
+    pub potential_dependencies: Option<usize>,
+
+    /// The `GraphFormat` to export the dependency graph in.
+    pub format: GraphFormat,
+}
+
+impl DependencyGraphQuery {
+    /// Creates a new `DependencyGraphQuery` for a single root crate.
+    ///
+    /// # Arguments
+    /// * `crate_id` - the name of the root crate.
+    /// * `features` - the list of features to enable.
+    /// * `target` - the target triple to resolve platform-specific dependencies for.
+    /// * `cfg_name` - an additional cfg name to enable when resolving platform-specific dependencies.
+    /// * `options` - the `DependencyGraphOptions` controlling how the root crate's features are selected.
+    #[must_use]
+    pub fn new(
+        crate_id: &str,
+        features: Vec<String>,
+        target: &Option<String>,
+        cfg_name: &Option<String>,
+        options: &DependencyGraphOptions,
+    ) -> Self {
+        Self {
+            roots: vec![DependencyGraphRoot {
+                name: String::from(crate_id),
+                features,
+                default_features: options.default_features,
+                all_features: options.all_features,
+            }],
+            target: target.clone(),
+            cfgs: cfg_name.iter().cloned().collect(),
+            host: options.host.clone(),
+            infer_proc_macros: options.infer_proc_macros,
+            resolver: options.resolver,
+            max_depth: None,
+            exclude: vec![],
+            kinds: None,
+            potential_dependencies: options.potential_dependencies,
+            format: GraphFormat::Json,
+        }
+    }
+
+    /// Returns the `DependencyGraphOptions` of the first root crate, as passed to `new`.
+    #[must_use]
+    pub fn options(&self) -> DependencyGraphOptions {
+        DependencyGraphOptions {
+            default_features: self.roots[0].default_features,
+            all_features: self.roots[0].all_features,
+            resolver: self.resolver,
+            host: self.host.clone(),
+            infer_proc_macros: self.infer_proc_macros,
+            potential_dependencies: self.potential_dependencies,
+        }
+    }
+
+    /// Returns the first additional cfg name, as passed to `new`.
+    #[must_use]
+    pub fn cfg_name(&self) -> Option<String> {
+        self.cfgs.first().cloned()
+    }
+
+    /// Parses a `DependencyGraphQuery` from a JSON object.
+    ///
+    /// Only `roots` is required, where each root is an object with a `name` and optionally `features`,
+    /// `default_features` and `all_features`. The remaining fields are `target`, `cfgs`, `host`, `infer_proc_macros`,
+    /// `resolver` (`1` or `2`), `max_depth`, `exclude`, `kinds` (`normal`, `build` or `dev`), `potential_dependencies`
+    /// and `format` (as accepted by the `format` query option), any of which may be `null`.
+    ///
+    /// # Arguments
+    /// * `query` - the JSON object.
+    ///
+    /// # Errors
+    /// * Returns `DependencyGraphQueryError::Invalid` if the query is not a JSON object.
+    /// * Returns `DependencyGraphQueryError::Fields` with the first field found with an improper value.
+    pub fn parse(query: &str) -> Result<Self, DependencyGraphQueryError> {
+        let query: Value = serde_json::from_str(query)
+            .map_err(|err| DependencyGraphQueryError::Invalid(err.to_string()))?;
+        if !query.is_object() {
+            return Err(DependencyGraphQueryError::Invalid(String::from(
+                "expected a JSON object",
+            )));
+        }
+
+        let fields: QueryFields = serde_path_to_error::deserialize(query)
+            .map_err(|err| DependencyGraphQueryError::Fields(vec![QueryFieldError::from(err)]))?;
+
+        if fields.roots.is_empty() {
+            return Err(DependencyGraphQueryError::Fields(vec![QueryFieldError {
+                field: String::from("roots"),
+                message: String::from("must list at least one root crate"),
+            }]));
+        }
+
+        let duplicate_root_errors: Vec<QueryFieldError> = fields
+            .roots
+            .iter()
+            .enumerate()
+            .filter(|(index, root)| {
+                fields.roots[..*index]
+                    .iter()
+                    .any(|other_root| other_root.name == root.name)
+            })
+            .map(|(index, _)| QueryFieldError {
+                field: format!("roots[{index}].name"),
+                message: String::from("duplicate root crate"),
+            })
+            .collect();
+        if !duplicate_root_errors.is_empty() {
+            return Err(DependencyGraphQueryError::Fields(duplicate_root_errors));
+        }
+
+        Ok(Self {
+            roots: fields
+                .roots
+                .into_iter()
+                .map(|root| DependencyGraphRoot {
+                    name: root.name,
+                    features: root.features.unwrap_or_default(),
+                    default_features: root.default_features.unwrap_or(true),
+                    all_features: root.all_features.unwrap_or_default(),
+                })
+                .collect(),
+            target: fields.target,
+            cfgs: fields
+                .cfgs
+                .map(|cfgs| cfgs.into_iter().map(|cfg| cfg.0).collect())
+                .unwrap_or_default(),
+            host: fields.host,
+            infer_proc_macros: fields.infer_proc_macros.unwrap_or_default(),
+            resolver: fields
+                .resolver
+                .map_or_else(Resolver::default, |resolver| resolver.0),
+            max_depth: fields.max_depth,
+            exclude: fields.exclude.unwrap_or_default(),
+            kinds: fields.kinds,
+            potential_dependencies: fields.potential_dependencies,
+            format: fields.format.map_or(GraphFormat::Json, |format| format.0),
+        })
+    }
+
+    /// Returns the query with the parts which do not affect the dependency graph normalized, for use as a cache key.
+    ///
+    /// The features of each root are sorted and deduplicated (or cleared if every feature is enabled), as are the cfg
+    /// names and excluded crates, and the format is reset to `GraphFormat::Json`.
+    ///
+    /// # Arguments
+    /// * `default_target` - the target triple used when the query does not specify one.
+    #[must_use]
+    pub fn normalized(&self, default_target: &str) -> Self {
+        let mut query = self.clone();
+
+        for root in &mut query.roots {
+            // every feature is enabled anyways, so the requested ones do not matter
+            if root.all_features {
+                root.features.clear();
+            } else {
+                root.features.sort_unstable();
+                root.features.dedup();
+            }
+        }
+
+        if query.target.is_none() {
+            query.target = Some(String::from(default_target));
+        }
+
+        query.cfgs.sort_unstable();
+        query.cfgs.dedup();
+        query.exclude.sort_unstable();
+        query.exclude.dedup();
+
+        if let Some(kinds) = &mut query.kinds {
+            kinds.sort_unstable();
+            kinds.dedup();
+        }
+
+        query.format = GraphFormat::Json;
+
+        query
+    }
+}
+
+/// An error encountered while parsing a `DependencyGraphQuery`.
+#[derive(Debug, Eq, PartialEq)]
+pub enum DependencyGraphQueryError {
+    /// The query is not a JSON object, with a description of the problem.
+    Invalid(String),
+
+    /// The query has fields with improper values, as found by deserializing it.
+    Fields(Vec<QueryFieldError>),
+}
+
+impl Display for DependencyGraphQueryError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::Invalid(message) => write!(f, "Invalid graph query: {message}"),
+            Self::Fields(errors) => write!(
+                f,
+                "Invalid graph query: {}.",
+                errors
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<String>>()
+                    .join("; ")
+            ),
+        }
+    }
+}
+
+impl Error for DependencyGraphQueryError {}
+
+/// A field of a `DependencyGraphQuery` with an improper value.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct QueryFieldError {
+    /// The path of the field (e.g. `roots[0].features`).
+    pub field: String,
+
+    /// A description of the problem.
+    pub message: String,
+}
+
+impl Display for QueryFieldError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.field, self.message)
+    }
+}
+
+impl From<serde_path_to_error::Error<serde_json::Error>> for QueryFieldError {
+    fn from(err: serde_path_to_error::Error<serde_json::Error>) -> Self {
+        let path = err.path().to_string();
+        let message = err.into_inner().to_string();
+
+        // missing fields are reported at the object missing them, so point at the field itself
+        if let Some(field) = message
+            .strip_prefix("missing field `")
+            .and_then(|field| field.strip_suffix('`'))
+        {
+            return Self {
+                field: if path == "." {
+                    String::from(field)
+                } else {
+                    format!("{path}.{field}")
+                },
+                message: String::from("missing field"),
+            };
+        }
+
+        Self {
+            field: path,
+            message,
+        }
+    }
+}
+
+/// The fields of a JSON `DependencyGraphQuery`, before defaults are applied.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct QueryFields {
+    /// The root crates.
+    roots: Vec<RootFields>,
+
+    /// The target triple.
+    target: Option<String>,
+
+    /// The additional cfg names.
+    cfgs: Option<Vec<CfgField>>,
+
+    /// The host triple.
+    host: Option<String>,
+
+    /// If procedural macros are inferred.
+    infer_proc_macros: Option<bool>,
+
+    /// The feature resolver.
+    resolver: Option<ResolverField>,
+
+    /// The greatest distance from a root crate.
+    max_depth: Option<usize>,
+
+    /// The excluded crates.
+    exclude: Option<Vec<String>>,
+
+    /// The kinds of dependencies to follow.
+    kinds: Option<Vec<DependencyKind>>,
+
+    /// The maximum number of inactive optional dependencies.
+    potential_dependencies: Option<usize>,
+
+    /// The format to export the dependency graph in.
+    format: Option<FormatField>,
+}
+
+/// The fields of a root crate of a JSON `DependencyGraphQuery`, before defaults are applied.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields, expecting = "a root crate object")]
+struct RootFields {
+    /// The name of the crate.
+    name: String,
+
+    /// The features enabled for the crate.
+    features: Option<Vec<String>>,
+
+    /// If the default features of the crate are enabled.
+    default_features: Option<bool>,
+
+    /// If every feature of the crate is enabled.
+    all_features: Option<bool>,
+}
+
+/// A cfg name of a JSON `DependencyGraphQuery`, which must be a valid cfg.
+#[derive(Deserialize)]
+#[serde(try_from = "String")]
+struct CfgField(String);
+
+impl TryFrom<String> for CfgField {
+    type Error = &'static str;
+
+    fn try_from(cfg: String) -> Result<Self, Self::Error> {
+        Cfg::from_str(&cfg)
+            .map(|_| Self(cfg))
+            .map_err(|_| "not a valid cfg")
+    }
+}
+
+/// The `Resolver` of a JSON `DependencyGraphQuery`, given by its version.
+#[derive(Deserialize)]
+#[serde(try_from = "u64")]
+struct ResolverField(Resolver);
+
+impl TryFrom<u64> for ResolverField {
+    type Error = &'static str;
+
+    fn try_from(version: u64) -> Result<Self, Self::Error> {
+        match version {
+            1 => Ok(Self(Resolver::Unified)),
+            2 => Ok(Self(Resolver::V2)),
+            _ => Err("expected 1 or 2"),
+        }
+    }
+}
+
+/// The `GraphFormat` of a JSON `DependencyGraphQuery`, given by its name.
+#[derive(Deserialize)]
+#[serde(try_from = "String")]
+struct FormatField(GraphFormat);
+
+impl TryFrom<String> for FormatField {
+    type Error = String;
+
+    fn try_from(format: String) -> Result<Self, Self::Error> {
+        format
+            .parse()
+            .map(Self)
+            .map_err(|_| format!("unknown format `{format}`"))
+    }
+}
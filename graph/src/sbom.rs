@@ -0,0 +1,577 @@
+use super::{Crate, DependencyGraph, DependencyKind, Graph};
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::Serialize;
+use std::{
+    error::Error,
+    fmt::{self, Display, Formatter},
+    str::FromStr,
+};
+
+/// The name of the tool recorded as the author of SBOMs.
+const TOOL_NAME: &str = "vault";
+
+/// The version of the tool recorded as the author of SBOMs.
+const TOOL_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// A Software Bill of Materials format.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SbomFormat {
+    /// A `CycloneDX` 1.5 JSON document.
+    CycloneDx,
+
+    /// An SPDX 2.3 JSON document.
+    Spdx,
+}
+
+/// An error returned when parsing an unknown `SbomFormat` name.
+#[derive(Debug, Eq, PartialEq)]
+pub struct UnknownSbomFormat(pub String);
+
+impl Display for UnknownSbomFormat {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "Unknown SBOM format: {}", self.0)
+    }
+}
+
+impl Error for UnknownSbomFormat {}
+
+impl FromStr for SbomFormat {
+    type Err = UnknownSbomFormat;
+
+    /// Parses an `SbomFormat` from its name, as used by the `format` query option.
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name {
+            "cyclonedx" => Ok(Self::CycloneDx),
+            "spdx" => Ok(Self::Spdx),
+            _ => Err(UnknownSbomFormat(String::from(name))),
+        }
+    }
+}
+
+impl SbomFormat {
+    /// Returns the `Content-Type` of documents in the format.
+    #[must_use]
+    pub const fn content_type(self) -> &'static str {
+        match self {
+            Self::CycloneDx => "application/vnd.cyclonedx+json",
+            Self::Spdx => "application/spdx+json",
+        }
+    }
+}
+
+/// The identity of a generated SBOM document.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SbomInfo {
+    /// The time at which the document was created.
+    pub created: DateTime<Utc>,
+
+    /// A random UUID identifying the document.
+    pub uuid: String,
+}
+
+impl SbomInfo {
+    /// Creates a new `SbomInfo` for a document created now, with a random version 4 UUID.
+    #[must_use]
+    pub fn new() -> Self {
+        let mut bytes: [u8; 16] = rand::random();
+        bytes[6] = (bytes[6] & 0x0f) | 0x40;
+        bytes[8] = (bytes[8] & 0x3f) | 0x80;
+
+        let hex = format!("{:032x}", u128::from_be_bytes(bytes));
+
+        Self {
+            created: Utc::now(),
+            uuid: format!(
+                "{}-{}-{}-{}-{}",
+                &hex[0..8],
+                &hex[8..12],
+                &hex[12..16],
+                &hex[16..20],
+                &hex[20..32]
+            ),
+        }
+    }
+
+    /// Returns the creation time as an RFC 3339 UTC timestamp.
+    fn timestamp(&self) -> String {
+        self.created.to_rfc3339_opts(SecondsFormat::Secs, true)
+    }
+}
+
+impl Default for SbomInfo {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Graph {
+    /// Returns a Software Bill of Materials listing the crates of a dependency graph, serialized to JSON.
+    ///
+    /// Licenses are taken from the registry, and are omitted for crates which do not declare one. Relationships
+    /// follow the active dependencies of the dependency graph, once per pair of crates.
+    ///
+    /// # Arguments
+    /// * `dependency_graph` - the `DependencyGraph` to describe.
+    /// * `manifest` - the `Crate` outside the registry the dependency graph was built from, if any.
+    /// * `format` - the `SbomFormat` of the document.
+    /// * `info` - the `SbomInfo` identifying the document.
+    #[must_use]
+    pub fn get_sbom(
+        &self,
+        dependency_graph: &DependencyGraph,
+        manifest: Option<&Crate>,
+        format: SbomFormat,
+        info: &SbomInfo,
+    ) -> String {
+        let components: Vec<&Crate> = dependency_graph
+            .crates
+            .iter()
+            .map(|crate_distance| match manifest {
+                Some(manifest) if crate_distance.distance == 0 => manifest,
+                _ => &self.crates()[crate_distance.name],
+            })
+            .collect();
+
+        // unique edges by component index
+        let mut edges: Vec<(usize, usize, DependencyKind)> = vec![];
+        for dependency in &dependency_graph.dependencies {
+            if !dependency.active {
+                continue;
+            }
+
+            let index_of = |name: &String| {
+                components
+                    .iter()
+                    .position(|component| &component.name == name)
+                    .unwrap()
+            };
+            let edge = (
+                index_of(dependency.from),
+                index_of(dependency.to),
+                dependency.kind,
+            );
+
+            if !edges
+                .iter()
+                .any(|&(from, to, _)| (from, to) == (edge.0, edge.1))
+            {
+                edges.push(edge);
+            }
+        }
+
+        match format {
+            SbomFormat::CycloneDx => {
+                serde_json::to_string(&CycloneDxBom::new(&components, &edges, info)).unwrap()
+            }
+            SbomFormat::Spdx => {
+                serde_json::to_string(&SpdxDocument::new(&components, &edges, info)).unwrap()
+            }
+        }
+    }
+}
+
+/// Returns the package URL of a crate.
+///
+/// # Arguments
+/// * `crate_val` - the `Crate`.
+fn purl(crate_val: &Crate) -> String {
+    if crate_val.version.is_empty() {
+        format!("pkg:cargo/{}", crate_val.name)
+    } else {
+        format!("pkg:cargo/{}@{}", crate_val.name, crate_val.version)
+    }
+}
+
+/// Returns the SPDX license expression of a crate, if declared.
+///
+/// Legacy crates.io licenses separate alternatives with `/`, which is rewritten to `OR`.
+///
+/// # Arguments
+/// * `crate_val` - the `Crate`.
+fn license_expression(crate_val: &Crate) -> Option<String> {
+    crate_val
+        .license
+        .as_deref()
+        .filter(|license| !license.trim().is_empty())
+        .map(|license| {
+            license
+                .split('/')
+                .map(str::trim)
+                .collect::<Vec<&str>>()
+                .join(" OR ")
+        })
+}
+
+/// A `CycloneDX` 1.5 bill of materials.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CycloneDxBom<'a> {
+    /// Always `CycloneDX`.
+    bom_format: &'static str,
+
+    /// Always `1.5`.
+    spec_version: &'static str,
+
+    /// The URN of the document's UUID.
+    serial_number: String,
+
+    /// The revision of the document.
+    version: usize,
+
+    /// The metadata of the document.
+    metadata: CycloneDxMetadata<'a>,
+
+    /// The dependencies of the root crate.
+    components: Vec<CycloneDxComponent<'a>>,
+
+    /// The dependencies of each component.
+    dependencies: Vec<CycloneDxDependency>,
+}
+
+/// The metadata of a `CycloneDX` bill of materials.
+#[derive(Serialize)]
+struct CycloneDxMetadata<'a> {
+    /// The time at which the document was created.
+    timestamp: String,
+
+    /// The tools which created the document.
+    tools: CycloneDxTools,
+
+    /// The root crate.
+    component: CycloneDxComponent<'a>,
+}
+
+/// The tools which created a `CycloneDX` bill of materials.
+#[derive(Serialize)]
+struct CycloneDxTools {
+    /// The tools, as components.
+    components: Vec<CycloneDxTool>,
+}
+
+/// A tool which created a `CycloneDX` bill of materials.
+#[derive(Serialize)]
+struct CycloneDxTool {
+    /// Always `application`.
+    #[serde(rename = "type")]
+    component_type: &'static str,
+
+    /// The name of the tool.
+    name: &'static str,
+
+    /// The version of the tool.
+    version: &'static str,
+}
+
+/// A crate listed in a `CycloneDX` bill of materials.
+#[derive(Serialize)]
+struct CycloneDxComponent<'a> {
+    /// Always `library`.
+    #[serde(rename = "type")]
+    component_type: &'static str,
+
+    /// The reference of the component, which is its package URL.
+    #[serde(rename = "bom-ref")]
+    bom_ref: String,
+
+    /// The name of the crate.
+    name: &'a String,
+
+    /// The version of the crate.
+    #[serde(skip_serializing_if = "String::is_empty")]
+    version: &'a String,
+
+    /// The description of the crate.
+    #[serde(skip_serializing_if = "String::is_empty")]
+    description: &'a String,
+
+    /// The licenses of the crate, if declared.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    licenses: Vec<CycloneDxLicense>,
+
+    /// The package URL of the crate.
+    purl: String,
+}
+
+/// A license expression in a `CycloneDX` bill of materials.
+#[derive(Serialize)]
+struct CycloneDxLicense {
+    /// The SPDX license expression.
+    expression: String,
+}
+
+/// The dependencies of a component in a `CycloneDX` bill of materials.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CycloneDxDependency {
+    /// The reference of the component.
+    #[serde(rename = "ref")]
+    dependency_ref: String,
+
+    /// The references of the components it depends on.
+    depends_on: Vec<String>,
+}
+
+impl<'a> CycloneDxBom<'a> {
+    /// Creates a new `CycloneDxBom`.
+    ///
+    /// # Arguments
+    /// * `components` - the crates to list, with the root first.
+    /// * `edges` - the unique dependencies between crates, by index.
+    /// * `info` - the `SbomInfo` identifying the document.
+    fn new(
+        components: &[&'a Crate],
+        edges: &[(usize, usize, DependencyKind)],
+        info: &SbomInfo,
+    ) -> Self {
+        let component = |crate_val: &'a Crate| CycloneDxComponent {
+            component_type: "library",
+            bom_ref: purl(crate_val),
+            name: &crate_val.name,
+            version: &crate_val.version,
+            description: &crate_val.description,
+            licenses: license_expression(crate_val)
+                .map(|expression| CycloneDxLicense { expression })
+                .into_iter()
+                .collect(),
+            purl: purl(crate_val),
+        };
+
+        Self {
+            bom_format: "CycloneDX",
+            spec_version: "1.5",
+            serial_number: format!("urn:uuid:{}", info.uuid),
+            version: 1,
+            metadata: CycloneDxMetadata {
+                timestamp: info.timestamp(),
+                tools: CycloneDxTools {
+                    components: vec![CycloneDxTool {
+                        component_type: "application",
+                        name: TOOL_NAME,
+                        version: TOOL_VERSION,
+                    }],
+                },
+                component: component(components[0]),
+            },
+            components: components[1..]
+                .iter()
+                .map(|&crate_val| component(crate_val))
+                .collect(),
+            dependencies: components
+                .iter()
+                .enumerate()
+                .map(|(index, &crate_val)| CycloneDxDependency {
+                    dependency_ref: purl(crate_val),
+                    depends_on: edges
+                        .iter()
+                        .filter(|&&(from, _, _)| from == index)
+                        .map(|&(_, to, _)| purl(components[to]))
+                        .collect(),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// An SPDX 2.3 document.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SpdxDocument<'a> {
+    /// Always `SPDX-2.3`.
+    spdx_version: &'static str,
+
+    /// Always `CC0-1.0`.
+    data_license: &'static str,
+
+    /// Always `SPDXRef-DOCUMENT`.
+    #[serde(rename = "SPDXID")]
+    spdx_id: &'static str,
+
+    /// The name of the document, after the root crate.
+    name: String,
+
+    /// A unique URI identifying the document.
+    document_namespace: String,
+
+    /// How the document was created.
+    creation_info: SpdxCreationInfo,
+
+    /// The crates described by the document.
+    packages: Vec<SpdxPackage<'a>>,
+
+    /// The relationships between the document and crates.
+    relationships: Vec<SpdxRelationship>,
+}
+
+/// How an SPDX document was created.
+#[derive(Serialize)]
+struct SpdxCreationInfo {
+    /// The time at which the document was created.
+    created: String,
+
+    /// The tools which created the document.
+    creators: Vec<String>,
+}
+
+/// A crate described by an SPDX document.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SpdxPackage<'a> {
+    /// The identifier of the package within the document.
+    #[serde(rename = "SPDXID")]
+    spdx_id: String,
+
+    /// The name of the crate.
+    name: &'a String,
+
+    /// The version of the crate.
+    #[serde(skip_serializing_if = "String::is_empty")]
+    version_info: &'a String,
+
+    /// Where the crate can be downloaded from, or `NOASSERTION`.
+    download_location: String,
+
+    /// Always `false`, as files are not analyzed.
+    files_analyzed: bool,
+
+    /// Always `NOASSERTION`, as licenses are not concluded.
+    license_concluded: &'static str,
+
+    /// The license expression declared by the crate, or `NOASSERTION`.
+    license_declared: String,
+
+    /// Always `NOASSERTION`.
+    copyright_text: &'static str,
+
+    /// The description of the crate.
+    #[serde(skip_serializing_if = "String::is_empty")]
+    description: &'a String,
+
+    /// The package URL of the crate.
+    external_refs: Vec<SpdxExternalRef>,
+}
+
+/// An external reference of a package in an SPDX document.
+#[derive(Serialize)]
+struct SpdxExternalRef {
+    /// Always `PACKAGE-MANAGER`.
+    #[serde(rename = "referenceCategory")]
+    category: &'static str,
+
+    /// Always `purl`.
+    #[serde(rename = "referenceType")]
+    kind: &'static str,
+
+    /// The package URL.
+    #[serde(rename = "referenceLocator")]
+    locator: String,
+}
+
+/// A relationship between elements of an SPDX document.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SpdxRelationship {
+    /// The identifier of the element the relationship is from.
+    spdx_element_id: String,
+
+    /// The type of the relationship.
+    relationship_type: &'static str,
+
+    /// The identifier of the element the relationship is to.
+    related_spdx_element: String,
+}
+
+impl<'a> SpdxDocument<'a> {
+    /// Creates a new `SpdxDocument`.
+    ///
+    /// # Arguments
+    /// * `components` - the crates to describe, with the root first.
+    /// * `edges` - the unique dependencies between crates, by index.
+    /// * `info` - the `SbomInfo` identifying the document.
+    fn new(
+        components: &[&'a Crate],
+        edges: &[(usize, usize, DependencyKind)],
+        info: &SbomInfo,
+    ) -> Self {
+        let root = components[0];
+        let spdx_ids: Vec<String> = components
+            .iter()
+            .enumerate()
+            .map(|(index, crate_val)| {
+                let name: String = crate_val
+                    .name
+                    .chars()
+                    .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+                    .collect();
+                format!("SPDXRef-Package-{name}-{index}")
+            })
+            .collect();
+
+        let mut relationships = vec![SpdxRelationship {
+            spdx_element_id: String::from("SPDXRef-DOCUMENT"),
+            relationship_type: "DESCRIBES",
+            related_spdx_element: spdx_ids[0].clone(),
+        }];
+        for &(from, to, kind) in edges {
+            relationships.push(if kind == DependencyKind::Build {
+                SpdxRelationship {
+                    spdx_element_id: spdx_ids[to].clone(),
+                    relationship_type: "BUILD_DEPENDENCY_OF",
+                    related_spdx_element: spdx_ids[from].clone(),
+                }
+            } else {
+                SpdxRelationship {
+                    spdx_element_id: spdx_ids[from].clone(),
+                    relationship_type: "DEPENDS_ON",
+                    related_spdx_element: spdx_ids[to].clone(),
+                }
+            });
+        }
+
+        Self {
+            spdx_version: "SPDX-2.3",
+            data_license: "CC0-1.0",
+            spdx_id: "SPDXRef-DOCUMENT",
+            name: if root.version.is_empty() {
+                root.name.clone()
+            } else {
+                format!("{}-{}", root.name, root.version)
+            },
+            document_namespace: format!("https://spdx.org/spdxdocs/{}-{}", root.name, info.uuid),
+            creation_info: SpdxCreationInfo {
+                created: info.timestamp(),
+                creators: vec![format!("Tool: {TOOL_NAME}-{TOOL_VERSION}")],
+            },
+            packages: components
+                .iter()
+                .zip(spdx_ids)
+                .enumerate()
+                .map(|(index, (&crate_val, spdx_id))| SpdxPackage {
+                    spdx_id,
+                    name: &crate_val.name,
+                    version_info: &crate_val.version,
+                    // the root crate may not be published
+                    download_location: if index == 0 || crate_val.version.is_empty() {
+                        String::from("NOASSERTION")
+                    } else {
+                        format!(
+                            "https://crates.io/api/v1/crates/{}/{}/download",
+                            crate_val.name, crate_val.version
+                        )
+                    },
+                    files_analyzed: false,
+                    license_concluded: "NOASSERTION",
+                    license_declared: license_expression(crate_val)
+                        .unwrap_or_else(|| String::from("NOASSERTION")),
+                    copyright_text: "NOASSERTION",
+                    description: &crate_val.description,
+                    external_refs: vec![SpdxExternalRef {
+                        category: "PACKAGE-MANAGER",
+                        kind: "purl",
+                        locator: purl(crate_val),
+                    }],
+                })
+                .collect(),
+            relationships,
+        }
+    }
+}
@@ -40,13 +40,17 @@ pub struct Crate {
     ///
     /// This is not set on deserialization and must be populated later when assigning versions to crates.
     ///
-    /// As NaiveDateTime does not provide a default constructor, the default is defined using the default_naive_date_time module provided below.
+    /// As `NaiveDateTime` does not provide a default constructor, the default is defined using the `default_naive_date_time` module provided below.
     #[serde(skip_deserializing, default = "default_naive_date_time")]
     pub created_at: NaiveDateTime,
 
     /// The dependencies of the `Crate`.
     ///
     /// This is not set on deserialization and instead must be populated later when processing dependencies.
+    ///
+    /// Once a `Graph` is loaded, the dependencies of crates in the registry are moved into its compact storage and
+    /// this is left empty; they are listed by `Graph::get_dependencies` instead. Crates outside the registry (such as
+    /// ones built from a manifest) keep their dependencies here.
     #[serde(skip_deserializing, default, skip_serializing)]
     pub dependencies: Vec<Dependency>,
 
@@ -56,7 +60,7 @@ pub struct Crate {
     /// The number of downloads of the `Crate`.
     pub downloads: usize,
 
-    /// The features exposed by the `Crate`.
+    /// The features exposed by the `Crate`, including the implicit features of optional dependencies.
     ///
     /// This is not set on deserialization and instead must be populated later when assigning versions to crates.
     #[serde(skip_deserializing, default)]
@@ -68,6 +72,18 @@ pub struct Crate {
     #[serde(skip_deserializing, default)]
     pub keywords: Vec<String>,
 
+    /// The SPDX license expression of the most recent stable version (if available) of the `Crate`, if declared.
+    ///
+    /// This is not set on deserialization and instead must be populated later when assigning versions to crates.
+    #[serde(skip_deserializing, default)]
+    pub license: Option<String>,
+
+    /// The greatest distance from the `Crate` to any of its transitive dependencies in its default configuration.
+    ///
+    /// This is not set on deserialization and instead must be computed once the registry is loaded.
+    #[serde(skip_deserializing, default)]
+    pub max_depth: usize,
+
     /// The SQL id of the `Crate`.
     ///
     /// This is disregarded by the API.
@@ -79,6 +95,24 @@ pub struct Crate {
     /// This becomes the key of the `Crate` for easier search/access by the API.
     pub name: String,
 
+    /// The `PageRank` of the `Crate` over dependency edges, scaled so that the average crate scores `1`.
+    ///
+    /// This is not set on deserialization and instead must be computed once all dependencies are loaded.
+    #[serde(skip_deserializing, default)]
+    pub pagerank: f64,
+
+    /// The number of crates the `Crate` depends on transitively in its default configuration.
+    ///
+    /// This is not set on deserialization and instead must be computed once the registry is loaded.
+    #[serde(skip_deserializing, default)]
+    pub transitive_dependencies: usize,
+
+    /// The number of crates depending on the `Crate`, directly or transitively.
+    ///
+    /// This is not set on deserialization and instead must be computed once all dependencies are loaded.
+    #[serde(skip_deserializing, default)]
+    pub transitive_dependents: usize,
+
     /// The most recent stable version (if available) of the `Crate`.
     ///
     /// This is not set on deserialization and instead must be populated later when assigning versions to crates.
@@ -132,6 +166,35 @@ pub struct Dependency {
 
     /// The destination version of the `Dependency`.
     pub to: String,
+
+    /// The kind of the `Dependency`.
+    #[serde(skip_serializing)]
+    pub kind: DependencyKind,
+}
+
+/// The kind of a `Dependency`.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DependencyKind {
+    /// A standard dependency.
+    Normal,
+
+    /// A dependency only used for tests, examples, and benchmarks.
+    Dev,
+
+    /// A dependency of the build script.
+    Build,
+}
+
+impl From<usize> for DependencyKind {
+    /// Converts the kind of a `SqlDependency` (0: standard, 1: dev, 2: build).
+    fn from(kind: usize) -> Self {
+        match kind {
+            1 => Self::Dev,
+            2 => Self::Build,
+            _ => Self::Normal,
+        }
+    }
 }
 
 /// A keyword in the crates.io registry.
@@ -174,6 +237,12 @@ pub struct Version {
     /// The id of the `Version`.
     pub id: usize,
 
+    /// The license expression of the `Version`, if declared.
+    ///
+    /// Older dumps without a license column leave this empty.
+    #[serde(default)]
+    pub license: Option<String>,
+
     /// The number of the `Version`.
     ///
     /// This will likely be SemVer-compliant; however some versions are not.
@@ -212,7 +281,8 @@ pub struct SqlDependency {
 /// An implementation of a default constructor for the `NaiveDateTime` struct.
 ///
 /// This creates a `NaiveDateTime` with 0 seconds and 0 nanoseconds since January 1, 1970.
-fn default_naive_date_time() -> NaiveDateTime {
+#[allow(deprecated, clippy::missing_const_for_fn)]
+pub fn default_naive_date_time() -> NaiveDateTime {
     NaiveDateTime::from_timestamp_opt(0, 0).unwrap()
 }
 
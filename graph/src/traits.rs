@@ -1,7 +1,11 @@
-use super::schema::{Category, Crate, Keyword};
+use super::{
+    compact::CrateMap,
+    schema::{Category, Crate, Keyword},
+};
 use ahash::AHashMap;
 use rand::Rng;
 use std::{
+    cmp::Ordering,
     collections::{BTreeSet, VecDeque},
     hash::BuildHasher,
     ops::Bound::{Excluded, Included},
@@ -20,6 +24,14 @@ pub trait Vertex {
 
     /// Returns a number corresponding to the popularity of the Vertex.
     fn popularity(&self) -> usize;
+
+    /// Returns a number corresponding to the structural importance of the Vertex.
+    ///
+    /// This defaults to the popularity of the Vertex.
+    #[allow(clippy::cast_precision_loss)]
+    fn centrality(&self) -> f64 {
+        self.popularity() as f64
+    }
 }
 
 impl Vertex for Category {
@@ -54,6 +66,11 @@ impl Vertex for Crate {
     fn popularity(&self) -> usize {
         self.downloads
     }
+
+    /// Returns a number corresponding to the structural importance of the Vertex.
+    fn centrality(&self) -> f64 {
+        self.pagerank
+    }
 }
 
 impl Vertex for Keyword {
@@ -87,35 +104,97 @@ impl<T, U, S: BuildHasher> Random<T> for AHashMap<U, T, S> {
     }
 }
 
+impl Random<Crate> for CrateMap {
+    /// Returns a random element from a `CrateMap`.
+    fn random(&self) -> &Crate {
+        self.values()
+            .nth(rand::thread_rng().gen_range(0..self.len()))
+            .unwrap()
+    }
+}
+
+/// A trait for collections of vertices which can be looked up by their unique identifiers.
+pub trait VertexMap<T: Vertex> {
+    /// Returns the vertex with the specified identifier, if found.
+    ///
+    /// # Arguments
+    /// * `id` - the unique identifier of the vertex.
+    fn get_vertex(&self, id: &str) -> Option<&T>;
+}
+
+impl<T: Vertex, S: BuildHasher> VertexMap<T> for AHashMap<String, T, S> {
+    /// Returns the vertex with the specified identifier, if found.
+    ///
+    /// # Arguments
+    /// * `id` - the unique identifier of the vertex.
+    fn get_vertex(&self, id: &str) -> Option<&T> {
+        self.get(id)
+    }
+}
+
+impl VertexMap<Crate> for CrateMap {
+    /// Returns the vertex with the specified identifier, if found.
+    ///
+    /// # Arguments
+    /// * `id` - the unique identifier of the vertex.
+    fn get_vertex(&self, id: &str) -> Option<&Crate> {
+        self.get(id)
+    }
+}
+
+/// The key by which search results are ranked.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum SearchRanking {
+    /// Rank results by popularity (e.g. downloads for crates).
+    #[default]
+    Popularity,
+
+    /// Rank results by structural importance (e.g. `PageRank` for crates).
+    Centrality,
+}
+
 /// A trait to enable searching for elements within a collection.
 pub trait Search<T: Vertex> {
-    /// Searches ro elements within a collection.
+    /// Searches ro elements within a collection, ranked by popularity.
+    ///
+    /// # Arguments
+    /// * `search_term` - the term being searched.
+    /// * `collection` - the `VertexMap` containing the values of the collection.
+    fn search<'a>(
+        &'a self,
+        search_term: &str,
+        collection: &'a impl VertexMap<T>,
+    ) -> VecDeque<&'a T> {
+        self.search_ranked(search_term, collection, SearchRanking::Popularity)
+    }
+
+    /// Searches ro elements within a collection, ranked by the given key.
     ///
     /// # Arguments
     /// * `search_term` - the term being searched.
-    /// * `collection` - the `AHashMap` containing the values of the collection.
-    fn search<'a>(&self, search_term: &str, collection: &'a AHashMap<String, T>)
-        -> VecDeque<&'a T>;
+    /// * `collection` - the `VertexMap` containing the values of the collection.
+    /// * `ranking` - the `SearchRanking` to order results by.
+    fn search_ranked<'a>(
+        &'a self,
+        search_term: &str,
+        collection: &'a impl VertexMap<T>,
+        ranking: SearchRanking,
+    ) -> VecDeque<&'a T>;
 }
 
 impl<T: Vertex> Search<T> for BTreeSet<String> {
-    /// Searches ro elements within a collection.
+    /// Searches ro elements within a collection, ranked by the given key.
     ///
     /// # Arguments
     /// * `search_term` - the term being searched.
-    /// * `collection` - the `AHashMap` containing the values of the collection.
-    fn search<'a>(
-        &self,
+    /// * `collection` - the `VertexMap` containing the values of the collection.
+    /// * `ranking` - the `SearchRanking` to order results by.
+    fn search_ranked<'a>(
+        &'a self,
         search_term: &str,
-        collection: &'a AHashMap<String, T>,
+        collection: &'a impl VertexMap<T>,
+        ranking: SearchRanking,
     ) -> VecDeque<&'a T> {
-        // Search results are sorted by popularity, with tiebreakers favoring shorter results.
-        // As the results are traversed in alphabetical order, further ties will be broken by lexicographic order.
-        let should_replace = |a: &T, b: &T| {
-            a.popularity() > b.popularity()
-                || (a.popularity() == b.popularity() && a.id().len() < b.id().len())
-        };
-
         if search_term.is_empty() {
             VecDeque::new()
         } else {
@@ -129,45 +208,105 @@ impl<T: Vertex> Search<T> for BTreeSet<String> {
             let prefixed_vertex_names = self
                 .range::<String, _>((Included(&String::from(search_term)), Excluded(&range_end)));
 
-            let mut search_results: VecDeque<&T> = VecDeque::with_capacity(MAX_SEARCH_LENGTH + 1);
-
-            for prefixed_vertex_name in prefixed_vertex_names {
-                if prefixed_vertex_name != search_term {
-                    let prefixed_vertex = collection.get(prefixed_vertex_name).unwrap();
-
-                    if search_results.is_empty() {
-                        // add if first element found
-                        search_results.push_back(prefixed_vertex);
-                    } else if should_replace(prefixed_vertex, search_results.back().unwrap()) {
-                        if let Some((index, _)) =
-                            search_results
-                                .iter()
-                                .enumerate()
-                                .find(|(_, results_vertex)| {
-                                    should_replace(prefixed_vertex, results_vertex)
-                                })
-                        {
-                            // insert before first element that has a lower priority than the current name
-                            search_results.insert(index, prefixed_vertex);
-
-                            // cap results length at `MAX_SEARCH_LENGTH`
-                            if search_results.len() > MAX_SEARCH_LENGTH {
-                                search_results.pop_back();
-                            }
-                        }
-                    }
-                }
-            }
+            rank_search_results(
+                search_term,
+                prefixed_vertex_names.map(|prefixed_vertex_name| {
+                    collection.get_vertex(prefixed_vertex_name).unwrap()
+                }),
+                collection,
+                ranking,
+            )
+        }
+    }
+}
+
+impl Search<Crate> for CrateMap {
+    /// Searches ro elements within a collection, ranked by the given key.
+    ///
+    /// The `CrateMap` holds the crates themselves, so results are taken from it rather than looked up in the
+    /// collection.
+    ///
+    /// # Arguments
+    /// * `search_term` - the term being searched.
+    /// * `_collection` - the `VertexMap` containing the values of the collection.
+    /// * `ranking` - the `SearchRanking` to order results by.
+    fn search_ranked<'a>(
+        &'a self,
+        search_term: &str,
+        _collection: &'a impl VertexMap<Crate>,
+        ranking: SearchRanking,
+    ) -> VecDeque<&'a Crate> {
+        if search_term.is_empty() {
+            VecDeque::new()
+        } else {
+            // crates are ordered by name, so those with the search term as a prefix are adjacent
+            rank_search_results(
+                search_term,
+                self.prefixed(search_term).iter(),
+                self,
+                ranking,
+            )
+        }
+    }
+}
 
-            // If the search term is a vertex, prepend tha to the results.
-            if let Some(search_vertex) = collection.get(search_term) {
-                search_results.push_front(search_vertex);
-                if search_results.len() > MAX_SEARCH_LENGTH {
-                    search_results.pop_back();
+/// Ranks the vertices with the search term as a prefix, keeping at most `MAX_SEARCH_LENGTH`.
+///
+/// # Arguments
+/// * `search_term` - the term being searched.
+/// * `prefixed_vertices` - the vertices with the search term as a prefix, in alphabetical order.
+/// * `collection` - the `VertexMap` containing the values of the collection.
+/// * `ranking` - the `SearchRanking` to order results by.
+fn rank_search_results<'a, T: Vertex + 'a>(
+    search_term: &str,
+    prefixed_vertices: impl Iterator<Item = &'a T>,
+    collection: &'a impl VertexMap<T>,
+    ranking: SearchRanking,
+) -> VecDeque<&'a T> {
+    // Search results are sorted by the ranking key, with tiebreakers favoring shorter results.
+    // As the results are traversed in alphabetical order, further ties will be broken by lexicographic order.
+    let should_replace = |a: &T, b: &T| {
+        let ordering = match ranking {
+            SearchRanking::Popularity => a.popularity().cmp(&b.popularity()),
+            SearchRanking::Centrality => a.centrality().total_cmp(&b.centrality()),
+        };
+
+        ordering == Ordering::Greater
+            || (ordering == Ordering::Equal && a.id().len() < b.id().len())
+    };
+
+    let mut search_results: VecDeque<&T> = VecDeque::with_capacity(MAX_SEARCH_LENGTH + 1);
+
+    for prefixed_vertex in prefixed_vertices {
+        if prefixed_vertex.id() != search_term {
+            if search_results.is_empty() {
+                // add if first element found
+                search_results.push_back(prefixed_vertex);
+            } else if should_replace(prefixed_vertex, search_results.back().unwrap()) {
+                if let Some((index, _)) = search_results
+                    .iter()
+                    .enumerate()
+                    .find(|(_, results_vertex)| should_replace(prefixed_vertex, results_vertex))
+                {
+                    // insert before first element that has a lower priority than the current name
+                    search_results.insert(index, prefixed_vertex);
+
+                    // cap results length at `MAX_SEARCH_LENGTH`
+                    if search_results.len() > MAX_SEARCH_LENGTH {
+                        search_results.pop_back();
+                    }
                 }
             }
+        }
+    }
 
-            search_results
+    // If the search term is a vertex, prepend tha to the results.
+    if let Some(search_vertex) = collection.get_vertex(search_term) {
+        search_results.push_front(search_vertex);
+        if search_results.len() > MAX_SEARCH_LENGTH {
+            search_results.pop_back();
         }
     }
+
+    search_results
 }
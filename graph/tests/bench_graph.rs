@@ -5,7 +5,7 @@ extern crate test;
 extern crate lazy_static;
 
 use test::Bencher;
-use vault_graph::Graph;
+use vault_graph::{DependencyGraphOptions, DependencyKind, Graph, Resolver};
 
 lazy_static! {
     static ref GRAPH: Graph = Graph::test();
@@ -19,6 +19,7 @@ fn bench_graph_actix_web(b: &mut Bencher) {
             vec![],
             &Some(String::from("x86_64-unknown-linux-gnu")),
             &Some(String::from("unix")),
+            &DependencyGraphOptions::default(),
         )
     });
 }
@@ -31,6 +32,7 @@ fn bench_graph_rocket(b: &mut Bencher) {
             vec![],
             &Some(String::from("x86_64-unknown-linux-gnu")),
             &Some(String::from("unix")),
+            &DependencyGraphOptions::default(),
         )
     });
 }
@@ -43,6 +45,7 @@ fn bench_graph_warp(b: &mut Bencher) {
             vec![],
             &Some(String::from("x86_64-unknown-linux-gnu")),
             &Some(String::from("unix")),
+            &DependencyGraphOptions::default(),
         )
     });
 }
@@ -55,6 +58,7 @@ fn bench_graph_hyper(b: &mut Bencher) {
             vec![],
             &Some(String::from("x86_64-unknown-linux-gnu")),
             &Some(String::from("unix")),
+            &DependencyGraphOptions::default(),
         )
     });
 }
@@ -67,6 +71,7 @@ fn bench_graph_serde(b: &mut Bencher) {
             vec![],
             &Some(String::from("x86_64-unknown-linux-gnu")),
             &Some(String::from("unix")),
+            &DependencyGraphOptions::default(),
         )
     });
 }
@@ -79,6 +84,7 @@ fn bench_graph_tokio(b: &mut Bencher) {
             vec![],
             &Some(String::from("x86_64-unknown-linux-gnu")),
             &Some(String::from("unix")),
+            &DependencyGraphOptions::default(),
         )
     });
 }
@@ -91,6 +97,7 @@ fn bench_graph_futures(b: &mut Bencher) {
             vec![],
             &Some(String::from("x86_64-unknown-linux-gnu")),
             &Some(String::from("unix")),
+            &DependencyGraphOptions::default(),
         )
     });
 }
@@ -103,6 +110,7 @@ fn bench_graph_wasm_bindgen(b: &mut Bencher) {
             vec![],
             &Some(String::from("x86_64-unknown-linux-gnu")),
             &Some(String::from("unix")),
+            &DependencyGraphOptions::default(),
         )
     });
 }
@@ -115,6 +123,7 @@ fn bench_graph_ripgrep(b: &mut Bencher) {
             vec![],
             &Some(String::from("x86_64-unknown-linux-gnu")),
             &Some(String::from("unix")),
+            &DependencyGraphOptions::default(),
         )
     });
 }
@@ -127,6 +136,7 @@ fn bench_graph_clippy(b: &mut Bencher) {
             vec![],
             &Some(String::from("x86_64-unknown-linux-gnu")),
             &Some(String::from("unix")),
+            &DependencyGraphOptions::default(),
         )
     });
 }
@@ -139,6 +149,7 @@ fn bench_graph_rustfmt(b: &mut Bencher) {
             vec![],
             &Some(String::from("x86_64-unknown-linux-gnu")),
             &Some(String::from("unix")),
+            &DependencyGraphOptions::default(),
         )
     });
 }
@@ -151,6 +162,7 @@ fn bench_graph_cargo(b: &mut Bencher) {
             vec![],
             &Some(String::from("x86_64-unknown-linux-gnu")),
             &Some(String::from("unix")),
+            &DependencyGraphOptions::default(),
         )
     });
 }
@@ -163,6 +175,7 @@ fn bench_graph_crossbeam(b: &mut Bencher) {
             vec![],
             &Some(String::from("x86_64-unknown-linux-gnu")),
             &Some(String::from("unix")),
+            &DependencyGraphOptions::default(),
         )
     });
 }
@@ -175,6 +188,7 @@ fn bench_graph_parking_lot(b: &mut Bencher) {
             vec![],
             &Some(String::from("x86_64-unknown-linux-gnu")),
             &Some(String::from("unix")),
+            &DependencyGraphOptions::default(),
         )
     });
 }
@@ -187,6 +201,7 @@ fn bench_graph_socket2(b: &mut Bencher) {
             vec![],
             &Some(String::from("x86_64-unknown-linux-gnu")),
             &Some(String::from("unix")),
+            &DependencyGraphOptions::default(),
         )
     });
 }
@@ -199,6 +214,7 @@ fn bench_graph_rayon(b: &mut Bencher) {
             vec![],
             &Some(String::from("x86_64-unknown-linux-gnu")),
             &Some(String::from("unix")),
+            &DependencyGraphOptions::default(),
         )
     });
 }
@@ -211,6 +227,7 @@ fn bench_graph_diesel(b: &mut Bencher) {
             vec![],
             &Some(String::from("x86_64-unknown-linux-gnu")),
             &Some(String::from("unix")),
+            &DependencyGraphOptions::default(),
         )
     });
 }
@@ -223,6 +240,7 @@ fn bench_graph_sqlx(b: &mut Bencher) {
             vec![],
             &Some(String::from("x86_64-unknown-linux-gnu")),
             &Some(String::from("unix")),
+            &DependencyGraphOptions::default(),
         )
     });
 }
@@ -235,6 +253,30 @@ fn bench_graph_tokei(b: &mut Bencher) {
             vec![],
             &Some(String::from("x86_64-unknown-linux-gnu")),
             &Some(String::from("unix")),
+            &DependencyGraphOptions::default(),
         )
     });
 }
+
+#[bench]
+fn bench_graph_tokio_all_features_v2(b: &mut Bencher) {
+    b.iter(|| {
+        GRAPH.get_dependency_graph(
+            "tokio",
+            vec![],
+            &Some(String::from("x86_64-unknown-linux-gnu")),
+            &Some(String::from("unix")),
+            &DependencyGraphOptions {
+                all_features: true,
+                resolver: Resolver::V2,
+                potential_dependencies: Some(500),
+                ..DependencyGraphOptions::default()
+            },
+        )
+    });
+}
+
+#[bench]
+fn bench_graph_registry_cycles(b: &mut Bencher) {
+    b.iter(|| GRAPH.get_cycles(&[DependencyKind::Normal, DependencyKind::Build]));
+}